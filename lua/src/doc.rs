@@ -0,0 +1,361 @@
+use std::{cell::RefCell, rc::Rc};
+
+use mlua::{Lua, MetaMethod, Table, UserData, UserDataMethods, Value};
+use shared::types::uom::{nodes, AttributeValue, File, NodeId, NodeKind, UcreError};
+
+use crate::value::{error, from_lua, to_lua};
+
+/// SharedFile is the file a script runs against, shared by the `doc` global and all node handles
+pub type SharedFile = Rc<RefCell<File>>;
+
+/// the fields of a node handle that are no attributes
+const FIELDS: &[&str] = &["kind", "text", "children", "parent"];
+
+/// LuaNode is the handle of a node of the document passed to lua:
+///
+/// ```text
+/// heading = doc.queryId("hello-world")
+/// heading.text = heading.text .. " from lua"
+/// heading.level = 2
+/// heading.color = { color = "#f523ad" }
+/// heading.parent:append("paragraph", "below the heading")
+/// ```
+///
+/// `kind`, `text`, `children` and `parent` are fields of the node, `kind`, `children` and
+/// `parent` read only. Any other field is an attribute, setting it to nil removes it. Changes go
+/// through the mutating methods of the [shared::types::uom::Document], so they are recorded and
+/// validated as any other change.
+#[derive(Clone)]
+pub struct LuaNode {
+    file: SharedFile,
+    id: NodeId,
+}
+
+impl LuaNode {
+    fn new(file: &SharedFile, id: NodeId) -> Self {
+        LuaNode {
+            file: file.clone(),
+            id,
+        }
+    }
+
+    fn get<'lua>(&self, lua: &'lua Lua, key: &str) -> mlua::Result<Value<'lua>> {
+        let file = self.file.borrow();
+        let doc = file.doc();
+        let node = doc.get(self.id).ok_or_else(|| error("node was removed"))?;
+        Ok(match key {
+            "kind" => Value::String(lua.create_string(node.kind().name())?),
+            "text" => match node.text() {
+                Some(t) => Value::String(lua.create_string(t)?),
+                None => Value::Nil,
+            },
+            "children" => {
+                let children = doc
+                    .children(self.id)
+                    .iter()
+                    .map(|c| LuaNode::new(&self.file, *c));
+                Value::Table(lua.create_sequence_from(children)?)
+            }
+            // top-level nodes have no parent, the root is not exposed as a node
+            "parent" => match doc.parent(self.id).filter(|p| *p != doc.root()) {
+                Some(p) => Value::UserData(lua.create_userdata(LuaNode::new(&self.file, p))?),
+                None => Value::Nil,
+            },
+            key => match node.attr().get(key) {
+                Some(v) => to_lua(lua, v)?,
+                None => Value::Nil,
+            },
+        })
+    }
+
+    fn set(&self, key: &str, value: Value) -> mlua::Result<()> {
+        let mut file = self.file.borrow_mut();
+        let doc = file.doc_mut();
+        match key {
+            "text" => {
+                let text = match value {
+                    Value::String(s) => s.to_str()?.to_string(),
+                    Value::Nil => String::new(),
+                    v => {
+                        return Err(error(format!(
+                            "text has to be a string, got a {}",
+                            v.type_name()
+                        )))
+                    }
+                };
+                doc.set_text(self.id, text).map_err(ucre_error)
+            }
+            key if FIELDS.contains(&key) => Err(error(format!("{key} is read only"))),
+            key => match value {
+                Value::Nil => doc
+                    .remove_attr(self.id, key)
+                    .map(|_| ())
+                    .map_err(ucre_error),
+                v => doc
+                    .set_attr(self.id, key.to_string(), from_lua(v)?)
+                    .map_err(ucre_error),
+            },
+        }
+    }
+}
+
+impl UserData for LuaNode {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        // appends a new node of kind, optionally with text, returns it
+        methods.add_method(
+            "append",
+            |_, this, (kind, text): (String, Option<String>)| {
+                append(&this.file, this.id, &kind, text)
+            },
+        );
+        // removes the node and its descendants from the document
+        methods.add_method("remove", |_, this, ()| {
+            this.file
+                .borrow_mut()
+                .doc_mut()
+                .remove(this.id)
+                .map(|_| ())
+                .map_err(ucre_error)
+        });
+        methods.add_meta_method(MetaMethod::Index, |lua, this, key: String| {
+            this.get(lua, &key)
+        });
+        methods.add_meta_method(
+            MetaMethod::NewIndex,
+            |_, this, (key, value): (String, Value)| this.set(&key, value),
+        );
+        methods.add_meta_method(
+            MetaMethod::Eq,
+            |_, this, other: mlua::UserDataRef<LuaNode>| Ok(this.id == other.id),
+        );
+        // formatted like the nodes printed by `ucre query`
+        methods.add_meta_method(MetaMethod::ToString, |_, this, ()| {
+            let file = this.file.borrow();
+            let node = file
+                .doc()
+                .get(this.id)
+                .ok_or_else(|| error("node was removed"))?;
+            let mut s = node.kind().name().to_string();
+            if let Some(id) = node.attr().get("id").and_then(AttributeValue::as_str) {
+                s.push_str(&format!("#{id}"));
+            }
+            if let Some(text) = node.text().filter(|t| !t.is_empty()) {
+                s.push_str(&format!(" {}", AttributeValue::from(text)));
+            }
+            Ok(s)
+        });
+    }
+}
+
+/// LuaMeta is the handle of the meta-data of the document passed to lua, `doc.meta`:
+///
+/// ```text
+/// doc.meta.title = doc.meta.title .. " (draft)"
+/// doc.meta.keywords = { "hello", "world" }
+/// doc.meta.version = nil
+/// ```
+///
+/// Values are converted like attributes and validated like meta-data set by ucc, see
+/// [shared::types::uom::MetaData]. Setting `theme` switches the theme, see [File::set_theme].
+/// The limits of the sandbox are applied before the script runs, so `luaInstructions` and
+/// `luaMemory` are read only.
+#[derive(Clone)]
+pub struct LuaMeta {
+    file: SharedFile,
+}
+
+impl UserData for LuaMeta {
+    fn add_methods<'lua, M: UserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_meta_method(MetaMethod::Index, |lua, this, key: String| {
+            match this.file.borrow().meta_data().get(&key) {
+                Some(v) => to_lua(lua, v),
+                None => Ok(Value::Nil),
+            }
+        });
+        methods.add_meta_method(
+            MetaMethod::NewIndex,
+            |_, this, (key, value): (String, Value)| {
+                let mut file = this.file.borrow_mut();
+                match (key.as_str(), value) {
+                    ("luaInstructions" | "luaMemory", _) => {
+                        Err(error(format!("{key} is read only")))
+                    }
+                    ("theme", Value::Nil) => file.set_theme(None).map_err(ucre_error),
+                    ("theme", Value::String(s)) => {
+                        file.set_theme(Some(s.to_str()?)).map_err(ucre_error)
+                    }
+                    (_, Value::Nil) => {
+                        file.meta_data_mut().remove(&key);
+                        Ok(())
+                    }
+                    (_, v) => file
+                        .meta_data_mut()
+                        .set(key, from_lua(v)?)
+                        .map_err(ucre_error),
+                }
+            },
+        );
+    }
+}
+
+/// global creates the `doc` global of a script running against file:
+///
+/// - `doc.queryId(id)`: the node whose `id` attribute is id, nil if there is none
+/// - `doc.query(selector)`: all nodes matching selector in document order, see
+///   [shared::types::uom::Selector]
+/// - `doc.children()`: the top-level nodes
+/// - `doc.append(kind, text)`: appends a top-level node, see [LuaNode]
+/// - `doc.theme()` and `doc.setTheme(name)`: the style theme of the document, see
+///   [File::set_theme]
+/// - `doc.meta`: the meta-data of the document, see [LuaMeta]
+pub fn global<'lua>(lua: &'lua Lua, file: &SharedFile) -> mlua::Result<Table<'lua>> {
+    let doc = lua.create_table()?;
+    let f = file.clone();
+    doc.set(
+        "queryId",
+        lua.create_function(move |_, id: String| {
+            Ok(f.borrow()
+                .doc()
+                .query_id(&id)
+                .map(|id| LuaNode::new(&f, id)))
+        })?,
+    )?;
+    let f = file.clone();
+    doc.set(
+        "query",
+        lua.create_function(move |lua, selector: String| {
+            let ids = f
+                .borrow()
+                .doc()
+                .query(&selector)
+                .map_err(ucre_error)?
+                .collect::<Vec<_>>();
+            lua.create_sequence_from(ids.into_iter().map(|id| LuaNode::new(&f, id)))
+        })?,
+    )?;
+    let f = file.clone();
+    doc.set(
+        "children",
+        lua.create_function(move |lua, ()| {
+            let file = f.borrow();
+            let doc = file.doc();
+            lua.create_sequence_from(
+                doc.children(doc.root())
+                    .iter()
+                    .map(|c| LuaNode::new(&f, *c)),
+            )
+        })?,
+    )?;
+    let f = file.clone();
+    doc.set(
+        "append",
+        lua.create_function(move |_, (kind, text): (String, Option<String>)| {
+            let root = f.borrow().doc().root();
+            append(&f, root, &kind, text)
+        })?,
+    )?;
+    let f = file.clone();
+    doc.set(
+        "theme",
+        lua.create_function(move |_, ()| Ok(f.borrow().theme().map(String::from)))?,
+    )?;
+    let f = file.clone();
+    doc.set(
+        "setTheme",
+        lua.create_function(move |_, name: Option<String>| {
+            f.borrow_mut()
+                .set_theme(name.as_deref())
+                .map_err(ucre_error)
+        })?,
+    )?;
+    doc.set("meta", LuaMeta { file: file.clone() })?;
+    Ok(doc)
+}
+
+/// appends a new node of kind to parent, optionally with text
+fn append(
+    file: &SharedFile,
+    parent: NodeId,
+    kind: &str,
+    text: Option<String>,
+) -> mlua::Result<LuaNode> {
+    let kind =
+        NodeKind::resolve(kind).ok_or_else(|| error(format!("Invalid node kind '{kind}'")))?;
+    let mut node = nodes::new(kind);
+    if let Some(text) = text {
+        node.set_text(text).map_err(ucre_error)?;
+    }
+    let id = file
+        .borrow_mut()
+        .doc_mut()
+        .append(parent, node)
+        .map_err(ucre_error)?;
+    Ok(LuaNode::new(file, id))
+}
+
+/// ucre_error raises e in the calling script
+fn ucre_error(e: UcreError) -> mlua::Error {
+    error(e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use mlua::Lua;
+    use shared::types::uom::{nodes::Heading, Document, File, MetaData, Node};
+
+    use super::{global, SharedFile};
+
+    fn shared() -> SharedFile {
+        let mut heading = Heading::with_text(1, "a");
+        heading.set_attr("id".into(), "title".into());
+        heading.set_attr("tag".into(), true.into());
+        let doc = Document::from_nodes(vec![Box::new(heading)]).expect("failed to build document");
+        Rc::new(RefCell::new(File::new(MetaData::new(), String::new(), doc)))
+    }
+
+    fn exec(file: &SharedFile, source: &str) -> mlua::Result<()> {
+        let lua = Lua::new();
+        lua.globals().set("doc", global(&lua, file)?)?;
+        lua.load(source).exec()
+    }
+
+    #[test]
+    fn handles() {
+        let file = shared();
+        exec(
+            &file,
+            r#"
+            local h = doc.queryId("title")
+            assert(h == doc.children()[1] and h.kind == "heading")
+            h.text = nil
+            h.tag = nil
+            assert(h.text == "" and h.tag == nil)
+            h:remove()
+            assert(not pcall(function() return h.text end))
+            "#,
+        )
+        .expect("failed to run");
+        assert!(file.borrow().doc().is_empty());
+    }
+
+    #[test]
+    fn rejected() {
+        for source in [
+            "doc.queryId(\"title\").children = {}",
+            "doc.queryId(\"title\").text = 1",
+            "doc.queryId(\"title\").single = { 1 }",
+            "doc.queryId(\"title\").level = 0 / 0",
+            "doc.queryId(\"title\"):append(\"list-item\", \"a\")",
+        ] {
+            let file = shared();
+            assert!(exec(&file, source).is_err(), "{source}");
+            let file = file.borrow();
+            let heading = file.doc().children(file.doc().root())[0];
+            let heading = file.doc().get(heading).expect("heading exists");
+            assert_eq!(heading.attr().len(), 3, "{source}");
+            assert_eq!(heading.text(), Some("a"), "{source}");
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::{cell::RefCell, rc::Rc};
+
+use shared::types::uom::{Document, File, MetaData, UcreError};
+
+mod doc;
+mod sandbox;
+mod value;
+
+use sandbox::Limit;
+
+pub use doc::{LuaMeta, LuaNode};
+pub use sandbox::Sandbox;
+
+/// run executes the `lua [...]` blocks of file in the default [Sandbox], see [run_with]
+pub fn run(file: &mut File) -> Result<(), UcreError> {
+    run_with(file, &Sandbox::default())
+}
+
+/// run_with executes the `lua [...]` blocks of file in a fresh Lua 5.4 state restricted by
+/// sandbox, once its document is built. Scripts reach the document via the `doc` global, see
+/// [doc::global] and [LuaNode]. Changes made by the script are kept even if it fails later on.
+pub fn run_with(file: &mut File, sandbox: &Sandbox) -> Result<(), UcreError> {
+    if file.lua().trim().is_empty() {
+        return Ok(());
+    }
+    let source = file.lua().to_string();
+    let sandbox = sandbox.for_file(file);
+    // the file is shared with the handles of the script while it runs
+    let placeholder = File::new(MetaData::new(), String::new(), Document::new());
+    let shared = Rc::new(RefCell::new(std::mem::replace(file, placeholder)));
+    let result = exec(&shared, &source, &sandbox);
+    *file = Rc::try_unwrap(shared)
+        .ok()
+        .expect("handles are dropped with the lua state")
+        .into_inner();
+    result
+}
+
+fn exec(file: &doc::SharedFile, source: &str, sandbox: &Sandbox) -> Result<(), UcreError> {
+    let (lua, exceeded) = sandbox.create()?;
+    lua.globals()
+        .set("doc", doc::global(&lua, file).map_err(error)?)
+        .map_err(error)?;
+    lua.load(source).set_name("lua").exec().map_err(|e| {
+        let limit = match exceeded.get() {
+            Some(limit) => limit,
+            None if is_memory_error(&e) => Limit::Memory,
+            None => return error(e),
+        };
+        UcreError::lua(match limit {
+            Limit::Instructions => format!(
+                "lua: Script exceeded its limit of {} instructions and was terminated",
+                sandbox.instructions
+            ),
+            Limit::Memory => format!(
+                "lua: Script exceeded its limit of {} MiB of memory and was terminated",
+                sandbox.memory
+            ),
+            Limit::Time => format!(
+                "lua: Script exceeded its limit of {:?} and was terminated",
+                sandbox.time
+            ),
+        })
+    })
+}
+
+fn is_memory_error(e: &mlua::Error) -> bool {
+    match e {
+        mlua::Error::MemoryError(_) => true,
+        mlua::Error::CallbackError { cause, .. } => is_memory_error(cause),
+        _ => false,
+    }
+}
+
+fn error(e: mlua::Error) -> UcreError {
+    UcreError::lua(format!("lua: {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use shared::types::uom::{AttributeValue, ErrorKind, File, Unit};
+
+    use super::{run, run_with, Sandbox};
+
+    fn file(doc: &str, lua: &str) -> File {
+        let source = format!("doc {{\n{doc}\n}}\nlua [\n{lua}\n]");
+        ucc::parse(source.as_bytes()).expect("failed to parse")
+    }
+
+    #[test]
+    fn example() {
+        let mut file =
+            ucc::parse(include_bytes!("../../example/helloworld.ucre")).expect("failed to parse");
+        run(&mut file).expect("failed to run");
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.query_id("hello-world").expect("heading exists"))
+            .expect("heading exists");
+        assert_eq!(heading.text(), Some("Hello World from lua"));
+    }
+
+    #[test]
+    fn nodes() {
+        let mut file = file(
+            r#"heading {
+                id "title"
+                level 1
+                text "a"
+            }
+            container {
+                paragraph { text "b" }
+            }"#,
+            r#"
+            local h = doc.queryId("title")
+            assert(h.kind == "heading" and h.level == 1 and h.parent == nil)
+            assert(doc.queryId("missing") == nil)
+            h.level = 2
+            h.size = { length = 12, unit = "pt" }
+            h.text = tostring(h)
+            local c = doc.query("container")[1]
+            assert(#c.children == 1 and c.children[1].parent == c)
+            c.children[1]:remove()
+            c:append("paragraph", "c")
+            doc.append("paragraph", "d")
+            assert(#doc.children() == 3)
+            "#,
+        );
+        run(&mut file).expect("failed to run");
+        let doc = file.doc();
+        let [h, c, _] = doc.children(doc.root())[..] else {
+            panic!("expected three top-level nodes");
+        };
+        let heading = doc.get(h).expect("heading exists");
+        assert_eq!(heading.attr()["level"], AttributeValue::Number(2.0));
+        assert_eq!(
+            heading.attr()["size"],
+            AttributeValue::Length(12.0, Unit::Pt)
+        );
+        assert_eq!(heading.text(), Some(r#"heading#title "a""#));
+        let p = doc.children(c)[0];
+        assert_eq!(doc.get(p).and_then(|p| p.text()), Some("c"));
+    }
+
+    #[test]
+    fn themes() {
+        let source = "style {\n theme dark {\n accent white\n }\n}\n\
+                      lua [\n assert(doc.theme() == nil)\n doc.setTheme(\"dark\")\n]";
+        let mut file = ucc::parse(source.as_bytes()).expect("failed to parse");
+        run(&mut file).expect("failed to run");
+        assert_eq!(file.theme(), Some("dark"));
+    }
+
+    #[test]
+    fn meta_data() {
+        let source = "meta-data {\n title \"Hello\"\n version \"1.0\"\n}\n\
+                      style {\n theme dark {\n accent white\n }\n}\n\
+                      lua [\n\
+                      assert(doc.meta.title == \"Hello\" and doc.meta.author == nil)\n\
+                      doc.meta.title = doc.meta.title .. \" World\"\n\
+                      doc.meta.keywords = { \"a\", \"b\" }\n\
+                      doc.meta.version = nil\n\
+                      doc.meta.theme = \"dark\"\n\
+                      ]";
+        let mut parsed = ucc::parse(source.as_bytes()).expect("failed to parse");
+        run(&mut parsed).expect("failed to run");
+        let meta_data = parsed.meta_data();
+        assert_eq!(meta_data.title(), Some("Hello World"));
+        assert_eq!(meta_data.keywords(), vec!["a", "b"]);
+        assert_eq!(meta_data.version(), None);
+        assert_eq!(parsed.theme(), Some("dark"));
+
+        for lua in [
+            "doc.meta.language = \"not a tag!\"",
+            "doc.meta.date = \"yesterday\"",
+            "doc.meta.theme = \"light\"",
+            "doc.meta.luaInstructions = 1",
+        ] {
+            let mut file = file("", lua);
+            let err = run(&mut file).expect_err(lua);
+            assert_eq!(err.kind(), ErrorKind::Lua, "{lua}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        for lua in [
+            "print heading",
+            "doc.queryId(\"title\").kind = \"paragraph\"",
+            "doc.queryId(\"title\").level = print",
+            "doc.append(\"Blink!\")",
+            "doc.query(\">>\")",
+            "doc.setTheme(\"dark\")",
+            "error(\"boom\")",
+        ] {
+            let mut file = file("heading {\n id \"title\"\n level 1\n}", lua);
+            let err = run(&mut file).expect_err(lua);
+            assert_eq!(err.kind(), ErrorKind::Lua, "{lua}");
+        }
+    }
+
+    #[test]
+    fn sandbox() {
+        for lua in [
+            "assert(io == nil and require == nil and debug == nil and package == nil)",
+            "assert(loadfile == nil and dofile == nil and os.execute == nil and os.getenv == nil)",
+            "assert(os.time() > 0 and load(\"return 1\")() == 1)",
+            "assert(load(\"return math.pi\")() == math.pi)",
+            "assert(load(\"return math\", \"chunk\", \"t\", {})() == nil)",
+            "assert(load(string.dump(function() end)) == nil)",
+        ] {
+            let mut file = file("", lua);
+            run(&mut file).unwrap_or_else(|e| panic!("{lua}: {e}"));
+        }
+
+        let slow = Sandbox {
+            instructions: 100_000,
+            ..Default::default()
+        };
+        let small = Sandbox {
+            memory: 8,
+            ..Default::default()
+        };
+        for (lua, sandbox, limit) in [
+            ("while true do end", slow, "instructions"),
+            (
+                "while true do pcall(function() while true do end end) end",
+                slow,
+                "instructions",
+            ),
+            (
+                "local t = {} for i = 1, 1e8 do t[i] = i end",
+                small,
+                "memory",
+            ),
+            (
+                "local s = string.rep(\"x\", 16 * 1024 * 1024)",
+                small,
+                "memory",
+            ),
+            (
+                "while true do pcall(string.rep, \"x\", 1e9) end",
+                small,
+                "memory",
+            ),
+            (
+                "local s = string.rep(\"x\", 2 * 1024 * 1024)",
+                Sandbox {
+                    memory: 0,
+                    ..Default::default()
+                },
+                "memory",
+            ),
+            (
+                "while true do end",
+                Sandbox {
+                    time: Duration::from_millis(50),
+                    ..Default::default()
+                },
+                "50ms",
+            ),
+        ] {
+            let mut file = file("", lua);
+            let err = run_with(&mut file, &sandbox).expect_err(lua);
+            assert_eq!(err.kind(), ErrorKind::Lua, "{lua}");
+            assert!(err.to_string().contains(limit), "{lua}: {err}");
+        }
+
+        // documents may lower the limits, but not raise them
+        let mut file = ucc::parse(b"meta-data {\n luaInstructions 10\n luaMemory 1024\n}")
+            .expect("failed to parse");
+        let limits = small.for_file(&file);
+        assert_eq!((limits.instructions, limits.memory), (10, 8));
+        assert!(run_with(&mut file, &small).is_ok());
+    }
+}
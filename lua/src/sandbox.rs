@@ -0,0 +1,176 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use mlua::{HookTriggers, Lua, LuaOptions, MultiValue, StdLib, Value};
+use shared::types::uom::{File, UcreError};
+
+use crate::value::error;
+
+/// the number of instructions between two checks of the instruction limit
+const CHECK_EVERY: u32 = 1000;
+
+/// MIB is the number of bytes in a MiB, the unit of [Sandbox::memory]
+const MIB: usize = 1024 * 1024;
+
+/// the message lua raises when an allocation fails
+const MEMORY_ERROR: &[u8] = b"not enough memory";
+
+/// Limit is a limit of the [Sandbox] a script exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Limit {
+    Instructions,
+    Memory,
+    Time,
+}
+
+impl Limit {
+    fn error(self) -> mlua::Error {
+        error(match self {
+            Limit::Instructions => "instruction limit exceeded",
+            Limit::Memory => "memory limit exceeded",
+            Limit::Time => "time limit exceeded",
+        })
+    }
+}
+
+/// Exceeded is set to the limit a script exceeded, once it did
+pub(crate) type Exceeded = Rc<Cell<Option<Limit>>>;
+
+/// Sandbox restricts what the lua of a document may do and how many resources it may use.
+///
+/// Untrusted scripts only get the `coroutine`, `table`, `string`, `utf8` and `math` libraries and
+/// `os.time`, `os.clock`, `os.date` and `os.difftime`. `io`, the rest of `os`, `require`,
+/// `loadfile`, `dofile` and `debug` are missing, `load` only accepts source code. A script
+/// running more instructions, allocating more memory or running longer than allowed is
+/// terminated, neither `pcall` nor `xpcall` or `coroutine.resume` keep it alive.
+///
+/// Instructions and time are checked between instructions of the script, a single call into the
+/// standard library, such as `string.find` backtracking on a pathological pattern, is not
+/// interrupted but the script is terminated once the call returns.
+///
+/// Documents may lower the limits via the `luaInstructions` and `luaMemory` meta-data, see
+/// [Sandbox::for_file], but never raise them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sandbox {
+    /// the number of instructions a script may run
+    pub instructions: u64,
+    /// the memory in MiB a script may use, at least 1 MiB
+    pub memory: u64,
+    /// the wall-clock time a script may run
+    pub time: Duration,
+    /// whether the script gets all standard libraries, only for documents of trusted authors
+    pub trusted: bool,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Sandbox {
+            instructions: 100_000_000,
+            memory: 64,
+            time: Duration::from_secs(10),
+            trusted: false,
+        }
+    }
+}
+
+impl Sandbox {
+    /// the sandbox for file, its limits lowered to those asked for in its meta-data
+    pub fn for_file(&self, file: &File) -> Sandbox {
+        let meta_data = file.meta_data();
+        Sandbox {
+            instructions: meta_data
+                .lua_instructions()
+                .map_or(self.instructions, |n| n.min(self.instructions)),
+            memory: meta_data
+                .lua_memory()
+                .map_or(self.memory, |n| n.min(self.memory)),
+            time: self.time,
+            trusted: self.trusted,
+        }
+    }
+
+    /// creates a lua state restricted by the sandbox, the returned flag is set once the script
+    /// exceeded one of the limits
+    pub(crate) fn create(&self) -> Result<(Lua, Exceeded), UcreError> {
+        let lua = if self.trusted {
+            // SAFETY: trusted scripts are allowed to use the debug library and c modules
+            unsafe { Lua::unsafe_new() }
+        } else {
+            let libs = StdLib::COROUTINE
+                | StdLib::TABLE
+                | StdLib::STRING
+                | StdLib::UTF8
+                | StdLib::MATH
+                | StdLib::OS;
+            Lua::new_with(libs, LuaOptions::new()).map_err(crate::error)?
+        };
+        // lua treats 0 and limits beyond isize::MAX as no limit at all
+        let memory = usize::try_from(self.memory)
+            .unwrap_or(usize::MAX)
+            .saturating_mul(MIB)
+            .clamp(MIB, isize::MAX as usize);
+        lua.set_memory_limit(memory).map_err(crate::error)?;
+
+        let exceeded: Exceeded = Rc::new(Cell::new(None));
+        let (limit, ran) = (self.instructions, Cell::new(0u64));
+        let (time, start) = (self.time, Instant::now());
+        let flag = exceeded.clone();
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(CHECK_EVERY),
+            move |_, _| {
+                ran.set(ran.get() + CHECK_EVERY as u64);
+                if flag.get().is_none() && ran.get() > limit {
+                    flag.set(Some(Limit::Instructions));
+                } else if flag.get().is_none() && start.elapsed() > time {
+                    flag.set(Some(Limit::Time));
+                }
+                // raised again on every check, so a caught error does not keep the script alive
+                flag.get().map_or(Ok(()), |l| Err(l.error()))
+            },
+        );
+        if !self.trusted {
+            restrict(&lua, &exceeded).map_err(crate::error)?;
+        }
+        Ok((lua, exceeded))
+    }
+}
+
+/// PRELUDE removes what scripts must not reach from the globals, it is passed a function
+/// raising an error once a limit is exceeded and returning its arguments otherwise
+const PRELUDE: &str = r##"
+local check = ...
+local load, pcall, xpcall, resume = load, pcall, xpcall, coroutine.resume
+loadfile, dofile = nil, nil
+os = { time = os.time, clock = os.clock, date = os.date, difftime = os.difftime }
+-- only source code, precompiled chunks can crash the interpreter
+_G.load = function(...)
+  local chunk, name, _, env = ...
+  -- an explicit nil env leaves the chunk without globals
+  if select("#", ...) >= 4 then return load(chunk, name, "t", env) end
+  return load(chunk, name, "t")
+end
+-- errors are caught as usual, unless a limit is exceeded
+_G.pcall = function(...) return check(pcall(...)) end
+_G.xpcall = function(...) return check(xpcall(...)) end
+coroutine.resume = function(...) return check(resume(...)) end
+"##;
+
+fn restrict(lua: &Lua, exceeded: &Exceeded) -> mlua::Result<()> {
+    let flag = exceeded.clone();
+    let check = lua.create_function(move |_, results: MultiValue| {
+        // a failed allocation is caught like any other error, remember it to raise it again
+        let mut values = results.iter();
+        if let (Some(Value::Boolean(false)), Some(Value::String(msg))) =
+            (values.next(), values.next())
+        {
+            if flag.get().is_none() && msg.as_bytes() == MEMORY_ERROR {
+                flag.set(Some(Limit::Memory));
+            }
+        }
+        flag.get().map_or(Ok(results), |l| Err(l.error()))
+    })?;
+    lua.load(PRELUDE).set_name("sandbox").call(check)
+}
@@ -0,0 +1,131 @@
+use mlua::{Lua, Table, Value};
+use shared::types::uom::{AttributeValue, Color, Unit};
+
+/// to_lua converts v into a lua value, mirroring the json schema of attribute values: strings,
+/// numbers and booleans map onto their lua counterparts, lists onto sequences and all other
+/// values onto tables such as `{ length = 12, unit = "pt" }`, `{ color = "#f523ad" }`,
+/// `{ ref = "id" }`, `{ var = "name" }` and `{ keyword = "left" }`
+pub fn to_lua<'lua>(lua: &'lua Lua, v: &AttributeValue) -> mlua::Result<Value<'lua>> {
+    Ok(match v {
+        AttributeValue::String(s) => Value::String(lua.create_string(s)?),
+        AttributeValue::Number(n) => Value::Number(*n),
+        AttributeValue::Boolean(b) => Value::Boolean(*b),
+        AttributeValue::List(l) => Value::Table(
+            lua.create_sequence_from(
+                l.iter()
+                    .map(|v| to_lua(lua, v))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )?,
+        ),
+        AttributeValue::Length(n, u) => {
+            let t = lua.create_table()?;
+            t.set("length", *n)?;
+            t.set("unit", u.suffix())?;
+            Value::Table(t)
+        }
+        AttributeValue::Color(c) => {
+            Value::Table(lua.create_table_from([("color", c.to_string())])?)
+        }
+        AttributeValue::NodeRef(r) => Value::Table(lua.create_table_from([("ref", r.as_str())])?),
+        AttributeValue::Var(v) => Value::Table(lua.create_table_from([("var", v.as_str())])?),
+        AttributeValue::Keyword(k) => {
+            Value::Table(lua.create_table_from([("keyword", k.as_str())])?)
+        }
+    })
+}
+
+/// from_lua converts v back into an attribute value, see [to_lua]
+pub fn from_lua(v: Value) -> mlua::Result<AttributeValue> {
+    Ok(match v {
+        Value::String(s) => AttributeValue::String(s.to_str()?.to_string()),
+        Value::Integer(n) => AttributeValue::Number(n as f64),
+        Value::Number(n) => AttributeValue::Number(n),
+        Value::Boolean(b) => AttributeValue::Boolean(b),
+        Value::Table(t) => table(t)?,
+        v => {
+            return Err(error(format!(
+                "a {} is no valid attribute value",
+                v.type_name()
+            )))
+        }
+    })
+}
+
+fn table(t: Table) -> mlua::Result<AttributeValue> {
+    if let Some(n) = t.get::<_, Option<f64>>("length")? {
+        let unit = t.get::<_, Option<String>>("unit")?.unwrap_or_default();
+        let u = Unit::from_suffix(&unit).ok_or_else(|| error(format!("Unknown unit '{unit}'")))?;
+        return Ok(AttributeValue::Length(n, u));
+    }
+    if let Some(c) = t.get::<_, Option<String>>("color")? {
+        return c
+            .strip_prefix('#')
+            .and_then(Color::from_hex)
+            .map(AttributeValue::Color)
+            .ok_or_else(|| error(format!("Invalid color '{c}'")));
+    }
+    if let Some(r) = t.get::<_, Option<String>>("ref")? {
+        return Ok(AttributeValue::NodeRef(r));
+    }
+    if let Some(v) = t.get::<_, Option<String>>("var")? {
+        return Ok(AttributeValue::Var(v));
+    }
+    if let Some(k) = t.get::<_, Option<String>>("keyword")? {
+        return Ok(AttributeValue::Keyword(k));
+    }
+    t.sequence_values::<Value>()
+        .map(|v| from_lua(v?))
+        .collect::<Result<_, _>>()
+        .map(AttributeValue::List)
+}
+
+/// error creates a lua runtime error, raised in the script calling into the document
+pub fn error(msg: impl Into<String>) -> mlua::Error {
+    mlua::Error::RuntimeError(msg.into())
+}
+
+#[cfg(test)]
+mod test {
+    use mlua::{Lua, Value};
+    use shared::types::uom::{AttributeValue, Color, Unit};
+
+    use super::{from_lua, to_lua};
+
+    #[test]
+    fn round_trip() {
+        let lua = Lua::new();
+        for v in [
+            AttributeValue::String("a \"b\"".into()),
+            AttributeValue::Number(-0.5),
+            AttributeValue::Boolean(true),
+            AttributeValue::Length(12.0, Unit::Pt),
+            AttributeValue::Color(Color::rgba(0xf5, 0x23, 0xad, 0x80)),
+            AttributeValue::NodeRef("hello".into()),
+            AttributeValue::Var("accent".into()),
+            AttributeValue::Keyword("left".into()),
+            AttributeValue::List(vec![1.0.into(), "a".into()]),
+        ] {
+            let converted = to_lua(&lua, &v).and_then(from_lua);
+            assert_eq!(converted.ok(), Some(v));
+        }
+    }
+
+    #[test]
+    fn from_script() {
+        let lua = Lua::new();
+        let eval = |s: &str| from_lua(lua.load(s).eval::<Value>()?);
+        assert_eq!(eval("return 3").ok(), Some(AttributeValue::Number(3.0)));
+        assert_eq!(
+            eval(r#"return { length = 50, unit = "%" }"#).ok(),
+            Some(AttributeValue::Length(50.0, Unit::Percent))
+        );
+        assert_eq!(
+            eval(r##"return { color = "#fff" }"##).ok(),
+            Some(AttributeValue::Color(Color::rgba(255, 255, 255, 255)))
+        );
+        assert!(eval(r#"return { length = 1, unit = "furlong" }"#).is_err());
+        assert!(eval(r#"return { color = "red" }"#).is_err());
+        assert!(eval("return print").is_err());
+        assert!(eval("return nil").is_err());
+    }
+}
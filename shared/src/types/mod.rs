@@ -1,2 +1,3 @@
+pub mod style;
 pub mod token;
 pub mod uom;
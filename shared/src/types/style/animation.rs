@@ -0,0 +1,566 @@
+use std::collections::HashMap;
+
+use super::{Cascade, ComputedStyle, ComputedStyles};
+use crate::types::uom::{AttributeValue, Color, NodeId, Unit};
+
+/// Easing maps the progress of a transition to the progress of its values, the named timing
+/// functions of CSS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Easing {
+    Linear,
+    Ease,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "linear" => Easing::Linear,
+            "ease" => Easing::Ease,
+            "ease-in" => Easing::EaseIn,
+            "ease-out" => Easing::EaseOut,
+            "ease-in-out" => Easing::EaseInOut,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::Ease => "ease",
+            Easing::EaseIn => "ease-in",
+            Easing::EaseOut => "ease-out",
+            Easing::EaseInOut => "ease-in-out",
+        }
+    }
+
+    /// eases the progress t, clamped to 0 to 1
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        let (x1, y1, x2, y2) = match self {
+            Easing::Linear => return t,
+            Easing::Ease => (0.25, 0.1, 0.25, 1.0),
+            Easing::EaseIn => (0.42, 0.0, 1.0, 1.0),
+            Easing::EaseOut => (0.0, 0.0, 0.58, 1.0),
+            Easing::EaseInOut => (0.42, 0.0, 0.58, 1.0),
+        };
+        // the curve from (0, 0) to (1, 1) is monotonic in x, bisect for the parameter at x = t
+        let bezier = |p1: f64, p2: f64, s: f64| {
+            3.0 * p1 * s * (1.0 - s).powi(2) + 3.0 * p2 * s * s * (1.0 - s) + s.powi(3)
+        };
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..48 {
+            let mid = (lo + hi) / 2.0;
+            if bezier(x1, x2, mid) < t {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        bezier(y1, y2, (lo + hi) / 2.0)
+    }
+}
+
+/// duration of v in ms, None if v is no duration
+pub fn duration(v: &AttributeValue) -> Option<f64> {
+    match v {
+        AttributeValue::Length(n, Unit::Ms) => Some(*n),
+        AttributeValue::Length(n, Unit::S) => Some(n * 1000.0),
+        _ => None,
+    }
+}
+
+/// interpolate computes the value at t between from and to. Numbers, lengths of the same unit,
+/// colors and lists of the same length are interpolated, all other values switch from from to to
+/// halfway through.
+pub fn interpolate(from: &AttributeValue, to: &AttributeValue, t: f64) -> AttributeValue {
+    let lerp = |a: f64, b: f64| a + (b - a) * t;
+    match (from, to) {
+        (AttributeValue::Number(a), AttributeValue::Number(b)) => {
+            AttributeValue::Number(lerp(*a, *b))
+        }
+        (AttributeValue::Length(a, u), AttributeValue::Length(b, v)) if u == v => {
+            AttributeValue::Length(lerp(*a, *b), *u)
+        }
+        (AttributeValue::List(a), AttributeValue::List(b)) if a.len() == b.len() => {
+            AttributeValue::List(a.iter().zip(b).map(|(a, b)| interpolate(a, b, t)).collect())
+        }
+        _ => match (color(from), color(to)) {
+            (Some(a), Some(b)) => {
+                let channel =
+                    |a: u8, b: u8| lerp(a as f64, b as f64).round().clamp(0.0, 255.0) as u8;
+                AttributeValue::Color(Color::rgba(
+                    channel(a.r, b.r),
+                    channel(a.g, b.g),
+                    channel(a.b, b.b),
+                    channel(a.a, b.a),
+                ))
+            }
+            _ if t < 0.5 => from.clone(),
+            _ => to.clone(),
+        },
+    }
+}
+
+fn color(v: &AttributeValue) -> Option<Color> {
+    match v {
+        AttributeValue::Color(c) => Some(*c),
+        AttributeValue::String(s) | AttributeValue::Keyword(s) => Color::parse(s),
+        _ => None,
+    }
+}
+
+/// Transition is the `transition` property of a node, making changes of properties take effect
+/// gradually:
+///
+/// ```text
+/// transition color fontSize 200ms ease-in 50ms
+/// ```
+///
+/// The properties to transition, `all` for every property, followed by the duration, an optional
+/// easing defaulting to `ease` and an optional delay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub properties: Vec<String>,
+    /// in ms
+    pub duration: f64,
+    pub easing: Easing,
+    /// in ms
+    pub delay: f64,
+}
+
+impl Transition {
+    /// the transition of style, None if it sets none or its value is malformed
+    pub fn of(style: &ComputedStyle) -> Option<Self> {
+        let values = match style.get("transition")? {
+            AttributeValue::List(l) => l.as_slice(),
+            v => std::slice::from_ref(v),
+        };
+        let mut transition = Transition {
+            properties: vec![],
+            duration: 0.0,
+            easing: Easing::Ease,
+            delay: 0.0,
+        };
+        let mut durations = 0;
+        for v in values {
+            match (v, duration(v)) {
+                (_, Some(ms)) if durations == 0 => transition.duration = ms,
+                (_, Some(ms)) if durations == 1 => transition.delay = ms,
+                (AttributeValue::String(p) | AttributeValue::Keyword(p), None)
+                    if durations == 0 =>
+                {
+                    transition.properties.push(p.clone());
+                    continue;
+                }
+                (AttributeValue::String(e) | AttributeValue::Keyword(e), None) => {
+                    transition.easing = Easing::from_name(e)?;
+                    continue;
+                }
+                _ => return None,
+            }
+            durations += 1;
+        }
+        (durations > 0 && !transition.properties.is_empty()).then_some(transition)
+    }
+
+    pub fn applies_to(&self, property: &str) -> bool {
+        property != "transition"
+            && property != "animation"
+            && self.properties.iter().any(|p| p == "all" || p == property)
+    }
+}
+
+/// Animation is the `animation` property of a node, running the keyframes of the stylesheet
+/// called name:
+///
+/// ```text
+/// animation pulse 2s ease-in-out infinite 500ms
+/// ```
+///
+/// The name of the keyframes, the duration of an iteration, an optional easing defaulting to
+/// `ease`, an optional number of iterations defaulting to 1 or `infinite` and an optional delay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub name: String,
+    /// of a single iteration, in ms
+    pub duration: f64,
+    pub easing: Easing,
+    /// [f64::INFINITY] for `infinite`
+    pub iterations: f64,
+    /// in ms
+    pub delay: f64,
+}
+
+impl Animation {
+    /// the animation of style, None if it sets none or its value is malformed
+    pub fn of(style: &ComputedStyle) -> Option<Self> {
+        let AttributeValue::List(values) = style.get("animation")? else {
+            return None;
+        };
+        let (AttributeValue::String(name) | AttributeValue::Keyword(name), Some(ms)) =
+            (values.first()?, duration(values.get(1)?))
+        else {
+            return None;
+        };
+        let mut animation = Animation {
+            name: name.clone(),
+            duration: ms,
+            easing: Easing::Ease,
+            iterations: 1.0,
+            delay: 0.0,
+        };
+        for v in &values[2..] {
+            match v {
+                AttributeValue::String(s) | AttributeValue::Keyword(s) if s == "infinite" => {
+                    animation.iterations = f64::INFINITY
+                }
+                AttributeValue::String(e) | AttributeValue::Keyword(e) => {
+                    animation.easing = Easing::from_name(e)?
+                }
+                AttributeValue::Number(n) if *n >= 0.0 => animation.iterations = *n,
+                v => animation.delay = duration(v)?,
+            }
+        }
+        Some(animation)
+    }
+
+    /// the progress of the current iteration elapsed ms after the animation started, None
+    /// before its delay passed and once all iterations ran
+    pub fn progress(&self, elapsed: f64) -> Option<f64> {
+        let elapsed = elapsed - self.delay;
+        if elapsed < 0.0 || self.duration <= 0.0 {
+            return None;
+        }
+        let iteration = elapsed / self.duration;
+        (iteration < self.iterations).then(|| iteration.fract())
+    }
+}
+
+/// Running is a transition of a single property in progress
+#[derive(Debug, Clone)]
+struct Running {
+    from: AttributeValue,
+    to: AttributeValue,
+    start: f64,
+    duration: f64,
+    easing: Easing,
+}
+
+impl Running {
+    fn at(&self, now: f64) -> AttributeValue {
+        let t = match self.duration {
+            d if d <= 0.0 => 1.0,
+            d => (now - self.start) / d,
+        };
+        interpolate(&self.from, &self.to, self.easing.apply(t))
+    }
+
+    fn is_done(&self, now: f64) -> bool {
+        now >= self.start + self.duration
+    }
+}
+
+/// Animator drives the [Transition]s and [Animation]s of a document. It keeps the styles computed
+/// by the [Cascade] as targets and yields the styles of a point in time via [Animator::sample].
+///
+/// Time is passed in by the caller in ms, e.g. the time since a renderer started. After restyling
+/// the document, the new styles are handed to [Animator::update], which starts a transition for
+/// each property that changed and transitions on its node.
+#[derive(Debug, Clone, Default)]
+pub struct Animator {
+    targets: ComputedStyles,
+    transitions: HashMap<(NodeId, String), Running>,
+    /// the keyframes running on a node and when they started
+    animations: HashMap<NodeId, (String, f64)>,
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the styles computed by the cascade, transitions and animations run towards them
+    pub fn targets(&self) -> &ComputedStyles {
+        &self.targets
+    }
+
+    /// replaces the target styles with styles at now
+    pub fn update(&mut self, styles: ComputedStyles, now: f64) {
+        self.transitions
+            .retain(|(id, _), r| !r.is_done(now) && styles.get(*id).is_some());
+        for (id, style) in styles.iter() {
+            match Animation::of(style) {
+                Some(a) if self.animations.get(&id).is_some_and(|(n, _)| *n == a.name) => {}
+                Some(a) => {
+                    self.animations.insert(id, (a.name, now));
+                }
+                None => {
+                    self.animations.remove(&id);
+                }
+            }
+            let Some(previous) = self.targets.get(id) else {
+                continue;
+            };
+            let transition = Transition::of(style);
+            for (property, to) in style.iter() {
+                let key = (id, property.to_string());
+                let Some(from) = previous.get(property).filter(|from| *from != to) else {
+                    continue;
+                };
+                match &transition {
+                    Some(t) if t.applies_to(property) => {
+                        let from = match self.transitions.get(&key) {
+                            Some(r) => r.at(now),
+                            None => from.clone(),
+                        };
+                        let running = Running {
+                            from,
+                            to: to.clone(),
+                            start: now + t.delay,
+                            duration: t.duration,
+                            easing: t.easing,
+                        };
+                        self.transitions.insert(key, running);
+                    }
+                    _ => {
+                        self.transitions.remove(&key);
+                    }
+                }
+            }
+        }
+        self.animations.retain(|id, _| styles.get(*id).is_some());
+        self.targets = styles;
+    }
+
+    /// the styles at now, keyframe values are resolved by cascade
+    pub fn sample(&self, cascade: &Cascade, now: f64) -> ComputedStyles {
+        let mut styles = self.targets.clone();
+        for ((id, property), running) in &self.transitions {
+            if let Some(style) = styles.get_mut(*id) {
+                style.set(property, running.at(now));
+            }
+        }
+        for (id, (name, start)) in &self.animations {
+            let (Some(target), Some(keyframes)) = (self.targets.get(*id), cascade.keyframes(name))
+            else {
+                continue;
+            };
+            let Some(animation) = Animation::of(target) else {
+                continue;
+            };
+            let Some(progress) = animation.progress(now - start) else {
+                continue;
+            };
+            let Some(style) = styles.get_mut(*id) else {
+                continue;
+            };
+            let mut properties = keyframes
+                .frames
+                .iter()
+                .flat_map(|f| f.declarations.iter().map(|d| d.property.as_str()))
+                .collect::<Vec<_>>();
+            properties.sort_unstable();
+            properties.dedup();
+            for property in properties {
+                // frames setting property, the value of the target stands in for missing 0% and
+                // 100% frames
+                let mut frames = keyframes
+                    .frames
+                    .iter()
+                    .filter_map(|f| {
+                        let d = f
+                            .declarations
+                            .iter()
+                            .rev()
+                            .find(|d| d.property == property)?;
+                        Some((f.offset / 100.0, cascade.resolve_value(&d.value, target)?))
+                    })
+                    .collect::<Vec<_>>();
+                frames.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let base = target.get(property).cloned();
+                if let Some(base) = base.filter(|_| frames.first().is_none_or(|f| f.0 > 0.0)) {
+                    frames.insert(0, (0.0, base));
+                }
+                let base = target.get(property).cloned();
+                if let Some(base) = base.filter(|_| frames.last().is_none_or(|f| f.0 < 1.0)) {
+                    frames.push((1.0, base));
+                }
+                let Some(i) = frames.iter().rposition(|f| f.0 <= progress) else {
+                    continue;
+                };
+                let value = match frames.get(i + 1) {
+                    Some(next) => {
+                        let (from, to) = (&frames[i], next);
+                        let t = (progress - from.0) / (to.0 - from.0);
+                        interpolate(&from.1, &to.1, animation.easing.apply(t))
+                    }
+                    None => frames[i].1.clone(),
+                };
+                style.set(property, value);
+            }
+        }
+        styles
+    }
+
+    /// checks whether a transition or animation is running at now, a renderer only has to redraw
+    /// while one is
+    pub fn is_animating(&self, now: f64) -> bool {
+        self.transitions.values().any(|r| !r.is_done(now))
+            || self.animations.iter().any(|(id, (_, start))| {
+                self.targets
+                    .get(*id)
+                    .and_then(Animation::of)
+                    .is_some_and(|a| a.delay + a.duration * a.iterations > now - start)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{
+        style::{Cascade, Declaration, Keyframe, Keyframes, Rule, Stylesheet},
+        uom::{nodes::Heading, AttributeValue, Color, Document, NodeKind, Unit},
+    };
+
+    use super::{interpolate, Animation, Animator, Easing, Transition};
+
+    fn px(n: f64) -> AttributeValue {
+        AttributeValue::Length(n, Unit::Px)
+    }
+
+    #[test]
+    fn easing_and_interpolation() {
+        assert_eq!(Easing::Linear.apply(0.3), 0.3);
+        for e in [
+            Easing::Ease,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert!(e.apply(0.0).abs() < 1e-9);
+            assert!((e.apply(1.0) - 1.0).abs() < 1e-9);
+        }
+        assert!((Easing::EaseInOut.apply(0.5) - 0.5).abs() < 1e-6);
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+
+        assert_eq!(interpolate(&px(0.0), &px(10.0), 0.5), px(5.0));
+        assert_eq!(
+            interpolate(
+                &"black".into(),
+                &AttributeValue::Color(Color::rgba(255, 255, 255, 255)),
+                0.5
+            ),
+            AttributeValue::Color(Color::rgba(128, 128, 128, 255))
+        );
+        assert_eq!(
+            interpolate(&"left".into(), &"right".into(), 0.4),
+            "left".into()
+        );
+        assert_eq!(
+            interpolate(&px(0.0), &AttributeValue::Length(1.0, Unit::Em), 0.6),
+            AttributeValue::Length(1.0, Unit::Em)
+        );
+    }
+
+    #[test]
+    fn transitions() {
+        let mut heading = Rule::new(NodeKind::Heading);
+        heading.declarations = vec![
+            Declaration::new("width", px(0.0)),
+            Declaration::new(
+                "transition",
+                AttributeValue::List(vec![
+                    "width".into(),
+                    AttributeValue::Length(0.1, Unit::S),
+                    "linear".into(),
+                ]),
+            ),
+        ];
+        let mut style = Stylesheet {
+            rules: vec![heading],
+            ..Default::default()
+        };
+        let doc = Document::from_nodes(vec![Box::new(Heading::with_text(1, "a"))])
+            .expect("failed to build document");
+        let h = doc.children(doc.root())[0];
+        let cascade = Cascade::new(&style);
+        let mut animator = Animator::new();
+        animator.update(cascade.compute(&doc), 0.0);
+        assert!(!animator.is_animating(0.0));
+        assert_eq!(
+            Transition::of(cascade.compute(&doc).get(h).expect("styled")).map(|t| t.duration),
+            Some(100.0)
+        );
+
+        style.rules[0].declarations[0] = Declaration::new("width", px(10.0));
+        let cascade = Cascade::new(&style);
+        animator.update(cascade.compute(&doc), 1000.0);
+        assert!(animator.is_animating(1050.0));
+        let width = |now| {
+            animator
+                .sample(&cascade, now)
+                .get(h)
+                .and_then(|s| s.px("width", 0.0))
+        };
+        assert_eq!(width(1000.0), Some(0.0));
+        assert_eq!(width(1050.0), Some(5.0));
+        assert_eq!(width(1200.0), Some(10.0));
+        assert!(!animator.is_animating(1100.0));
+    }
+
+    #[test]
+    fn keyframes() {
+        let mut heading = Rule::new(NodeKind::Heading);
+        heading.declarations = vec![
+            Declaration::new("width", px(0.0)),
+            Declaration::new(
+                "animation",
+                AttributeValue::List(vec![
+                    "grow".into(),
+                    AttributeValue::Length(100.0, Unit::Ms),
+                    "linear".into(),
+                    AttributeValue::Number(2.0),
+                ]),
+            ),
+        ];
+        let style = Stylesheet {
+            rules: vec![heading],
+            keyframes: vec![Keyframes {
+                name: "grow".into(),
+                frames: vec![Keyframe {
+                    offset: 50.0,
+                    declarations: vec![Declaration::new(
+                        "width",
+                        AttributeValue::Length(1.0, Unit::Em),
+                    )],
+                }],
+            }],
+            ..Default::default()
+        };
+        let doc = Document::from_nodes(vec![Box::new(Heading::with_text(1, "a"))])
+            .expect("failed to build document");
+        let h = doc.children(doc.root())[0];
+        let cascade = Cascade::new(&style);
+        let styles = cascade.compute(&doc);
+        let animation = Animation::of(styles.get(h).expect("styled")).expect("animated");
+        assert_eq!(animation.iterations, 2.0);
+        let mut animator = Animator::new();
+        animator.update(styles, 0.0);
+        let width = |now| {
+            animator
+                .sample(&cascade, now)
+                .get(h)
+                .and_then(|s| s.px("width", 0.0))
+        };
+        // 1em is the default font size of 12pt, 16px
+        assert_eq!(width(25.0), Some(8.0));
+        assert_eq!(width(50.0), Some(16.0));
+        assert_eq!(width(175.0), Some(8.0));
+        assert!(animator.is_animating(199.0));
+        assert!(!animator.is_animating(200.0));
+        assert_eq!(width(200.0), Some(0.0));
+    }
+}
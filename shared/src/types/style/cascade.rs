@@ -0,0 +1,910 @@
+use std::collections::HashMap;
+
+use super::{
+    length::{resolve, LengthContext},
+    Condition, Declaration, Keyframes, Rule, State, States, Stylesheet, Theme, Viewport,
+};
+use crate::types::uom::{
+    AttributeValue, Color, Combinator, Document, File, NodeId, Predicate, Selector, UcreError, Unit,
+};
+
+/// MAX_VAR_DEPTH limits how deep variables may reference other variables, deeper references
+/// are treated as a cycle
+const MAX_VAR_DEPTH: usize = 16;
+
+/// INHERITED lists the properties a node takes from its parent if no rule sets them
+pub const INHERITED: &[&str] = &[
+    "color",
+    "fontFamily",
+    "fontSize",
+    "fontStyle",
+    "fontWeight",
+    "lineHeight",
+    "textAlign",
+];
+
+/// the value of a property neither set by a rule nor inherited from a parent
+pub fn default_value(property: &str) -> Option<AttributeValue> {
+    Some(match property {
+        "color" => AttributeValue::Color(Color::rgba(0, 0, 0, 255)),
+        "fontFamily" => "sans-serif".into(),
+        "fontSize" => AttributeValue::Length(12.0, Unit::Pt),
+        "fontStyle" => "normal".into(),
+        "fontWeight" => "normal".into(),
+        "lineHeight" => AttributeValue::Number(1.2),
+        "textAlign" => "left".into(),
+        _ => return None,
+    })
+}
+
+/// Specificity orders rules matching the same node, a rule of higher specificity overrides the
+/// declarations of rules with a lower one. Compared by the number of `id` predicates, then the
+/// number of other predicates and states, then the number of kinds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Specificity {
+    pub ids: u16,
+    pub predicates: u16,
+    pub kinds: u16,
+}
+
+impl Specificity {
+    pub fn of(selector: &Selector) -> Self {
+        let mut s = Specificity::default();
+        for (_, compound) in selector.parts() {
+            if compound.kind.is_some() {
+                s.kinds += 1;
+            }
+            for p in &compound.predicates {
+                match p {
+                    Predicate::Exists(k) | Predicate::Equals(k, _) if k == "id" => s.ids += 1,
+                    _ => s.predicates += 1,
+                }
+            }
+        }
+        s
+    }
+}
+
+/// CascadeRule is a [Rule] flattened into the selector formed by its kind, its predicates and
+/// those of the rules it is nested in
+#[derive(Debug, Clone)]
+pub struct CascadeRule {
+    pub selector: Selector,
+    pub specificity: Specificity,
+    /// position of the rule in the stylesheet, counted in document order of the rules
+    pub order: usize,
+    pub declarations: Vec<Declaration>,
+    /// viewport conditions that have to hold for the rule to apply
+    pub conditions: Vec<Condition>,
+    /// the states required of the node matched by each compound of selector
+    pub states: Vec<Vec<State>>,
+}
+
+impl CascadeRule {
+    /// checks whether the conditions of the rule hold for viewport
+    pub fn applies_in(&self, viewport: &Viewport) -> bool {
+        self.conditions.iter().all(|c| c.matches(viewport))
+    }
+}
+
+/// Cascade decides which declarations of a [Stylesheet] apply to the nodes of a [Document].
+///
+/// Rules are matched against a node via their selector, nesting is the descendant relation. Rules
+/// of `when` blocks are skipped while the viewport does not meet their conditions, rules requiring
+/// states while the node is not in them, see [Cascade::states_mut].
+/// Declarations of all matching rules are applied in ascending order of specificity and, for
+/// equal specificity, source order, so the last one applied wins. Properties listed in
+/// [INHERITED] not set by any rule are taken from the parent, all others and those of top-level
+/// nodes fall back to [default_value]. The value `inherit` takes the value of the parent for any
+/// property.
+///
+/// Lengths are computed to px where possible: `fontSize` in em and % relative to the font size of
+/// the parent, all other lengths in em relative to the font size of the node, vw and vh relative
+/// to the viewport. Percentages of properties other than `fontSize` are kept, since they depend
+/// on the layout.
+///
+/// Variables (`$name`) are substituted before a declaration is applied, using the variables of
+/// the active theme over those of the `vars {}` blocks. A declaration referencing an undefined
+/// variable, or variables referencing each other in a cycle, is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Cascade {
+    /// sorted by specificity and order
+    rules: Vec<CascadeRule>,
+    viewport: Viewport,
+    base: Vec<Declaration>,
+    themes: Vec<Theme>,
+    theme: Option<String>,
+    /// the variables in effect for the active theme
+    variables: HashMap<String, AttributeValue>,
+    keyframes: Vec<Keyframes>,
+    states: States,
+}
+
+impl Cascade {
+    pub fn new(style: &Stylesheet) -> Self {
+        let mut rules = vec![];
+        for rule in &style.rules {
+            flatten(rule, None, &rule.conditions, &mut rules);
+        }
+        rules.sort_by_key(|r| (r.specificity, r.order));
+        let mut cascade = Cascade {
+            rules,
+            viewport: Viewport::default(),
+            base: style.variables.clone(),
+            themes: style.themes.clone(),
+            theme: None,
+            variables: HashMap::new(),
+            keyframes: style.keyframes.clone(),
+            states: States::new(),
+        };
+        cascade.collect_variables();
+        cascade
+    }
+
+    /// activates the theme called name, None deactivates the active theme. Errors if the
+    /// stylesheet defines no such theme.
+    pub fn set_theme(&mut self, name: Option<&str>) -> Result<(), UcreError> {
+        if let Some(name) = name {
+            if !self.themes.iter().any(|t| t.name == name) {
+                return Err(UcreError::validate(format!(
+                    "style: Unknown theme '{name}'"
+                )));
+            }
+        }
+        self.theme = name.map(String::from);
+        self.collect_variables();
+        Ok(())
+    }
+
+    pub fn with_theme(mut self, name: Option<&str>) -> Result<Self, UcreError> {
+        self.set_theme(name)?;
+        Ok(self)
+    }
+
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// the value of the variable called name in the active theme, variables it references
+    /// substituted
+    pub fn variable(&self, name: &str) -> Option<AttributeValue> {
+        self.substitute(&AttributeValue::Var(name.into()), 0)
+    }
+
+    /// the keyframes called name, the last ones if the stylesheet defines several
+    pub fn keyframes(&self, name: &str) -> Option<&Keyframes> {
+        self.keyframes.iter().rev().find(|k| k.name == name)
+    }
+
+    /// computes v as if declared for a node of style, as done for keyframes: variables are
+    /// substituted and lengths resolved against the font size of the node. None if v references
+    /// an undefined variable.
+    pub fn resolve_value(
+        &self,
+        v: &AttributeValue,
+        style: &ComputedStyle,
+    ) -> Option<AttributeValue> {
+        let ctx = LengthContext {
+            font_size: 16.0,
+            percent_base: None,
+            viewport: self.viewport,
+        };
+        let ctx = LengthContext {
+            font_size: style.font_size(&ctx),
+            ..ctx
+        };
+        Some(resolve(&self.substitute(v, 0)?, &ctx))
+    }
+
+    fn collect_variables(&mut self) {
+        let theme = self
+            .theme
+            .as_deref()
+            .and_then(|name| self.themes.iter().find(|t| t.name == name));
+        self.variables = self
+            .base
+            .iter()
+            .chain(theme.into_iter().flat_map(|t| &t.variables))
+            .map(|d| (d.property.clone(), d.value.clone()))
+            .collect();
+    }
+
+    /// replaces the variables in v by their values, None if one is undefined or too deeply
+    /// nested
+    fn substitute(&self, v: &AttributeValue, depth: usize) -> Option<AttributeValue> {
+        match v {
+            AttributeValue::Var(name) if depth < MAX_VAR_DEPTH => {
+                self.substitute(self.variables.get(name)?, depth + 1)
+            }
+            AttributeValue::Var(_) => None,
+            AttributeValue::List(l) => Some(AttributeValue::List(
+                l.iter()
+                    .map(|v| self.substitute(v, depth))
+                    .collect::<Option<_>>()?,
+            )),
+            v => Some(v.clone()),
+        }
+    }
+
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// changes the viewport, e.g. on resize. Returns whether the change toggled the conditions
+    /// of a rule, only then styles computed before differ beyond their viewport relative lengths.
+    pub fn set_viewport(&mut self, viewport: Viewport) -> bool {
+        let previous = std::mem::replace(&mut self.viewport, viewport);
+        self.rules
+            .iter()
+            .filter(|r| !r.conditions.is_empty())
+            .any(|r| r.applies_in(&previous) != r.applies_in(&viewport))
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    pub fn rules(&self) -> &[CascadeRule] {
+        &self.rules
+    }
+
+    /// the rules applying to id in the current viewport and states, lowest precedence first
+    pub fn matching<'c>(
+        &'c self,
+        doc: &'c Document,
+        id: NodeId,
+    ) -> impl Iterator<Item = &'c CascadeRule> + 'c {
+        self.rules.iter().filter(move |r| {
+            r.applies_in(&self.viewport)
+                && r.selector.matches_where(doc, id, &|part, n| {
+                    r.states[part].iter().all(|s| self.states.contains(n, *s))
+                })
+        })
+    }
+
+    pub fn states(&self) -> &States {
+        &self.states
+    }
+
+    /// the interaction states of the nodes, see [Cascade::restyle] for applying changes
+    pub fn states_mut(&mut self) -> &mut States {
+        &mut self.states
+    }
+
+    /// checks whether a rule depends on state, changes of states no rule depends on need no
+    /// restyle
+    pub fn uses(&self, state: State) -> bool {
+        self.rules
+            .iter()
+            .any(|r| r.states.iter().flatten().any(|s| *s == state))
+    }
+
+    /// recomputes the styles of the nodes whose states changed and of their descendants, which
+    /// inherit from them and may be matched by rules nested in state dependent ones
+    pub fn restyle(&self, doc: &Document, styles: &mut ComputedStyles, changed: &[NodeId]) {
+        let mut roots = changed
+            .iter()
+            .copied()
+            .filter(|id| doc.get(*id).is_some())
+            .collect::<Vec<_>>();
+        // skip nodes below another changed node, they are restyled with it
+        roots.retain(|id| !doc.ancestors(*id).any(|a| changed.contains(&a)));
+        for root in roots {
+            for id in std::iter::once(root).chain(doc.descendants(root)) {
+                let parent = doc.parent(id).and_then(|p| styles.get(p));
+                let style = self.cascade(doc, id, parent);
+                styles.styles.insert(id, style);
+            }
+        }
+    }
+
+    /// computes the style of every node of doc
+    pub fn compute(&self, doc: &Document) -> ComputedStyles {
+        let mut styles = ComputedStyles::default();
+        // descendants are yielded in pre-order, parents are computed before their children
+        for id in doc.descendants(doc.root()) {
+            let parent = doc.parent(id).and_then(|p| styles.get(p));
+            let style = self.cascade(doc, id, parent);
+            styles.styles.insert(id, style);
+        }
+        styles
+    }
+
+    /// computes the style of id, computing the styles of its ancestors on the way
+    pub fn compute_node(&self, doc: &Document, id: NodeId) -> ComputedStyle {
+        let mut path = std::iter::once(id)
+            .chain(doc.ancestors(id))
+            .filter(|a| *a != doc.root())
+            .collect::<Vec<_>>();
+        path.reverse();
+        let mut style: Option<ComputedStyle> = None;
+        for a in path {
+            style = Some(self.cascade(doc, a, style.as_ref()));
+        }
+        style.unwrap_or_default()
+    }
+
+    fn cascade(&self, doc: &Document, id: NodeId, parent: Option<&ComputedStyle>) -> ComputedStyle {
+        let mut values = HashMap::new();
+        for property in INHERITED {
+            let value = parent
+                .and_then(|p| p.get(property).cloned())
+                .or_else(|| default_value(property));
+            if let Some(v) = value {
+                values.insert(property.to_string(), v);
+            }
+        }
+        for rule in self.matching(doc, id) {
+            for d in &rule.declarations {
+                let Some(value) = self.substitute(&d.value, 0) else {
+                    continue;
+                };
+                let value = match value {
+                    AttributeValue::String(ref s) | AttributeValue::Keyword(ref s)
+                        if s == "inherit" =>
+                    {
+                        match parent.and_then(|p| p.get(&d.property)) {
+                            Some(v) => v.clone(),
+                            None => match default_value(&d.property) {
+                                Some(v) => v,
+                                None => {
+                                    values.remove(&d.property);
+                                    continue;
+                                }
+                            },
+                        }
+                    }
+                    v => v,
+                };
+                values.insert(d.property.clone(), value);
+            }
+        }
+        self.resolve_lengths(&mut values, parent);
+        ComputedStyle { values }
+    }
+
+    fn resolve_lengths(
+        &self,
+        values: &mut HashMap<String, AttributeValue>,
+        parent: Option<&ComputedStyle>,
+    ) {
+        let default = LengthContext {
+            font_size: 16.0,
+            percent_base: None,
+            viewport: self.viewport,
+        };
+        let parent_font = parent.map(|p| p.font_size(&default)).unwrap_or_else(|| {
+            let size = default_value("fontSize").expect("fontSize has a default");
+            ComputedStyle::px_of(&resolve(&size, &default)).unwrap_or(default.font_size)
+        });
+        if let Some(size) = values.get_mut("fontSize") {
+            *size = resolve(
+                size,
+                &LengthContext {
+                    font_size: parent_font,
+                    percent_base: Some(parent_font),
+                    ..default
+                },
+            );
+        }
+        let ctx = LengthContext {
+            font_size: values
+                .get("fontSize")
+                .and_then(ComputedStyle::px_of)
+                .unwrap_or(parent_font),
+            ..default
+        };
+        for (property, value) in values.iter_mut() {
+            if property != "fontSize" {
+                *value = resolve(value, &ctx);
+            }
+        }
+    }
+}
+
+fn flatten(
+    rule: &Rule,
+    parent: Option<&CascadeRule>,
+    conditions: &[Condition],
+    out: &mut Vec<CascadeRule>,
+) {
+    let (selector, mut states) = match parent {
+        Some(p) => (
+            p.selector
+                .clone()
+                .then(Combinator::Descendant, rule.compound()),
+            p.states.clone(),
+        ),
+        None => (Selector::new(rule.compound()), vec![]),
+    };
+    states.push(rule.states.clone());
+    let mut specificity = Specificity::of(&selector);
+    // states weigh as much as predicates
+    specificity.predicates += states.iter().map(|s| s.len() as u16).sum::<u16>();
+    let flattened = CascadeRule {
+        specificity,
+        order: out.len(),
+        declarations: rule.declarations.clone(),
+        conditions: conditions.to_vec(),
+        selector,
+        states,
+    };
+    out.push(flattened.clone());
+    for nested in &rule.rules {
+        flatten(nested, Some(&flattened), conditions, out);
+    }
+}
+
+/// ComputedStyle holds the value of every property set for a node after the cascade
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComputedStyle {
+    values: HashMap<String, AttributeValue>,
+}
+
+impl ComputedStyle {
+    pub fn get(&self, property: &str) -> Option<&AttributeValue> {
+        self.values.get(property)
+    }
+
+    /// overrides the computed value of property, e.g. with an animated one
+    pub fn set(&mut self, property: &str, value: AttributeValue) {
+        self.values.insert(property.into(), value);
+    }
+
+    pub fn length(&self, property: &str) -> Option<(f64, Unit)> {
+        self.get(property).and_then(AttributeValue::as_length)
+    }
+
+    /// property in px, percentages are resolved against percent_base
+    pub fn px(&self, property: &str, percent_base: f64) -> Option<f64> {
+        match self.get(property)? {
+            AttributeValue::Length(n, Unit::Percent) => Some(n / 100.0 * percent_base),
+            v => Self::px_of(v),
+        }
+    }
+
+    fn px_of(v: &AttributeValue) -> Option<f64> {
+        match v {
+            AttributeValue::Length(n, Unit::Px) => Some(*n),
+            _ => None,
+        }
+    }
+
+    fn font_size(&self, default: &LengthContext) -> f64 {
+        self.get("fontSize")
+            .and_then(Self::px_of)
+            .unwrap_or(default.font_size)
+    }
+
+    /// property as a color, strings holding a named color or a color function are converted
+    pub fn color(&self, property: &str) -> Option<Color> {
+        match self.get(property)? {
+            AttributeValue::Color(c) => Some(*c),
+            AttributeValue::String(s) | AttributeValue::Keyword(s) => Color::parse(s),
+            _ => None,
+        }
+    }
+
+    /// iterates over all properties sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        let mut entries = self
+            .values
+            .iter()
+            .map(|(k, v)| (k.as_str(), v))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _)| *k);
+        entries.into_iter()
+    }
+}
+
+/// ComputedStyles maps the nodes of a [Document] to their [ComputedStyle], see
+/// [Cascade::compute]
+#[derive(Debug, Clone, Default)]
+pub struct ComputedStyles {
+    styles: HashMap<NodeId, ComputedStyle>,
+}
+
+impl ComputedStyles {
+    pub fn get(&self, id: NodeId) -> Option<&ComputedStyle> {
+        self.styles.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut ComputedStyle> {
+        self.styles.get_mut(&id)
+    }
+
+    /// iterates over the styles of all nodes in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &ComputedStyle)> {
+        self.styles.iter().map(|(id, s)| (*id, s))
+    }
+}
+
+impl File {
+    /// the stylesheet the document is styled with, its sheets cascaded in this order, later ones
+    /// overriding earlier ones at equal specificity:
+    ///
+    /// 1. the sheets named by the `styles` meta-data and `import` statements, see
+    ///    [File::linked_sheets]
+    /// 2. the `style {}` blocks of the file
+    /// 3. the sheets of the reader, see [File::add_user_sheet]
+    pub fn cascaded_style(&self) -> Stylesheet {
+        let mut style = Stylesheet::new();
+        for sheet in self.linked_sheets() {
+            style.extend(sheet);
+        }
+        style.extend(self.style());
+        for sheet in self.user_sheets() {
+            style.extend(sheet);
+        }
+        style
+    }
+
+    /// computes the style of every node of the document of the file via its stylesheet, using
+    /// the theme selected in the meta-data, see [File::set_theme]
+    pub fn computed_styles(&self) -> Result<ComputedStyles, UcreError> {
+        Ok(self.cascade()?.compute(self.doc()))
+    }
+
+    /// the cascade of the stylesheet of the file in its theme and the default viewport, kept by
+    /// renderers to restyle the document on resize, see [Cascade::set_viewport]
+    pub fn cascade(&self) -> Result<Cascade, UcreError> {
+        Cascade::new(&self.cascaded_style()).with_theme(self.theme())
+    }
+
+    /// the theme of the stylesheet the document is styled with
+    pub fn theme(&self) -> Option<&str> {
+        self.meta_data().theme()
+    }
+
+    /// switches the document to the theme called name, None to the variables of the `vars {}`
+    /// blocks only. Errors if no sheet of the file defines such a theme.
+    pub fn set_theme(&mut self, name: Option<&str>) -> Result<(), UcreError> {
+        match name {
+            Some(name) if self.cascaded_style().theme(name).is_none() => Err(UcreError::validate(
+                format!("style: Unknown theme '{name}'"),
+            )),
+            Some(name) => self.meta_data_mut().set("theme".into(), name.into()),
+            None => {
+                self.meta_data_mut().remove("theme");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{
+        style::{Condition, Declaration, Rule, State, Stylesheet, Theme},
+        uom::{
+            nodes::{Container, Heading, Image, Paragraph},
+            AttributeValue, Color, Document, File, MetaData, Node, NodeKind, Predicate, Unit,
+        },
+    };
+
+    use super::{Cascade, Specificity, Viewport};
+
+    fn rule(kind: NodeKind, matches: Vec<Predicate>, declarations: &[(&str, f64)]) -> Rule {
+        let mut r = Rule::new(kind);
+        r.matches = matches;
+        r.declarations = declarations
+            .iter()
+            .map(|(p, v)| Declaration::new(p, AttributeValue::Length(*v, Unit::Px)))
+            .collect();
+        r
+    }
+
+    fn doc() -> Document {
+        let mut heading = Heading::with_text(1, "Hello");
+        heading.set_attr("id".into(), "hello".into());
+        Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("a")),
+                Box::new(Image::with_href("a.png", "a")),
+            ])),
+            Box::new(Image::with_href("b.png", "b")),
+        ])
+        .expect("failed to build document")
+    }
+
+    #[test]
+    fn specificity_and_order() {
+        let id = Predicate::Equals("id".into(), "hello".into());
+        let text = Predicate::Equals("text".into(), "Hello".into());
+        let mut container = rule(NodeKind::Container, vec![], &[("radius", 1.0)]);
+        container
+            .rules
+            .push(rule(NodeKind::Image, vec![], &[("radius", 2.0)]));
+        let style = Stylesheet {
+            rules: vec![
+                rule(NodeKind::Heading, vec![id.clone()], &[("fontSize", 1.0)]),
+                rule(NodeKind::Heading, vec![text], &[("fontSize", 2.0)]),
+                rule(NodeKind::Heading, vec![], &[("fontSize", 3.0)]),
+                container,
+                rule(NodeKind::Image, vec![], &[("radius", 3.0)]),
+            ],
+            ..Default::default()
+        };
+        let cascade = Cascade::new(&style);
+        assert_eq!(
+            Specificity::of(&cascade.rules()[5].selector),
+            Specificity {
+                ids: 1,
+                predicates: 0,
+                kinds: 1
+            }
+        );
+
+        let doc = doc();
+        let styles = cascade.compute(&doc);
+        let top = doc.children(doc.root());
+        let radius = |id| styles.get(id).and_then(|s| s.length("radius"));
+        assert_eq!(
+            styles.get(top[0]).and_then(|s| s.length("fontSize")),
+            Some((1.0, Unit::Px))
+        );
+        // the nested rule is more specific than the later top-level one
+        assert_eq!(radius(doc.children(top[1])[1]), Some((2.0, Unit::Px)));
+        assert_eq!(radius(top[2]), Some((3.0, Unit::Px)));
+        assert_eq!(radius(top[1]), Some((1.0, Unit::Px)));
+        assert_eq!(
+            cascade.compute_node(&doc, doc.children(top[1])[1]),
+            styles
+                .get(doc.children(top[1])[1])
+                .cloned()
+                .unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn inheritance() {
+        let mut container = rule(NodeKind::Container, vec![], &[("fontSize", 20.0)]);
+        container.declarations.push(Declaration::new(
+            "radius",
+            AttributeValue::Length(4.0, Unit::Px),
+        ));
+        let mut image = Rule::new(NodeKind::Image);
+        image.declarations = vec![
+            Declaration::new("radius", "inherit".into()),
+            Declaration::new("fontFamily", "mono".into()),
+        ];
+        let style = Stylesheet {
+            rules: vec![container, image],
+            ..Default::default()
+        };
+        let doc = doc();
+        let styles = Cascade::new(&style).compute(&doc);
+        let top = doc.children(doc.root());
+        let (paragraph, image) = (doc.children(top[1])[0], doc.children(top[1])[1]);
+        let get = |id, p| styles.get(id).and_then(|s| s.get(p)).cloned();
+
+        assert_eq!(
+            get(paragraph, "fontSize"),
+            Some(AttributeValue::Length(20.0, Unit::Px))
+        );
+        assert_eq!(get(paragraph, "radius"), None);
+        assert_eq!(
+            get(image, "radius"),
+            Some(AttributeValue::Length(4.0, Unit::Px))
+        );
+        assert_eq!(get(image, "fontFamily"), Some("mono".into()));
+        assert_eq!(get(top[0], "fontFamily"), Some("sans-serif".into()));
+        assert_eq!(
+            get(top[0], "fontSize"),
+            Some(AttributeValue::Length(16.0, Unit::Px))
+        );
+    }
+
+    #[test]
+    fn lengths_and_colors() {
+        let mut container = Rule::new(NodeKind::Container);
+        container.declarations = vec![
+            Declaration::new("fontSize", AttributeValue::Length(15.0, Unit::Pt)),
+            Declaration::new("color", "rebeccapurple".into()),
+        ];
+        let mut paragraph = Rule::new(NodeKind::Paragraph);
+        paragraph.declarations = vec![
+            Declaration::new("fontSize", AttributeValue::Length(150.0, Unit::Percent)),
+            Declaration::new(
+                "padding",
+                AttributeValue::List(vec![
+                    AttributeValue::Length(1.0, Unit::Em),
+                    AttributeValue::Length(10.0, Unit::Vw),
+                ]),
+            ),
+            Declaration::new("width", AttributeValue::Length(50.0, Unit::Percent)),
+        ];
+        let style = Stylesheet {
+            rules: vec![container, paragraph],
+            ..Default::default()
+        };
+        let doc = doc();
+        let cascade = Cascade::new(&style).with_viewport(Viewport::new(800.0, 600.0));
+        let styles = cascade.compute(&doc);
+        let p = doc.children(doc.children(doc.root())[1])[0];
+        let style = styles.get(p).expect("paragraph is styled");
+
+        assert_eq!(style.px("fontSize", 0.0), Some(30.0));
+        assert_eq!(
+            style.get("padding"),
+            Some(&AttributeValue::List(vec![
+                AttributeValue::Length(30.0, Unit::Px),
+                AttributeValue::Length(80.0, Unit::Px),
+            ]))
+        );
+        assert_eq!(style.px("width", 200.0), Some(100.0));
+        assert_eq!(
+            style.color("color"),
+            Some(Color::rgba(0x66, 0x33, 0x99, 255))
+        );
+    }
+
+    #[test]
+    fn themes() {
+        let var = |n: &str| AttributeValue::Var(n.into());
+        let mut heading = Rule::new(NodeKind::Heading);
+        heading.declarations = vec![
+            Declaration::new("color", var("text")),
+            Declaration::new(
+                "padding",
+                AttributeValue::List(vec![var("gap"), var("gap")]),
+            ),
+            Declaration::new("margin", var("missing")),
+            Declaration::new("radius", var("a")),
+        ];
+        let style = Stylesheet {
+            rules: vec![heading],
+            variables: vec![
+                Declaration::new("black", "black".into()),
+                Declaration::new("text", var("black")),
+                Declaration::new("gap", AttributeValue::Length(2.0, Unit::Px)),
+                Declaration::new("a", var("b")),
+                Declaration::new("b", var("a")),
+            ],
+            themes: vec![Theme {
+                name: "dark".into(),
+                variables: vec![Declaration::new("text", "white".into())],
+            }],
+            ..Default::default()
+        };
+        let doc = doc();
+        let h = doc.children(doc.root())[0];
+        let mut cascade = Cascade::new(&style);
+        let style = cascade.compute_node(&doc, h);
+        assert_eq!(style.get("color"), Some(&"black".into()));
+        assert_eq!(
+            style.get("padding"),
+            Some(&AttributeValue::List(vec![
+                AttributeValue::Length(2.0, Unit::Px),
+                AttributeValue::Length(2.0, Unit::Px),
+            ]))
+        );
+        assert_eq!(style.get("margin"), None);
+        assert_eq!(style.get("radius"), None);
+
+        cascade.set_theme(Some("dark")).expect("dark is defined");
+        assert_eq!(cascade.theme(), Some("dark"));
+        assert_eq!(
+            cascade.compute_node(&doc, h).get("color"),
+            Some(&"white".into())
+        );
+        assert!(cascade.set_theme(Some("light")).is_err());
+        cascade.set_theme(None).expect("no theme is always valid");
+        assert_eq!(cascade.variable("text"), Some("black".into()));
+    }
+
+    #[test]
+    fn conditions() {
+        let mut narrow = rule(NodeKind::Heading, vec![], &[("fontSize", 10.0)]);
+        narrow.conditions = vec![Condition::MaxWidth(600.0, Unit::Px)];
+        narrow
+            .rules
+            .push(rule(NodeKind::Image, vec![], &[("radius", 1.0)]));
+        let style = Stylesheet {
+            rules: vec![
+                rule(NodeKind::Heading, vec![], &[("fontSize", 20.0)]),
+                narrow,
+                rule(NodeKind::Container, vec![], &[("width", 1.0)]),
+            ],
+            ..Default::default()
+        };
+        let doc = doc();
+        let h = doc.children(doc.root())[0];
+        let mut cascade = Cascade::new(&style);
+        assert_eq!(
+            cascade.compute_node(&doc, h).px("fontSize", 0.0),
+            Some(20.0)
+        );
+        assert!(!cascade.set_viewport(Viewport::new(1000.0, 400.0)));
+        assert!(cascade.set_viewport(Viewport::new(500.0, 400.0)));
+        assert_eq!(
+            cascade.compute_node(&doc, h).px("fontSize", 0.0),
+            Some(10.0)
+        );
+        assert_eq!(
+            cascade.rules()[3].conditions,
+            vec![Condition::MaxWidth(600.0, Unit::Px)]
+        );
+        assert!(!cascade.set_viewport(Viewport::new(400.0, 400.0)));
+    }
+
+    #[test]
+    fn states() {
+        let mut container = rule(NodeKind::Container, vec![], &[]);
+        container.states = vec![State::Hover];
+        container
+            .rules
+            .push(rule(NodeKind::Image, vec![], &[("radius", 4.0)]));
+        let mut image = rule(NodeKind::Image, vec![], &[("radius", 1.0)]);
+        image.states = vec![State::Focus];
+        let style = Stylesheet {
+            rules: vec![container, image],
+            ..Default::default()
+        };
+        let doc = doc();
+        let c = doc.children(doc.root())[1];
+        let [p, i] = doc.children(c)[..] else {
+            panic!("container has two children");
+        };
+        let mut cascade = Cascade::new(&style);
+        assert!(cascade.uses(State::Hover) && !cascade.uses(State::Visited));
+        assert_eq!(
+            Specificity::of(&cascade.rules()[1].selector).predicates + 1,
+            cascade.rules()[1].specificity.predicates
+        );
+        let mut styles = cascade.compute(&doc);
+        let radius =
+            |styles: &super::ComputedStyles| styles.get(i).and_then(|s| s.px("radius", 0.0));
+        assert_eq!(radius(&styles), None);
+
+        let changed = cascade.states_mut().move_to(Some(i), State::Focus);
+        cascade.restyle(&doc, &mut styles, &changed);
+        assert_eq!(radius(&styles), Some(1.0));
+        let changed = cascade.states_mut().hover(&doc, Some(p));
+        cascade.restyle(&doc, &mut styles, &changed);
+        assert_eq!(radius(&styles), Some(4.0));
+        assert_eq!(styles.get(i), Some(&cascade.compute_node(&doc, i)));
+    }
+
+    #[test]
+    fn sheets() {
+        let id = Predicate::Equals("id".into(), "hello".into());
+        let linked = Stylesheet {
+            rules: vec![
+                rule(NodeKind::Heading, vec![id], &[("radius", 1.0)]),
+                rule(NodeKind::Image, vec![], &[("radius", 1.0)]),
+            ],
+            themes: vec![Theme {
+                name: "brand".into(),
+                variables: vec![],
+            }],
+            ..Default::default()
+        };
+        let inline = Stylesheet {
+            rules: vec![rule(NodeKind::Heading, vec![], &[("radius", 2.0)])],
+            ..Default::default()
+        };
+        let user = Stylesheet {
+            rules: vec![rule(NodeKind::Image, vec![], &[("radius", 3.0)])],
+            ..Default::default()
+        };
+        let mut file = File::new(MetaData::new(), String::new(), doc()).with_style(inline);
+        file.set_linked_sheets(vec![linked]);
+        file.add_user_sheet(user);
+        assert_eq!(file.cascaded_style().rules.len(), 4);
+        file.set_theme(Some("brand")).expect("brand is linked");
+
+        let styles = file.computed_styles().expect("brand is defined");
+        let doc = file.doc();
+        let px = |i: usize, property: &str| {
+            styles
+                .get(doc.children(doc.root())[i])
+                .and_then(|s| s.px(property, 0.0))
+        };
+        // the more specific linked rule still wins over the inline one
+        assert_eq!(px(0, "radius"), Some(1.0));
+        assert_eq!(px(2, "radius"), Some(3.0));
+    }
+}
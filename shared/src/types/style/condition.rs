@@ -0,0 +1,188 @@
+use std::fmt;
+
+use super::{
+    length::{to_px, LengthContext},
+    Viewport,
+};
+use crate::types::uom::{AttributeValue, UcreError, Unit};
+
+/// Orientation of a [Viewport], portrait if it is at least as high as it is wide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+impl Orientation {
+    pub fn of(viewport: &Viewport) -> Self {
+        if viewport.height >= viewport.width {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        }
+    }
+}
+
+/// Condition is a single viewport feature of a `when` block, e.g. `minWidth 600px`:
+///
+/// ```text
+/// style {
+///     when maxWidth 600px orientation portrait {
+///         heading {
+///             fontSize 16pt
+///         }
+///     }
+/// }
+/// ```
+///
+/// Widths and heights are lengths in px, pt, mm or em, em being 16px. Densities are the number of
+/// physical pixels per px, e.g. `minDensity 2` for high density displays. All conditions of a
+/// block have to hold for its rules to apply, `when` blocks may be nested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    MinWidth(f64, Unit),
+    MaxWidth(f64, Unit),
+    MinHeight(f64, Unit),
+    MaxHeight(f64, Unit),
+    MinDensity(f64),
+    MaxDensity(f64),
+    Orientation(Orientation),
+}
+
+impl Condition {
+    /// creates the condition called name, errors if name is no viewport feature or value is not
+    /// valid for it
+    pub fn new(name: &str, value: &AttributeValue) -> Result<Self, UcreError> {
+        let invalid = || {
+            UcreError::validate(format!(
+                "style: {value} is not a valid value for the condition {name}"
+            ))
+        };
+        let length = || match value {
+            AttributeValue::Length(n, u @ (Unit::Px | Unit::Pt | Unit::Mm | Unit::Em)) => {
+                Ok((*n, *u))
+            }
+            _ => Err(invalid()),
+        };
+        let density = || match value {
+            AttributeValue::Number(n) if *n > 0.0 => Ok(*n),
+            _ => Err(invalid()),
+        };
+        Ok(match name {
+            "minWidth" => length().map(|(n, u)| Condition::MinWidth(n, u))?,
+            "maxWidth" => length().map(|(n, u)| Condition::MaxWidth(n, u))?,
+            "minHeight" => length().map(|(n, u)| Condition::MinHeight(n, u))?,
+            "maxHeight" => length().map(|(n, u)| Condition::MaxHeight(n, u))?,
+            "minDensity" => Condition::MinDensity(density()?),
+            "maxDensity" => Condition::MaxDensity(density()?),
+            "orientation" => match value.as_str() {
+                Some("portrait") => Condition::Orientation(Orientation::Portrait),
+                Some("landscape") => Condition::Orientation(Orientation::Landscape),
+                _ => return Err(invalid()),
+            },
+            _ => {
+                return Err(UcreError::validate(format!(
+                    "style: Unknown condition '{name}'"
+                )))
+            }
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Condition::MinWidth(..) => "minWidth",
+            Condition::MaxWidth(..) => "maxWidth",
+            Condition::MinHeight(..) => "minHeight",
+            Condition::MaxHeight(..) => "maxHeight",
+            Condition::MinDensity(_) => "minDensity",
+            Condition::MaxDensity(_) => "maxDensity",
+            Condition::Orientation(_) => "orientation",
+        }
+    }
+
+    pub fn value(&self) -> AttributeValue {
+        match self {
+            Condition::MinWidth(n, u)
+            | Condition::MaxWidth(n, u)
+            | Condition::MinHeight(n, u)
+            | Condition::MaxHeight(n, u) => AttributeValue::Length(*n, *u),
+            Condition::MinDensity(n) | Condition::MaxDensity(n) => AttributeValue::Number(*n),
+            Condition::Orientation(o) => AttributeValue::Keyword(o.name().into()),
+        }
+    }
+
+    /// checks whether the condition holds for viewport
+    pub fn matches(&self, viewport: &Viewport) -> bool {
+        let px = |n: f64, u: Unit| {
+            let ctx = LengthContext {
+                font_size: 16.0,
+                percent_base: None,
+                viewport: *viewport,
+            };
+            to_px(n, u, &ctx).unwrap_or(n)
+        };
+        match *self {
+            Condition::MinWidth(n, u) => viewport.width >= px(n, u),
+            Condition::MaxWidth(n, u) => viewport.width <= px(n, u),
+            Condition::MinHeight(n, u) => viewport.height >= px(n, u),
+            Condition::MaxHeight(n, u) => viewport.height <= px(n, u),
+            Condition::MinDensity(n) => viewport.density >= n,
+            Condition::MaxDensity(n) => viewport.density <= n,
+            Condition::Orientation(o) => Orientation::of(viewport) == o,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Orientation(o) => write!(f, "orientation {}", o.name()),
+            c => write!(f, "{} {}", c.name(), c.value()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{
+        style::Viewport,
+        uom::{AttributeValue, Unit},
+    };
+
+    use super::{Condition, Orientation};
+
+    #[test]
+    fn conditions() {
+        let phone = Viewport::new(400.0, 800.0).with_density(3.0);
+        let monitor = Viewport::new(2560.0, 1440.0);
+        let max_width =
+            Condition::new("maxWidth", &AttributeValue::Length(600.0, Unit::Px)).expect("valid");
+        assert!(max_width.matches(&phone));
+        assert!(!max_width.matches(&monitor));
+        let min_width =
+            Condition::new("minWidth", &AttributeValue::Length(100.0, Unit::Em)).expect("valid");
+        assert!(!min_width.matches(&phone));
+        assert!(min_width.matches(&monitor));
+        let portrait = Condition::new("orientation", &"portrait".into()).expect("valid");
+        assert_eq!(portrait, Condition::Orientation(Orientation::Portrait));
+        assert!(portrait.matches(&phone));
+        assert!(!portrait.matches(&monitor));
+        let dense = Condition::new("minDensity", &AttributeValue::Number(2.0)).expect("valid");
+        assert!(dense.matches(&phone));
+        assert!(!dense.matches(&monitor));
+        assert_eq!(dense.to_string(), "minDensity 2");
+        assert_eq!(portrait.to_string(), "orientation portrait");
+
+        assert!(Condition::new("minWidth", &AttributeValue::Length(50.0, Unit::Vw)).is_err());
+        assert!(Condition::new("maxDensity", &AttributeValue::Number(0.0)).is_err());
+        assert!(Condition::new("orientation", &"sideways".into()).is_err());
+        assert!(Condition::new("color", &"black".into()).is_err());
+    }
+}
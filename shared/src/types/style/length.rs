@@ -0,0 +1,122 @@
+use crate::types::uom::{AttributeValue, Unit};
+
+/// CSS reference pixels per point and per millimeter, one inch being 96px, 72pt and 25.4mm
+pub const PX_PER_PT: f64 = 96.0 / 72.0;
+pub const PX_PER_MM: f64 = 96.0 / 25.4;
+
+/// Viewport is the size of the area a document is rendered into, in px
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub width: f64,
+    pub height: f64,
+    /// physical pixels per px, e.g. 2 on high density displays
+    pub density: f64,
+}
+
+impl Viewport {
+    pub const fn new(width: f64, height: f64) -> Self {
+        Viewport {
+            width,
+            height,
+            density: 1.0,
+        }
+    }
+
+    pub const fn with_density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(1280.0, 720.0)
+    }
+}
+
+/// LengthContext holds the lengths relative units are resolved against, all in px
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LengthContext {
+    /// the length of `1em`
+    pub font_size: f64,
+    /// the length of `100%`, None if percentages can only be resolved during layout
+    pub percent_base: Option<f64>,
+    pub viewport: Viewport,
+}
+
+/// to_px converts a length to px, None if value is a percentage and ctx has no percent base or a
+/// duration
+pub fn to_px(value: f64, unit: Unit, ctx: &LengthContext) -> Option<f64> {
+    Some(match unit {
+        Unit::Px => value,
+        Unit::Pt => value * PX_PER_PT,
+        Unit::Mm => value * PX_PER_MM,
+        Unit::Em => value * ctx.font_size,
+        Unit::Percent => value / 100.0 * ctx.percent_base?,
+        Unit::Vw => value / 100.0 * ctx.viewport.width,
+        Unit::Vh => value / 100.0 * ctx.viewport.height,
+        Unit::Ms | Unit::S => return None,
+    })
+}
+
+/// resolve converts all lengths in v to px that can be converted in ctx, lists included
+pub fn resolve(v: &AttributeValue, ctx: &LengthContext) -> AttributeValue {
+    match v {
+        AttributeValue::Length(n, u) => match to_px(*n, *u, ctx) {
+            Some(px) => AttributeValue::Length(px, Unit::Px),
+            None => v.clone(),
+        },
+        AttributeValue::List(l) => {
+            AttributeValue::List(l.iter().map(|v| resolve(v, ctx)).collect())
+        }
+        v => v.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::uom::{AttributeValue, Unit};
+
+    use super::{resolve, to_px, LengthContext, Viewport};
+
+    #[test]
+    fn units() {
+        let ctx = LengthContext {
+            font_size: 16.0,
+            percent_base: None,
+            viewport: Viewport::new(1000.0, 500.0),
+        };
+        assert_eq!(to_px(12.0, Unit::Pt, &ctx), Some(16.0));
+        assert_eq!(to_px(25.4, Unit::Mm, &ctx).map(f64::round), Some(96.0));
+        assert_eq!(to_px(1.5, Unit::Em, &ctx), Some(24.0));
+        assert_eq!(to_px(10.0, Unit::Vw, &ctx), Some(100.0));
+        assert_eq!(to_px(10.0, Unit::Vh, &ctx), Some(50.0));
+        assert_eq!(to_px(50.0, Unit::Percent, &ctx), None);
+        assert_eq!(
+            to_px(
+                50.0,
+                Unit::Percent,
+                &LengthContext {
+                    percent_base: Some(20.0),
+                    ..ctx
+                }
+            ),
+            Some(10.0)
+        );
+        assert_eq!(
+            resolve(
+                &AttributeValue::List(vec![
+                    AttributeValue::Length(1.0, Unit::Em),
+                    AttributeValue::Length(50.0, Unit::Percent),
+                    AttributeValue::Number(2.0),
+                ]),
+                &ctx
+            ),
+            AttributeValue::List(vec![
+                AttributeValue::Length(16.0, Unit::Px),
+                AttributeValue::Length(50.0, Unit::Percent),
+                AttributeValue::Number(2.0),
+            ])
+        );
+    }
+}
@@ -0,0 +1,198 @@
+mod animation;
+mod cascade;
+mod condition;
+mod length;
+mod state;
+
+pub use self::animation::{duration, interpolate, Animation, Animator, Easing, Transition};
+pub use self::cascade::{
+    default_value, Cascade, CascadeRule, ComputedStyle, ComputedStyles, Specificity, INHERITED,
+};
+pub use self::condition::{Condition, Orientation};
+pub use self::length::{resolve, to_px, LengthContext, Viewport, PX_PER_MM, PX_PER_PT};
+pub use self::state::{State, States};
+
+use super::uom::{AttributeValue, Compound, NodeKind, Predicate};
+
+/// Stylesheet holds the rules, variables and themes of the `style {}` blocks of a ucre file:
+///
+/// ```text
+/// style {
+///     import "brand.ustyle"
+///
+///     vars {
+///         accent #f523ad
+///         text black
+///     }
+///
+///     theme dark {
+///         text white
+///     }
+///
+///     heading {
+///         matches {
+///             id "hello-world"
+///             text "Hello World"
+///         }
+///         fontSize 12pt
+///         color $accent
+///     }
+///
+///     container {
+///         image {
+///             radius 50%
+///         }
+///     }
+///
+///     keyframes pulse {
+///         50% {
+///             color $accent
+///         }
+///     }
+///
+///     when maxWidth 600px {
+///         heading {
+///             fontSize 10pt
+///         }
+///     }
+/// }
+/// ```
+///
+/// A rule names the kind of node it applies to, followed by an optional `matches {}` block of
+/// predicates, its declarations and nested rules. Nested rules apply to descendants of the nodes
+/// their parent rule applies to. Rules of multiple `style {}` blocks are concatenated. Rules in a
+/// `when` block only apply while the viewport meets its conditions, see [Condition].
+///
+/// `keyframes name {}` blocks define animations run by the `animation` property, see [Animation].
+/// Properties change gradually if the `transition` property covers them, see [Transition].
+///
+/// `vars {}` defines variables, referenced as `$name` in values. A `theme name {}` block
+/// overrides variables while the theme is active, see [Cascade::set_theme]. Variables may
+/// reference other variables.
+///
+/// `import "path"` names a style sheet file, by convention ending in `.ustyle`, holding the
+/// contents of a `style {}` block. Paths are relative to the importing file. Imported sheets are
+/// loaded by ucc and cascade before the sheet importing them, see [Stylesheet::extend].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stylesheet {
+    /// the paths of all `import` statements
+    pub imports: Vec<String>,
+    pub rules: Vec<Rule>,
+    /// the variables of all `vars {}` blocks, the value of the last declaration of a name is used
+    pub variables: Vec<Declaration>,
+    pub themes: Vec<Theme>,
+    pub keyframes: Vec<Keyframes>,
+}
+
+impl Stylesheet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.imports.is_empty()
+            && self.rules.is_empty()
+            && self.variables.is_empty()
+            && self.themes.is_empty()
+            && self.keyframes.is_empty()
+    }
+
+    pub fn theme(&self, name: &str) -> Option<&Theme> {
+        self.themes.iter().find(|t| t.name == name)
+    }
+
+    /// cascades other after self: its rules follow those of self and override them at equal
+    /// specificity, its variables, themes and keyframes override those of the same name. The imports
+    /// of other are not followed.
+    pub fn extend(&mut self, other: &Stylesheet) {
+        self.rules.extend(other.rules.iter().cloned());
+        self.variables.extend(other.variables.iter().cloned());
+        for theme in &other.themes {
+            match self.themes.iter_mut().find(|t| t.name == theme.name) {
+                Some(t) => t.variables.extend(theme.variables.iter().cloned()),
+                None => self.themes.push(theme.clone()),
+            }
+        }
+        self.keyframes.extend(other.keyframes.iter().cloned());
+    }
+}
+
+/// Theme is a named set of variable overrides, e.g. `theme dark { text white }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub variables: Vec<Declaration>,
+}
+
+/// Keyframes is a named animation, e.g. `keyframes pulse { 50% { color red } }`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes {
+    pub name: String,
+    pub frames: Vec<Keyframe>,
+}
+
+/// Keyframe sets properties at a point of an animation, written as a percentage of its duration
+/// or `from` and `to` for 0% and 100%. Properties missing in the first or last frame animate from
+/// and to the value of the animated node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// in percent
+    pub offset: f64,
+    pub declarations: Vec<Declaration>,
+}
+
+/// Rule is a single `kind { ... }` block of a [Stylesheet]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub kind: NodeKind,
+    /// predicates of the `matches {}` block, a `key value` line is a [Predicate::Equals], a
+    /// `key` line a [Predicate::Exists]
+    pub matches: Vec<Predicate>,
+    /// interaction states of the `matches {}` block the node has to be in, a `:hover` line is
+    /// [State::Hover]
+    pub states: Vec<State>,
+    /// declarations in source order, a later declaration of a property overrides earlier ones
+    pub declarations: Vec<Declaration>,
+    pub rules: Vec<Rule>,
+    /// conditions of the `when` blocks enclosing the rule, only set for top-level rules since
+    /// nested rules are bound by those of their parent
+    pub conditions: Vec<Condition>,
+}
+
+impl Rule {
+    pub fn new(kind: NodeKind) -> Self {
+        Rule {
+            kind,
+            matches: vec![],
+            states: vec![],
+            declarations: vec![],
+            rules: vec![],
+            conditions: vec![],
+        }
+    }
+
+    /// the compound selector matching the nodes this rule applies to, ignoring its ancestors
+    pub fn compound(&self) -> Compound {
+        Compound {
+            kind: Some(self.kind.clone()),
+            predicates: self.matches.clone(),
+        }
+    }
+}
+
+/// Declaration sets a property to a value, e.g. `fontSize 12pt`. Multiple values, as in
+/// `padding 2px 4px`, are held as a [AttributeValue::List].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration {
+    pub property: String,
+    pub value: AttributeValue,
+}
+
+impl Declaration {
+    pub fn new(property: &str, value: AttributeValue) -> Self {
+        Declaration {
+            property: property.into(),
+            value,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::types::uom::{Document, NodeId};
+
+/// State is an interaction state of a node a rule can require in its `matches {}` block:
+///
+/// ```text
+/// link {
+///     matches {
+///         :hover
+///     }
+///     color $accent
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    /// the pointer is above the node or one of its descendants
+    Hover,
+    /// the node receives keyboard input
+    Focus,
+    /// the node is being pressed, by the pointer or a key
+    Active,
+    /// the node is a link that was followed
+    Visited,
+}
+
+impl State {
+    pub const ALL: [State; 4] = [State::Hover, State::Focus, State::Active, State::Visited];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            State::Hover => "hover",
+            State::Focus => "focus",
+            State::Active => "active",
+            State::Visited => "visited",
+        }
+    }
+
+    const fn bit(&self) -> u8 {
+        1 << *self as u8
+    }
+}
+
+/// States holds the interaction states of the nodes of a document, tracked by a renderer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct States {
+    nodes: HashMap<NodeId, u8>,
+}
+
+impl States {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, id: NodeId, state: State) -> bool {
+        self.nodes.get(&id).is_some_and(|s| s & state.bit() != 0)
+    }
+
+    /// puts id into state or takes it out of it, returns whether this changed anything
+    pub fn set(&mut self, id: NodeId, state: State, on: bool) -> bool {
+        let bits = self.nodes.entry(id).or_default();
+        let previous = *bits;
+        if on {
+            *bits |= state.bit();
+        } else {
+            *bits &= !state.bit();
+        }
+        let changed = *bits != previous;
+        if *bits == 0 {
+            self.nodes.remove(&id);
+        }
+        changed
+    }
+
+    /// the nodes in state
+    pub fn nodes(&self, state: State) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .filter(move |(_, s)| *s & state.bit() != 0)
+            .map(|(id, _)| *id)
+    }
+
+    /// moves state to id, e.g. focus, returns the nodes whose state changed
+    pub fn move_to(&mut self, id: Option<NodeId>, state: State) -> Vec<NodeId> {
+        let mut changed = self
+            .nodes(state)
+            .filter(|n| Some(*n) != id)
+            .collect::<Vec<_>>();
+        for n in &changed {
+            self.set(*n, state, false);
+        }
+        if let Some(id) = id {
+            if self.set(id, state, true) {
+                changed.push(id);
+            }
+        }
+        changed
+    }
+
+    /// moves the pointer to id, hovering it and its ancestors, returns the nodes whose state
+    /// changed
+    pub fn hover(&mut self, doc: &Document, id: Option<NodeId>) -> Vec<NodeId> {
+        let hovered = id
+            .into_iter()
+            .flat_map(|id| std::iter::once(id).chain(doc.ancestors(id)))
+            .filter(|n| *n != doc.root())
+            .collect::<Vec<_>>();
+        let mut changed = self
+            .nodes(State::Hover)
+            .filter(|n| !hovered.contains(n))
+            .collect::<Vec<_>>();
+        for n in &changed {
+            self.set(*n, State::Hover, false);
+        }
+        for n in hovered {
+            if self.set(n, State::Hover, true) {
+                changed.push(n);
+            }
+        }
+        changed
+    }
+
+    /// drops the states of nodes no longer in doc
+    pub fn retain(&mut self, doc: &Document) {
+        self.nodes.retain(|id, _| doc.get(*id).is_some());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::uom::{
+        nodes::{Container, Paragraph},
+        Document,
+    };
+
+    use super::{State, States};
+
+    #[test]
+    fn states() {
+        let doc = Document::from_nodes(vec![Box::new(Container::with_children(vec![
+            Box::new(Paragraph::with_text("a")),
+            Box::new(Paragraph::with_text("b")),
+        ]))])
+        .expect("failed to build document");
+        let container = doc.children(doc.root())[0];
+        let [a, b] = doc.children(container)[..] else {
+            panic!("container has two children");
+        };
+        let mut states = States::new();
+        assert_eq!(State::from_name("visited"), Some(State::Visited));
+
+        let mut changed = states.hover(&doc, Some(a));
+        changed.sort();
+        let mut expected = vec![container, a];
+        expected.sort();
+        assert_eq!(changed, expected);
+        assert!(states.contains(container, State::Hover));
+        assert_eq!(states.hover(&doc, Some(b)), vec![a, b]);
+        assert!(states.hover(&doc, Some(b)).is_empty());
+
+        assert_eq!(states.move_to(Some(a), State::Focus), vec![a]);
+        assert_eq!(states.move_to(Some(b), State::Focus), vec![a, b]);
+        assert!(states.contains(b, State::Focus) && states.contains(b, State::Hover));
+        assert!(states.set(a, State::Visited, true));
+        assert!(!states.set(a, State::Visited, true));
+        assert_eq!(states.move_to(None, State::Focus), vec![b]);
+        assert_eq!(states.hover(&doc, None).len(), 2);
+        assert_eq!(states.nodes(State::Visited).collect::<Vec<_>>(), vec![a]);
+    }
+}
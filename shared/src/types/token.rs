@@ -1,10 +1,27 @@
-#[derive(Debug)]
+use super::uom::{Color, Unit};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
     Ident(String),
     String(String),
     Number(f64),
+    Length(f64, Unit),
+    Color(Color),
+    /// NodeRef is a reference to a node by its `id`, written as `@id`
+    NodeRef(String),
+    /// Var is a reference to a style variable, written as `$name`
+    Var(String),
+    /// State is an interaction state a style rule requires, written as `:hover`
+    State(String),
     Lua(String),
     CurlyLeft,
     CurlyRight,
     Eof,
 }
+
+/// Token is a [TokenType] and the line it was found in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub ty: TokenType,
+    pub line: usize,
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+
+use serde_json::{json, Map, Value};
+
+use super::{plain_text, AttributeValue, Document, Inline, Node, NodeId, NodeKind, Path};
+
+/// Role is the purpose of a node for assistive technology, named after the closest WAI-ARIA role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Document,
+    Heading,
+    Paragraph,
+    Group,
+    Image,
+    /// an image with an empty alt text, skipped by screen readers
+    Presentation,
+    Link,
+    List,
+    ListItem,
+    Code,
+    Blockquote,
+    Table,
+    Row,
+    Cell,
+    ColumnHeader,
+    Separator,
+    Text,
+    /// custom node kinds, see [super::register]
+    Generic,
+}
+
+impl Role {
+    /// the WAI-ARIA role name
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Role::Document => "document",
+            Role::Heading => "heading",
+            Role::Paragraph => "paragraph",
+            Role::Group => "group",
+            Role::Image => "img",
+            Role::Presentation => "presentation",
+            Role::Link => "link",
+            Role::List => "list",
+            Role::ListItem => "listitem",
+            Role::Code => "code",
+            Role::Blockquote => "blockquote",
+            Role::Table => "table",
+            Role::Row => "row",
+            Role::Cell => "cell",
+            Role::ColumnHeader => "columnheader",
+            Role::Separator => "separator",
+            Role::Text => "text",
+            Role::Generic => "generic",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// AccessNode is a node of an [AccessTree]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessNode {
+    pub role: Role,
+    /// the node this one is derived from, links inside of inline runs point to the node holding
+    /// the runs, the root points to the document root
+    pub node: NodeId,
+    /// the accessible name: the `label` attribute if set, the alt text of images, the text of
+    /// links including their descendants, otherwise the plain text of the node
+    pub name: String,
+    /// level of headings, 1 to 6
+    pub level: Option<u8>,
+    /// target of links
+    pub href: Option<String>,
+    /// children in reading order
+    pub children: Vec<AccessNode>,
+}
+
+/// AccessTree is the accessibility tree derived from a [Document] via [Document::access_tree].
+///
+/// Each node of the document maps to exactly one [AccessNode], links in formatted text are
+/// added as [Role::Link] children of the node holding them, in front of its element children.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessTree {
+    pub root: AccessNode,
+}
+
+impl AccessTree {
+    /// all nodes in reading order, which is the document order, starting with the root
+    pub fn reading_order(&self) -> Vec<&AccessNode> {
+        fn collect<'a>(n: &'a AccessNode, out: &mut Vec<&'a AccessNode>) {
+            out.push(n);
+            n.children.iter().for_each(|c| collect(c, out));
+        }
+        let mut out = vec![];
+        collect(&self.root, &mut out);
+        out
+    }
+
+    /// exports the tree as pretty printed JSON:
+    ///
+    /// ```text
+    /// node = { "role": string, "name": string, "level": number, "href": string,
+    ///          "children": [node] }
+    /// ```
+    ///
+    /// `level` and `href` are only present for headings and links, `children` if not empty.
+    pub fn to_json(&self) -> String {
+        fn node(n: &AccessNode) -> Value {
+            let mut obj = Map::new();
+            obj.insert("role".into(), n.role.name().into());
+            obj.insert("name".into(), n.name.clone().into());
+            if let Some(level) = n.level {
+                obj.insert("level".into(), level.into());
+            }
+            if let Some(href) = &n.href {
+                obj.insert("href".into(), href.clone().into());
+            }
+            if !n.children.is_empty() {
+                obj.insert("children".into(), n.children.iter().map(node).collect());
+            }
+            Value::Object(obj)
+        }
+        serde_json::to_string_pretty(&json!(node(&self.root)))
+            .expect("serializing a Value can't fail")
+    }
+}
+
+/// IssueKind is a violation of the accessibility requirements found by
+/// [Document::check_accessibility]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// an image without an `alt` attribute, use `alt ""` for decorative images
+    MissingAlt,
+    /// a heading more than one level below the previous heading, the first heading is expected
+    /// to be of level 1
+    SkippedHeadingLevel { previous: u8, level: u8 },
+    /// a link without an accessible name
+    EmptyLink,
+}
+
+/// Issue is an accessibility violation at a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub node: NodeId,
+    pub path: Path,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match self.kind {
+            IssueKind::MissingAlt => write!(f, "image is missing its alt text"),
+            IssueKind::SkippedHeadingLevel { previous: 0, level } => {
+                write!(f, "first heading is of level {level} instead of 1")
+            }
+            IssueKind::SkippedHeadingLevel { previous, level } => {
+                write!(
+                    f,
+                    "heading of level {level} follows a heading of level {previous}"
+                )
+            }
+            IssueKind::EmptyLink => write!(f, "link has no text"),
+        }
+    }
+}
+
+impl Document {
+    /// derives the accessibility tree of the document
+    pub fn access_tree(&self) -> AccessTree {
+        AccessTree {
+            root: AccessNode {
+                role: Role::Document,
+                node: self.root(),
+                name: String::new(),
+                level: None,
+                href: None,
+                children: self
+                    .children(self.root())
+                    .iter()
+                    .map(|c| access_node(self, *c))
+                    .collect(),
+            },
+        }
+    }
+
+    /// checks the document for missing alt texts, skipped heading levels and empty links,
+    /// issues are returned in document order
+    pub fn check_accessibility(&self) -> Vec<Issue> {
+        let mut issues = vec![];
+        let mut previous = 0;
+        let mut issue = |kind, node| {
+            issues.push(Issue {
+                kind,
+                node,
+                path: self.path(node).unwrap_or_default(),
+            })
+        };
+        for n in self.access_tree().reading_order() {
+            match n.role {
+                Role::Image if n.name.trim().is_empty() => issue(IssueKind::MissingAlt, n.node),
+                Role::Link if n.name.trim().is_empty() => issue(IssueKind::EmptyLink, n.node),
+                Role::Heading => {
+                    let level = n.level.unwrap_or(1);
+                    if level > previous + 1 {
+                        issue(IssueKind::SkippedHeadingLevel { previous, level }, n.node);
+                    }
+                    previous = level;
+                }
+                _ => {}
+            }
+        }
+        issues
+    }
+}
+
+fn access_node(doc: &Document, id: NodeId) -> AccessNode {
+    let node = doc.get(id).expect("children of a document exist");
+    let attr = |k: &str| node.attr().get(k).and_then(AttributeValue::as_str);
+    let role = match node.kind() {
+        NodeKind::Heading => Role::Heading,
+        NodeKind::Paragraph => Role::Paragraph,
+        NodeKind::Container => Role::Group,
+        // an empty alt text marks decorative images, a missing one is reported by the checker
+        NodeKind::Image if attr("alt").is_some_and(|a| a.trim().is_empty()) => Role::Presentation,
+        NodeKind::Image => Role::Image,
+        NodeKind::Link => Role::Link,
+        NodeKind::List => Role::List,
+        NodeKind::ListItem => Role::ListItem,
+        NodeKind::CodeBlock => Role::Code,
+        NodeKind::Quote => Role::Blockquote,
+        NodeKind::Table => Role::Table,
+        NodeKind::Row => Role::Row,
+        NodeKind::Cell
+            if node.attr().get("header").and_then(AttributeValue::as_bool) == Some(true) =>
+        {
+            Role::ColumnHeader
+        }
+        NodeKind::Cell => Role::Cell,
+        NodeKind::Divider => Role::Separator,
+        NodeKind::Span => Role::Text,
+        NodeKind::Custom(_) => Role::Generic,
+    };
+    let name = match (attr("label"), role) {
+        (Some(label), _) => label.to_string(),
+        (None, Role::Image | Role::Presentation) => attr("alt").unwrap_or_default().to_string(),
+        (None, Role::Link) => deep_text(doc, id),
+        (None, _) => node.text().unwrap_or_default().to_string(),
+    };
+    let level = (role == Role::Heading).then(|| {
+        node.attr()
+            .get("level")
+            .and_then(AttributeValue::as_number)
+            .map_or(1, |l| l.clamp(1.0, 6.0) as u8)
+    });
+    let href = (role == Role::Link).then(|| attr("href").unwrap_or_default().to_string());
+
+    let mut children = vec![];
+    inline_links(id, node.inline().unwrap_or_default(), &mut children);
+    children.extend(doc.children(id).iter().map(|c| access_node(doc, *c)));
+    AccessNode {
+        role,
+        node: id,
+        name,
+        level,
+        href,
+        children,
+    }
+}
+
+/// appends the links found in runs to out
+fn inline_links(id: NodeId, runs: &[Inline], out: &mut Vec<AccessNode>) {
+    for run in runs {
+        match run {
+            Inline::Link { href, content } => out.push(AccessNode {
+                role: Role::Link,
+                node: id,
+                name: plain_text(content),
+                level: None,
+                href: Some(href.clone()),
+                children: vec![],
+            }),
+            Inline::Strong(c) | Inline::Emphasis(c) => inline_links(id, c, out),
+            _ => {}
+        }
+    }
+}
+
+/// the text of id followed by the text of its descendants
+fn deep_text(doc: &Document, id: NodeId) -> String {
+    let text = |n: &dyn Node| n.text().unwrap_or_default().to_string();
+    std::iter::once(id)
+        .chain(doc.descendants(id))
+        .filter_map(|d| doc.get(d).map(text))
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::uom::{
+        nodes::{Container, Heading, Image, Link, Paragraph},
+        Document, Inline, Node,
+    };
+
+    use super::{IssueKind, Role};
+
+    fn doc() -> Document {
+        let mut paragraph = Paragraph::new();
+        paragraph
+            .set_inline(vec![
+                Inline::Text("see ".into()),
+                Inline::Strong(vec![Inline::Link {
+                    href: "https://example.com".into(),
+                    content: vec![Inline::Text("example".into())],
+                }]),
+            ])
+            .expect("paragraphs hold inline runs");
+        let mut decorative = Image::new();
+        decorative.set_attr("alt".into(), "".into());
+        Document::from_nodes(vec![
+            Box::new(Heading::with_text(2, "Title")),
+            Box::new(Container::with_children(vec![
+                Box::new(paragraph),
+                Box::new(Image::with_href("logo.png", "Logo")),
+                Box::new(decorative),
+                Box::new(Image::new()),
+            ])),
+            Box::new(Heading::with_text(4, "Deep")),
+            Box::new(Link::with_href("/", "")),
+        ])
+        .expect("failed to build document")
+    }
+
+    #[test]
+    fn tree() {
+        let tree = doc().access_tree();
+        let order = tree
+            .reading_order()
+            .iter()
+            .map(|n| (n.role, n.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                (Role::Document, ""),
+                (Role::Heading, "Title"),
+                (Role::Group, ""),
+                (Role::Paragraph, "see example"),
+                (Role::Link, "example"),
+                (Role::Image, "Logo"),
+                (Role::Presentation, ""),
+                (Role::Image, ""),
+                (Role::Heading, "Deep"),
+                (Role::Link, ""),
+            ]
+        );
+        assert_eq!(tree.root.children[0].level, Some(2));
+        assert_eq!(
+            tree.root.children[1].children[0].children[0]
+                .href
+                .as_deref(),
+            Some("https://example.com")
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&tree.to_json()).expect("valid json");
+        assert_eq!(json["children"][0]["role"], "heading");
+        assert_eq!(json["children"][0]["level"], 2);
+        assert_eq!(json["children"][3]["href"], "/");
+    }
+
+    #[test]
+    fn check() {
+        let doc = doc();
+        let issues = doc.check_accessibility();
+        assert_eq!(
+            issues.iter().map(|i| i.kind.clone()).collect::<Vec<_>>(),
+            vec![
+                IssueKind::SkippedHeadingLevel {
+                    previous: 0,
+                    level: 2
+                },
+                IssueKind::MissingAlt,
+                IssueKind::SkippedHeadingLevel {
+                    previous: 2,
+                    level: 4
+                },
+                IssueKind::EmptyLink,
+            ]
+        );
+        assert_eq!(issues[1].to_string(), "/1/3: image is missing its alt text");
+    }
+}
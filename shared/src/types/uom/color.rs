@@ -0,0 +1,386 @@
+use std::{fmt, str::FromStr};
+
+use super::UcreError;
+
+/// Color is a rgba color, written in ucre lang as
+///
+/// ```text
+/// #f52           #rgb
+/// #f523ad        #rrggbb
+/// #f523ad80      #rrggbbaa
+/// rgb(245 35 173)         rgb(245, 35, 173, 0.5)   rgb(96% 14% 68% / 50%)
+/// hsl(320 91% 55%)        hsla(320deg, 91%, 55%, 0.5)
+/// ```
+///
+/// Named colors, e.g. `rebeccapurple`, are plain strings in ucre lang and resolved via
+/// [Color::from_name] where a color is expected. All notations are printed as hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+
+    /// parses s (without the leading `#`) as a hex color
+    pub fn from_hex(s: &str) -> Option<Self> {
+        if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize, len: usize| {
+            let c = u8::from_str_radix(&s[i * len..i * len + len], 16).ok()?;
+            Some(if len == 1 { c * 17 } else { c })
+        };
+        match s.len() {
+            3 => Some(Color::rgba(
+                channel(0, 1)?,
+                channel(1, 1)?,
+                channel(2, 1)?,
+                255,
+            )),
+            6 => Some(Color::rgba(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                255,
+            )),
+            8 => Some(Color::rgba(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                channel(3, 2)?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// parses a color in any of the notations listed at [Color] or a named color
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            return Color::from_hex(hex);
+        }
+        let Some((name, args)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) else {
+            return Color::from_name(s);
+        };
+        // rgb(r g b / a) and rgb(r, g, b, a) are equivalent
+        let args = args
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|a| !a.is_empty())
+            .collect::<Vec<_>>();
+        let alpha = match args.get(3) {
+            None => 255,
+            Some(a) => (fraction(a, 1.0)?.clamp(0.0, 1.0) * 255.0).round() as u8,
+        };
+        if args.len() < 3 || args.len() > 4 {
+            return None;
+        }
+        match name.trim() {
+            "rgb" | "rgba" => {
+                let channel =
+                    |a: &str| Some((fraction(a, 255.0)?.clamp(0.0, 1.0) * 255.0).round() as u8);
+                Some(Color::rgba(
+                    channel(args[0])?,
+                    channel(args[1])?,
+                    channel(args[2])?,
+                    alpha,
+                ))
+            }
+            "hsl" | "hsla" => {
+                let hue = args[0]
+                    .strip_suffix("deg")
+                    .unwrap_or(args[0])
+                    .parse::<f64>()
+                    .ok()?;
+                Some(Color::hsla(
+                    hue,
+                    fraction(args[1], 100.0)?,
+                    fraction(args[2], 100.0)?,
+                    alpha,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// converts hue in degrees, saturation and lightness between 0 and 1 to rgb
+    pub fn hsla(hue: f64, saturation: f64, lightness: f64, a: u8) -> Self {
+        let (s, l) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let h = hue.rem_euclid(360.0) / 30.0;
+        let chroma = s * l.min(1.0 - l);
+        let channel = |n: f64| {
+            let k = (n + h) % 12.0;
+            let v = l - chroma * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0);
+            (v * 255.0).round() as u8
+        };
+        Color::rgba(channel(0.0), channel(8.0), channel(4.0), a)
+    }
+
+    /// looks up a CSS named color, `transparent` included, case insensitive
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Color::rgba(0, 0, 0, 0));
+        }
+        let i = NAMED
+            .binary_search_by_key(&name.as_str(), |(n, _)| n)
+            .ok()?;
+        let [_, r, g, b] = NAMED[i].1.to_be_bytes();
+        Some(Color::rgba(r, g, b, 255))
+    }
+
+    /// the channels as fractions between 0 and 1, in the order r, g, b, a
+    pub fn to_f32(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| c as f32 / 255.0)
+    }
+}
+
+/// fraction parses a channel of a color function, `n%` or a number relative to max
+fn fraction(s: &str, max: f64) -> Option<f64> {
+    match s.strip_suffix('%') {
+        Some(p) => Some(p.parse::<f64>().ok()? / 100.0),
+        None => Some(s.parse::<f64>().ok()? / max),
+    }
+}
+
+impl FromStr for Color {
+    type Err = UcreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::parse(s).ok_or_else(|| UcreError::parse(format!("Invalid color '{s}'")))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)?;
+        if self.a != 255 {
+            write!(f, "{:02x}", self.a)?;
+        }
+        Ok(())
+    }
+}
+
+/// NAMED holds the CSS named colors sorted by name, see
+/// https://www.w3.org/TR/css-color-4/#named-colors
+const NAMED: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod test {
+    use super::Color;
+
+    #[test]
+    fn color_from_hex() {
+        assert_eq!(
+            Color::from_hex("f523ad"),
+            Some(Color::rgba(0xf5, 0x23, 0xad, 255))
+        );
+        assert_eq!(
+            Color::from_hex("fff"),
+            Some(Color::rgba(255, 255, 255, 255))
+        );
+        assert_eq!(
+            Color::from_hex("00000080"),
+            Some(Color::rgba(0, 0, 0, 0x80))
+        );
+        assert_eq!(Color::from_hex("f523a"), None);
+        assert_eq!(Color::from_hex("zzzzzz"), None);
+    }
+
+    #[test]
+    fn functions_and_names() {
+        let pink = Some(Color::rgba(0xf5, 0x23, 0xad, 255));
+        assert_eq!(Color::parse("rgb(245 35 173)"), pink);
+        assert_eq!(Color::parse("rgb(245, 35, 173)"), pink);
+        assert_eq!(Color::parse("#f523ad"), pink);
+        assert_eq!(
+            Color::parse("rgba(100%, 0%, 0%, 0.5)"),
+            Some(Color::rgba(255, 0, 0, 128))
+        );
+        assert_eq!(
+            Color::parse("rgb(0 0 255 / 25%)"),
+            Some(Color::rgba(0, 0, 255, 64))
+        );
+        assert_eq!(
+            Color::parse("hsl(0 100% 50%)"),
+            Some(Color::rgba(255, 0, 0, 255))
+        );
+        assert_eq!(
+            Color::parse("hsla(120deg, 100%, 25%, 1)"),
+            Some(Color::rgba(0, 128, 0, 255))
+        );
+        assert_eq!(
+            Color::parse("hsl(210 50% 40%)"),
+            Some(Color::rgba(51, 102, 153, 255))
+        );
+        assert_eq!(
+            Color::parse("RebeccaPurple"),
+            Some(Color::rgba(0x66, 0x33, 0x99, 255))
+        );
+        assert_eq!(Color::parse("transparent").map(|c| c.a), Some(0));
+        for invalid in [
+            "rgb(1 2)",
+            "rgb(1 2 3 4 5)",
+            "cmyk(1 2 3 4)",
+            "blurple",
+            "rgb(a b c)",
+        ] {
+            assert_eq!(Color::parse(invalid), None, "{invalid}");
+        }
+        assert_eq!(Color::rgba(255, 0, 0, 255).to_f32(), [1.0, 0.0, 0.0, 1.0]);
+    }
+}
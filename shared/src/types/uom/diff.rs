@@ -0,0 +1,786 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+};
+
+use super::{
+    nodes, plain_text, printer, AttributeKey, AttributeValue, Document, Inline, Node, NodeId,
+    NodeKind, UcreError,
+};
+
+/// Path addresses a node by the child indices leading from the document root to it: `/0/2` is
+/// the third child of the first top-level node, `/` is the root itself
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path(pub Vec<usize>);
+
+impl Path {
+    /// the path of the index-th child of the node at self
+    pub fn child(&self, index: usize) -> Path {
+        let mut p = self.0.clone();
+        p.push(index);
+        Path(p)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "/");
+        }
+        for i in &self.0 {
+            write!(f, "/{i}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Text is the textual content of a node, either plain or formatted inline runs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Text {
+    Plain(String),
+    Inline(Vec<Inline>),
+}
+
+impl Text {
+    pub(super) fn of(node: &dyn Node) -> Text {
+        match node.inline() {
+            Some(runs) if !matches!(runs, [] | [Inline::Text(_)]) => Text::Inline(runs.to_vec()),
+            _ => Text::Plain(node.text().unwrap_or_default().to_string()),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        matches!(self, Text::Plain(s) if s.is_empty())
+    }
+}
+
+/// Subtree is a detached copy of a node and its descendants, see [Document::subtree]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtree {
+    pub kind: NodeKind,
+    pub attributes: BTreeMap<AttributeKey, AttributeValue>,
+    pub text: Text,
+    pub children: Vec<Subtree>,
+}
+
+impl Subtree {
+    /// builds the node with its children, ready to be inserted into a [Document]
+    pub fn into_node(self) -> Result<Box<dyn Node>, UcreError> {
+        let mut node = nodes::new(self.kind);
+        for (k, v) in self.attributes {
+            node.set_attr(k, v);
+        }
+        match self.text {
+            Text::Plain(s) if s.is_empty() => {}
+            Text::Plain(s) => node.set_text(s)?,
+            Text::Inline(runs) => node.set_inline(runs)?,
+        }
+        if !self.children.is_empty() {
+            let children = self
+                .children
+                .into_iter()
+                .map(Subtree::into_node)
+                .collect::<Result<_, _>>()?;
+            node.set_children(children)?;
+        }
+        Ok(node)
+    }
+}
+
+impl fmt::Display for Subtree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut doc = Document::new();
+        let root = doc.root();
+        let node = self.clone().into_node().map_err(|_| fmt::Error)?;
+        let id = doc.append(root, node).map_err(|_| fmt::Error)?;
+        printer::write_node(f, &doc, id, 0)
+    }
+}
+
+/// Edit is a single step of an edit script produced by [Document::diff]. Paths of an edit refer
+/// to the document as left by the edits before it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// inserts subtree as the index-th child of parent
+    Insert {
+        parent: Path,
+        index: usize,
+        subtree: Subtree,
+    },
+    /// removes the node at path and its descendants
+    Delete {
+        path: Path,
+    },
+    /// moves the node at from and its descendants to be the index-th child of parent, see
+    /// [Document::move_node]
+    Move {
+        from: Path,
+        parent: Path,
+        index: usize,
+    },
+    SetAttribute {
+        path: Path,
+        key: AttributeKey,
+        value: AttributeValue,
+    },
+    RemoveAttribute {
+        path: Path,
+        key: AttributeKey,
+    },
+    SetText {
+        path: Path,
+        text: Text,
+    },
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Insert {
+                parent,
+                index,
+                subtree,
+            } => {
+                writeln!(f, "insert {}", parent.child(*index))?;
+                let s = subtree.to_string();
+                for (i, line) in s.lines().enumerate() {
+                    if i != 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "    {line}")?;
+                }
+                Ok(())
+            }
+            Self::Delete { path } => write!(f, "delete {path}"),
+            Self::Move {
+                from,
+                parent,
+                index,
+            } => write!(f, "move {from} to {}", parent.child(*index)),
+            Self::SetAttribute { path, key, value } => write!(f, "set {path} {key} {value}"),
+            Self::RemoveAttribute { path, key } => write!(f, "unset {path} {key}"),
+            Self::SetText {
+                path,
+                text: Text::Plain(s),
+            } => write!(f, "text {path} {}", AttributeValue::from(s.as_str())),
+            Self::SetText {
+                path,
+                text: Text::Inline(runs),
+            } => write!(
+                f,
+                "inline {path} {}",
+                AttributeValue::from(plain_text(runs).as_str())
+            ),
+        }
+    }
+}
+
+impl Document {
+    /// the path of id, None if id does not exist
+    pub fn path(&self, id: NodeId) -> Option<Path> {
+        if !self.contains(id) {
+            return None;
+        }
+        let mut path = vec![];
+        let mut cur = id;
+        while let Some(p) = self.parent(cur) {
+            let index = self.children(p).iter().position(|c| *c == cur)?;
+            path.push(index);
+            cur = p;
+        }
+        path.reverse();
+        Some(Path(path))
+    }
+
+    /// resolves path to the node it addresses
+    pub fn resolve(&self, path: &Path) -> Option<NodeId> {
+        path.0
+            .iter()
+            .try_fold(self.root(), |id, i| self.children(id).get(*i).copied())
+    }
+
+    /// copies id and its descendants out of the document
+    pub fn subtree(&self, id: NodeId) -> Option<Subtree> {
+        let node = self.get(id)?;
+        Some(Subtree {
+            kind: node.kind(),
+            attributes: node
+                .attr()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            text: Text::of(node),
+            children: self
+                .children(id)
+                .iter()
+                .filter_map(|c| self.subtree(*c))
+                .collect(),
+        })
+    }
+
+    /// diff computes an edit script transforming self into other, see [Document::patch].
+    ///
+    /// Nodes are matched by their `id` attribute first, matched nodes are moved instead of being
+    /// deleted and reinserted. Children of matched nodes are matched via their longest common
+    /// subsequence keyed by kind, remaining children of the same kind are paired up in order.
+    /// Matched nodes are then updated in place by changing their attributes and text.
+    pub fn diff(&self, other: &Document) -> Vec<Edit> {
+        let mut scratch = Document::new();
+        scratch.set_history_limit(0);
+        let root = scratch.root();
+        for c in self.children(self.root()) {
+            let node = self
+                .subtree(*c)
+                .and_then(|s| s.into_node().ok())
+                .expect("copies of valid nodes are valid");
+            scratch
+                .append(root, node)
+                .expect("copies of valid nodes are valid");
+        }
+        let mut differ = Differ {
+            other,
+            scratch,
+            matched: HashMap::from([(other.root(), root)]),
+            placed: HashSet::from([root]),
+            edits: vec![],
+        };
+        differ.matching();
+        differ.clear_unmatched();
+        differ.place_children(other.root(), root);
+        differ.delete_unplaced();
+        differ.edits
+    }
+
+    /// applies a single edit
+    pub fn apply(&mut self, edit: &Edit) -> Result<(), UcreError> {
+        let resolve = |doc: &Document, path: &Path| {
+            doc.resolve(path)
+                .ok_or_else(|| UcreError::validate(format!("patch: No node at {path}")))
+        };
+        match edit {
+            Edit::Insert {
+                parent,
+                index,
+                subtree,
+            } => {
+                let parent = resolve(self, parent)?;
+                self.insert(parent, *index, subtree.clone().into_node()?)?;
+            }
+            Edit::Delete { path } => {
+                let id = resolve(self, path)?;
+                self.remove(id)?;
+            }
+            Edit::Move {
+                from,
+                parent,
+                index,
+            } => {
+                let (id, parent) = (resolve(self, from)?, resolve(self, parent)?);
+                self.move_node(id, parent, *index)?;
+            }
+            Edit::SetAttribute { path, key, value } => {
+                let id = resolve(self, path)?;
+                self.set_attr(id, key.clone(), value.clone())?;
+            }
+            Edit::RemoveAttribute { path, key } => {
+                let id = resolve(self, path)?;
+                self.remove_attr(id, key)?;
+            }
+            Edit::SetText { path, text } => {
+                let id = resolve(self, path)?;
+                match text {
+                    Text::Plain(s) => self.set_text(id, s.clone())?,
+                    Text::Inline(runs) => self.set_inline(id, runs.clone())?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// applies all edits in order, stopping at the first failing one
+    pub fn patch(&mut self, edits: &[Edit]) -> Result<(), UcreError> {
+        edits.iter().try_for_each(|e| self.apply(e))
+    }
+}
+
+/// Differ holds the state of [Document::diff]: edits are applied to scratch, a copy of the old
+/// document, as they are generated, so the paths of every edit are computed against the document
+/// the edits before it produce
+struct Differ<'o> {
+    other: &'o Document,
+    scratch: Document,
+    /// nodes of other mapped to their counterparts in scratch
+    matched: HashMap<NodeId, NodeId>,
+    /// nodes of scratch already at their final position
+    placed: HashSet<NodeId>,
+    edits: Vec<Edit>,
+}
+
+impl Differ<'_> {
+    fn emit(&mut self, edit: Edit) {
+        self.scratch
+            .apply(&edit)
+            .expect("generated edits apply to scratch");
+        self.edits.push(edit);
+    }
+
+    fn path(&self, id: NodeId) -> Path {
+        self.scratch.path(id).expect("node exists in scratch")
+    }
+
+    /// matching fills matched, first by the `id` attribute, then top down via the longest common
+    /// subsequence of the children of matched nodes
+    fn matching(&mut self) {
+        let mut queue = vec![(self.other.root(), self.scratch.root())];
+        for y in self.other.descendants(self.other.root()) {
+            let Some(id) = self
+                .other
+                .get(y)
+                .and_then(|n| n.attr().get("id").and_then(AttributeValue::as_str))
+            else {
+                continue;
+            };
+            if let Some(x) = self.scratch.by_id(id) {
+                if self.scratch.get(x).map(|n| n.kind()) == self.other.get(y).map(|n| n.kind()) {
+                    self.matched.insert(y, x);
+                    queue.push((y, x));
+                }
+            }
+        }
+        let mut taken = self.matched.values().copied().collect::<HashSet<_>>();
+        while let Some((y, x)) = queue.pop() {
+            let ys = self
+                .other
+                .children(y)
+                .iter()
+                .filter(|c| !self.matched.contains_key(c))
+                .copied()
+                .collect::<Vec<_>>();
+            let xs = self
+                .scratch
+                .children(x)
+                .iter()
+                .filter(|c| !taken.contains(c))
+                .copied()
+                .collect::<Vec<_>>();
+            let ky = ys.iter().map(|c| key(self.other, *c)).collect::<Vec<_>>();
+            let kx = xs
+                .iter()
+                .map(|c| key(&self.scratch, *c))
+                .collect::<Vec<_>>();
+            let mut pairs = lcs(&ky, &kx);
+            // children not part of the subsequence are paired up by key too, moving a node is
+            // cheaper than deleting and reinserting it
+            let (mut used_y, mut used_x) = (vec![false; ys.len()], vec![false; xs.len()]);
+            for (i, j) in &pairs {
+                used_y[*i] = true;
+                used_x[*j] = true;
+            }
+            for i in 0..ys.len() {
+                if used_y[i] {
+                    continue;
+                }
+                if let Some(j) = (0..xs.len()).find(|j| !used_x[*j] && kx[*j] == ky[i]) {
+                    used_x[j] = true;
+                    pairs.push((i, j));
+                }
+            }
+            for (i, j) in pairs {
+                self.matched.insert(ys[i], xs[j]);
+                taken.insert(xs[j]);
+                queue.push((ys[i], xs[j]));
+            }
+        }
+    }
+
+    /// clear_unmatched deletes unmatched nodes without matched descendants and removes the id of
+    /// all other unmatched nodes before any node is inserted, so inserted ids never collide
+    fn clear_unmatched(&mut self) {
+        let taken = self.matched.values().copied().collect::<HashSet<_>>();
+        let mut delete = vec![];
+        let mut unset = vec![];
+        let mut stack = self.scratch.children(self.scratch.root()).to_vec();
+        while let Some(x) = stack.pop() {
+            if taken.contains(&x) {
+                stack.extend_from_slice(self.scratch.children(x));
+            } else if self.scratch.descendants(x).all(|d| !taken.contains(&d)) {
+                delete.push(x);
+            } else {
+                if self
+                    .scratch
+                    .get(x)
+                    .is_some_and(|n| n.attr().contains_key("id"))
+                {
+                    unset.push(x);
+                }
+                stack.extend_from_slice(self.scratch.children(x));
+            }
+        }
+        for x in delete {
+            let path = self.path(x);
+            self.emit(Edit::Delete { path });
+        }
+        for x in unset {
+            let path = self.path(x);
+            self.emit(Edit::RemoveAttribute {
+                path,
+                key: "id".into(),
+            });
+        }
+    }
+
+    /// place_children moves or inserts the counterparts of the children of y into x, the
+    /// counterpart of y, in the order of other. Children already inside x forming the longest
+    /// run in the right order stay where they are, all others are put right after the previously
+    /// placed child.
+    fn place_children(&mut self, y: NodeId, x: NodeId) {
+        let children = self.other.children(y).to_vec();
+        let positions = children
+            .iter()
+            .filter_map(|c| self.matched.get(c))
+            .filter_map(|c| self.scratch.children(x).iter().position(|s| s == c))
+            .collect::<Vec<_>>();
+        let stable = lis(&positions)
+            .into_iter()
+            .map(|i| self.scratch.children(x)[i])
+            .collect::<HashSet<_>>();
+
+        let mut prev: Option<NodeId> = None;
+        for c in children {
+            let after = |scratch: &Document| {
+                prev.map_or(0, |p| {
+                    scratch
+                        .children(x)
+                        .iter()
+                        .position(|s| *s == p)
+                        .expect("placed")
+                        + 1
+                })
+            };
+            let Some(cx) = self.matched.get(&c).copied() else {
+                let mut subtree = self.other.subtree(c).expect("node exists");
+                let whole = self
+                    .other
+                    .descendants(c)
+                    .all(|d| !self.matched.contains_key(&d));
+                if !whole {
+                    subtree.children.clear();
+                }
+                let (parent, index) = (self.path(x), after(&self.scratch));
+                self.emit(Edit::Insert {
+                    parent,
+                    index,
+                    subtree,
+                });
+                let cx = self.scratch.children(x)[index];
+                self.placed.insert(cx);
+                if whole {
+                    self.placed.extend(self.scratch.descendants(cx));
+                } else {
+                    self.matched.insert(c, cx);
+                    self.place_children(c, cx);
+                }
+                prev = Some(cx);
+                continue;
+            };
+
+            if !stable.contains(&cx) {
+                let mut index = after(&self.scratch);
+                let siblings = self.scratch.children(x);
+                if siblings
+                    .iter()
+                    .position(|s| *s == cx)
+                    .is_some_and(|i| i < index)
+                {
+                    // cx is removed before being inserted, shifting its later siblings
+                    index -= 1;
+                }
+                let (from, parent) = (self.path(cx), self.path(x));
+                self.emit(Edit::Move {
+                    from,
+                    parent,
+                    index,
+                });
+            }
+            self.placed.insert(cx);
+            self.update(cx, c);
+            self.place_children(c, cx);
+            prev = Some(cx);
+        }
+    }
+
+    /// update changes the attributes and text of x to the ones of y
+    fn update(&mut self, x: NodeId, y: NodeId) {
+        let a = self.scratch.get(x).expect("node exists");
+        let b = self.other.get(y).expect("node exists");
+        let path = self.path(x);
+        let mut removed = a
+            .attr()
+            .keys()
+            .filter(|k| !b.attr().contains_key(*k))
+            .cloned()
+            .collect::<Vec<_>>();
+        removed.sort();
+        let mut set = b
+            .attr()
+            .iter()
+            .filter(|(k, v)| a.attr().get(*k) != Some(*v))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        set.sort_by(|l, r| l.0.cmp(&r.0));
+        let (old, text) = (Text::of(a), Text::of(b));
+
+        for key in removed {
+            let path = path.clone();
+            self.emit(Edit::RemoveAttribute { path, key });
+        }
+        for (key, value) in set {
+            let path = path.clone();
+            self.emit(Edit::SetAttribute { path, key, value });
+        }
+        if old != text && !(old.is_empty() && text.is_empty()) {
+            self.emit(Edit::SetText { path, text });
+        }
+    }
+
+    /// delete_unplaced deletes the remaining nodes of the old document, their matched
+    /// descendants have been moved out by now
+    fn delete_unplaced(&mut self) {
+        let unplaced = self
+            .scratch
+            .descendants(self.scratch.root())
+            .filter(|x| {
+                !self.placed.contains(x)
+                    && self
+                        .scratch
+                        .parent(*x)
+                        .is_some_and(|p| self.placed.contains(&p))
+            })
+            .collect::<Vec<_>>();
+        for x in unplaced {
+            let path = self.path(x);
+            self.emit(Edit::Delete { path });
+        }
+    }
+}
+
+/// key identifies nodes that may be matched by the longest common subsequence
+fn key(doc: &Document, id: NodeId) -> Option<(NodeKind, Option<&AttributeValue>)> {
+    doc.get(id).map(|n| (n.kind(), n.attr().get("id")))
+}
+
+/// lis returns the indices of the longest strictly increasing subsequence of a
+fn lis(a: &[usize]) -> Vec<usize> {
+    // len[i] is the length of the longest subsequence ending at i, prev[i] its predecessor
+    let mut len = vec![1; a.len()];
+    let mut prev = vec![None; a.len()];
+    for i in 0..a.len() {
+        for j in 0..i {
+            if a[j] < a[i] && len[j] + 1 > len[i] {
+                len[i] = len[j] + 1;
+                prev[i] = Some(j);
+            }
+        }
+    }
+    let mut cur = (0..a.len()).max_by_key(|i| (len[*i], std::cmp::Reverse(*i)));
+    let mut seq = vec![];
+    while let Some(i) = cur {
+        seq.push(a[i]);
+        cur = prev[i];
+    }
+    seq
+}
+
+/// lcs returns the index pairs of the longest common subsequence of a and b
+fn lcs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    let mut table = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if a[i] == b[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = vec![];
+    while i < n && j < m {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Edit, Path, Text};
+    use crate::types::uom::{
+        nodes::{Container, Heading, Paragraph},
+        AttributeValue, Document, Inline, Node,
+    };
+
+    fn heading(id: &str, text: &str) -> Box<dyn Node> {
+        let mut h = Heading::with_text(1, text);
+        h.set_attr("id".into(), id.into());
+        Box::new(h)
+    }
+
+    fn patched(a: &mut Document, b: &Document) -> Vec<Edit> {
+        let edits = a.diff(b);
+        a.patch(&edits).expect("failed to apply diff");
+        assert!(*a == *b, "patched document differs");
+        edits
+    }
+
+    #[test]
+    fn paths() {
+        let doc = Document::from_nodes(vec![
+            heading("a", "a"),
+            Box::new(Container::with_children(vec![heading("b", "b")])),
+        ])
+        .expect("failed to build document");
+        let b = doc.by_id("b").expect("b exists");
+        assert_eq!(doc.path(b), Some(Path(vec![1, 0])));
+        assert_eq!(doc.resolve(&Path(vec![1, 0])), Some(b));
+        assert_eq!(doc.resolve(&Path(vec![2])), None);
+        assert_eq!(Path(vec![1, 0]).to_string(), "/1/0");
+        assert_eq!(Path::default().to_string(), "/");
+    }
+
+    #[test]
+    fn minimal() {
+        let mut a = Document::from_nodes(vec![
+            heading("a", "a"),
+            Box::new(Paragraph::with_text("text")),
+            Box::new(Container::with_children(vec![heading("b", "b")])),
+        ])
+        .expect("failed to build document");
+        assert!(a.diff(&a).is_empty());
+
+        let mut h = heading("b", "b");
+        h.set_attr("level".into(), AttributeValue::Number(2.0));
+        let b = Document::from_nodes(vec![
+            h,
+            Box::new(Paragraph::with_text("changed")),
+            Box::new(Container::new()),
+            heading("a", "a"),
+        ])
+        .expect("failed to build document");
+        let edits = patched(&mut a, &b);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Move {
+                    from: Path(vec![2, 0]),
+                    parent: Path::default(),
+                    index: 0,
+                },
+                Edit::SetAttribute {
+                    path: Path(vec![0]),
+                    key: "level".into(),
+                    value: AttributeValue::Number(2.0),
+                },
+                Edit::SetText {
+                    path: Path(vec![2]),
+                    text: Text::Plain("changed".into()),
+                },
+                Edit::Move {
+                    from: Path(vec![1]),
+                    parent: Path::default(),
+                    index: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn insert_delete() {
+        let mut a = Document::from_nodes(vec![
+            heading("a", "a"),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("p")),
+                heading("b", "b"),
+            ])),
+        ])
+        .expect("failed to build document");
+        // the id of a now belongs to a paragraph, the heading has to be deleted
+        let mut paragraph = Box::new(Paragraph::with_text("now a paragraph"));
+        paragraph.set_attr("id".into(), "a".into());
+        let b = Document::from_nodes(vec![
+            Box::new(Paragraph::with_text("a")),
+            heading("b", "b"),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("new")),
+                paragraph,
+            ])),
+        ])
+        .expect("failed to build document");
+        let edits = patched(&mut a, &b);
+        assert!(edits.iter().any(|e| matches!(e, Edit::Insert { .. })));
+        assert!(edits.iter().any(|e| matches!(e, Edit::Delete { .. })));
+    }
+
+    #[test]
+    fn attributes_and_inline() {
+        let mut h = heading("a", "a");
+        h.set_attr("tag".into(), true.into());
+        let mut a = Document::from_nodes(vec![h]).expect("failed to build document");
+        let mut h = heading("a", "a");
+        h.set_inline(vec![
+            Inline::Text("a ".into()),
+            Inline::Strong(vec![Inline::Text("b".into())]),
+        ])
+        .expect("headings support inline content");
+        let b = Document::from_nodes(vec![h]).expect("failed to build document");
+        let edits = patched(&mut a, &b);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::RemoveAttribute {
+                    path: Path(vec![0]),
+                    key: "tag".into(),
+                },
+                Edit::SetText {
+                    path: Path(vec![0]),
+                    text: Text::Inline(vec![
+                        Inline::Text("a ".into()),
+                        Inline::Strong(vec![Inline::Text("b".into())]),
+                    ]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut a = Document::from_nodes(vec![
+            heading("a", "a"),
+            Box::new(Container::with_children(vec![heading("b", "b")])),
+        ])
+        .expect("failed to build document");
+        let b = Document::from_nodes(vec![Box::new(Container::with_children(vec![
+            heading("b", "changed"),
+            Box::new(Paragraph::with_text("new")),
+        ]))])
+        .expect("failed to build document");
+        let original = a.to_string();
+        let edits = a.diff(&b);
+        a.transaction(|doc| doc.patch(&edits))
+            .expect("failed to apply diff");
+        assert!(a == b);
+        // the patch is undone as a whole
+        assert!(a.undo().expect("failed to undo patch"));
+        assert_eq!(a.to_string(), original);
+        assert!(a.redo().expect("failed to redo patch"));
+        assert!(a == b);
+    }
+}
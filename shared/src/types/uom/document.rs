@@ -0,0 +1,840 @@
+use std::collections::HashMap;
+
+use super::{
+    diff::{Path, Text},
+    history::History,
+    mutation::{Dirty, Mutation, Observer, ObserverId},
+    nodes,
+    value::is_name,
+    AttributeKey, AttributeValue, Edit, Inline, Node, UcreError,
+};
+
+/// NodeId is a stable handle to a node inside a [Document], it stays valid until the node is
+/// removed and is never reused for another node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+struct Entry {
+    node: Box<dyn Node>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    dirty: Dirty,
+}
+
+/// Document is the arena holding all nodes of the `doc {}` block of a ucre file.
+///
+/// Nodes are addressed via [NodeId]s, the tree structure (parents and children) lives in the
+/// arena, not in the nodes themselves: a node moved into a Document has its children taken via
+/// [Node::take_children]. The top-level nodes are children of [Document::root], a synthetic
+/// [nodes::Container] representing the `doc {}` block itself.
+///
+/// Nodes with an `id` attribute are indexed for O(1) lookup via [Document::by_id], ids are
+/// therefore required to be unique.
+///
+/// Every change made via the mutating methods of a Document is recorded as a [Mutation], handed
+/// to all observers registered via [Document::subscribe] and marks the changed nodes [Dirty],
+/// ancestors of dirty nodes are marked with [Dirty::DESCENDANT]. The [Edit] reverting a change
+/// is kept in the undo history, see [Document::undo] and [Document::begin]. Changes made via
+/// [Document::get_mut] are neither recorded nor undoable.
+pub struct Document {
+    entries: Vec<Option<Entry>>,
+    ids: HashMap<String, NodeId>,
+    mutations: Vec<Mutation>,
+    observers: Vec<(ObserverId, Observer)>,
+    next_observer: usize,
+    pub(super) history: History,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Document {
+            entries: vec![Some(Entry {
+                node: Box::new(nodes::Container::new()),
+                parent: None,
+                children: vec![],
+                dirty: Dirty::CLEAN,
+            })],
+            ids: HashMap::new(),
+            mutations: vec![],
+            observers: vec![],
+            next_observer: 0,
+            history: History::default(),
+        }
+    }
+
+    /// creates a Document with nodes as its top-level nodes
+    pub fn from_nodes(nodes: Vec<Box<dyn Node>>) -> Result<Self, UcreError> {
+        let mut doc = Self::new();
+        for node in nodes {
+            doc.append(doc.root(), node)?;
+        }
+        doc.clear_history();
+        Ok(doc)
+    }
+
+    /// the synthetic node all top-level nodes are children of
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// amount of nodes in the Document, excluding [Document::root]
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.entry(id).is_some()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&dyn Node> {
+        self.entry(id).map(|e| e.node.as_ref())
+    }
+
+    /// mutable access to a node, changes made this way are not recorded as [Mutation]s and
+    /// changing the `id` attribute bypasses the id index, use [Document::set_attr],
+    /// [Document::set_text] and [Document::set_inline] instead. The node is marked [Dirty::NODE]
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut (dyn Node + 'static)> {
+        if !self.contains(id) {
+            return None;
+        }
+        self.mark(id, Dirty::NODE);
+        Some(self.entry_mut(id).node.as_mut())
+    }
+
+    /// looks up the node with the given `id` attribute
+    pub fn by_id(&self, id: &str) -> Option<NodeId> {
+        self.ids.get(id).copied()
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.entry(id).and_then(|e| e.parent)
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.entry(id).map(|e| e.children.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn next_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let siblings = self.children(self.parent(id)?);
+        let i = siblings.iter().position(|c| *c == id)?;
+        siblings.get(i + 1).copied()
+    }
+
+    pub fn prev_sibling(&self, id: NodeId) -> Option<NodeId> {
+        let siblings = self.children(self.parent(id)?);
+        let i = siblings.iter().position(|c| *c == id)?;
+        i.checked_sub(1).map(|i| siblings[i])
+    }
+
+    /// iterates over the parent, grandparent, etc. of id up to and including [Document::root]
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |p| self.parent(*p))
+    }
+
+    /// iterates over all nodes below id in document order (pre-order), excluding id
+    pub fn descendants(&self, id: NodeId) -> Descendants<'_> {
+        let mut stack = self.children(id).to_vec();
+        stack.reverse();
+        Descendants { doc: self, stack }
+    }
+
+    /// subtree_eq compares the subtree at id with the subtree at other_id of other: both have
+    /// to consist of nodes with the same kind, attributes, text and inline runs in the same
+    /// order, NodeIds and dirty flags are ignored
+    pub fn subtree_eq(&self, id: NodeId, other: &Document, other_id: NodeId) -> bool {
+        let (Some(a), Some(b)) = (self.get(id), other.get(other_id)) else {
+            return false;
+        };
+        let (ac, bc) = (self.children(id), other.children(other_id));
+        a.kind() == b.kind()
+            && a.attr() == b.attr()
+            && a.text() == b.text()
+            && a.inline() == b.inline()
+            && ac.len() == bc.len()
+            && ac
+                .iter()
+                .zip(bc)
+                .all(|(a, b)| self.subtree_eq(*a, other, *b))
+    }
+
+    /// appends node and all of its children as the last child of parent
+    pub fn append(&mut self, parent: NodeId, node: Box<dyn Node>) -> Result<NodeId, UcreError> {
+        let index = self.children(parent).len();
+        self.insert(parent, index, node)
+    }
+
+    /// inserts node and all of its children as the index-th child of parent
+    pub fn insert(
+        &mut self,
+        parent: NodeId,
+        index: usize,
+        node: Box<dyn Node>,
+    ) -> Result<NodeId, UcreError> {
+        let id = self.attach(parent, index, node)?;
+        if self.history.recording() {
+            let path = self.path_of(id);
+            self.history.push(Edit::Delete { path });
+        }
+        self.mark(parent, Dirty::CHILDREN);
+        self.record(Mutation::ChildInserted {
+            parent,
+            child: id,
+            index,
+        });
+        Ok(id)
+    }
+
+    /// removes id and all of its descendants, returning the node with its children restored
+    pub fn remove(&mut self, id: NodeId) -> Result<Box<dyn Node>, UcreError> {
+        if id == self.root() {
+            return Err(UcreError::validate("Can't remove the document root"));
+        }
+        let parent = self
+            .parent(id)
+            .ok_or_else(|| UcreError::validate(format!("Node {id:?} does not exist")))?;
+        let index = self.index_in_parent(id);
+        if self.history.recording() {
+            let subtree = self.subtree(id).expect("node exists");
+            let parent = self.path_of(parent);
+            self.history.push(Edit::Insert {
+                parent,
+                index,
+                subtree,
+            });
+        }
+        self.entry_mut(parent).children.remove(index);
+        self.mark(parent, Dirty::CHILDREN);
+        self.record(Mutation::ChildRemoved {
+            parent,
+            child: id,
+            index,
+        });
+        Ok(self.detach(id))
+    }
+
+    /// moves id and its descendants to be the index-th child of new_parent, recorded as a
+    /// [Mutation::ChildRemoved] followed by a [Mutation::ChildInserted]. Errors like
+    /// [Document::insert] if index is out of range.
+    pub fn move_node(
+        &mut self,
+        id: NodeId,
+        new_parent: NodeId,
+        index: usize,
+    ) -> Result<(), UcreError> {
+        if id == self.root() {
+            return Err(UcreError::validate("Can't move the document root"));
+        }
+        if id == new_parent || self.ancestors(new_parent).any(|a| a == id) {
+            return Err(UcreError::validate("Can't move a node into itself"));
+        }
+        let node = self
+            .get(id)
+            .ok_or_else(|| UcreError::validate(format!("Node {id:?} does not exist")))?;
+        self.check_child(new_parent, node)?;
+        let old_parent = self.parent(id).expect("non root nodes have a parent");
+        // the index is one into the children of new_parent after id was removed
+        let len = self.children(new_parent).len() - usize::from(old_parent == new_parent);
+        if index > len {
+            return Err(UcreError::validate(format!(
+                "Can't move to {index}, {} has {len} other children",
+                self.name(new_parent),
+            )));
+        }
+        let old_index = self.index_in_parent(id);
+        self.entry_mut(old_parent).children.remove(old_index);
+        self.entry_mut(new_parent).children.insert(index, id);
+        self.entry_mut(id).parent = Some(new_parent);
+        if self.history.recording() {
+            let (from, parent) = (self.path_of(id), self.path_of(old_parent));
+            self.history.push(Edit::Move {
+                from,
+                parent,
+                index: old_index,
+            });
+        }
+        self.mark(old_parent, Dirty::CHILDREN);
+        self.mark(new_parent, Dirty::CHILDREN);
+        if !self.dirty(id).is_clean() {
+            // the new ancestors of a dirty subtree are marked like those of the old position
+            self.mark(id, Dirty::CLEAN);
+        }
+        self.record(Mutation::ChildRemoved {
+            parent: old_parent,
+            child: id,
+            index: old_index,
+        });
+        self.record(Mutation::ChildInserted {
+            parent: new_parent,
+            child: id,
+            index,
+        });
+        Ok(())
+    }
+
+    /// sets the text of id to s
+    pub fn set_text(&mut self, id: NodeId, s: String) -> Result<(), UcreError> {
+        if !self.contains(id) {
+            return Err(UcreError::validate(format!("Node {id:?} does not exist")));
+        }
+        let old = self.old_text(id);
+        self.entry_mut(id).node.set_text(s)?;
+        self.remember_text(id, old);
+        self.mark(id, Dirty::NODE);
+        self.record(Mutation::TextChanged { node: id });
+        Ok(())
+    }
+
+    /// sets the inline runs of id, recorded as a [Mutation::TextChanged]
+    pub fn set_inline(&mut self, id: NodeId, runs: Vec<Inline>) -> Result<(), UcreError> {
+        if !self.contains(id) {
+            return Err(UcreError::validate(format!("Node {id:?} does not exist")));
+        }
+        let old = self.old_text(id);
+        self.entry_mut(id).node.set_inline(runs)?;
+        self.remember_text(id, old);
+        self.mark(id, Dirty::NODE);
+        self.record(Mutation::TextChanged { node: id });
+        Ok(())
+    }
+
+    /// sets attribute k of id to v, keeping the id index up to date
+    pub fn set_attr(
+        &mut self,
+        id: NodeId,
+        k: AttributeKey,
+        v: AttributeValue,
+    ) -> Result<(), UcreError> {
+        if !self.contains(id) {
+            return Err(UcreError::validate(format!("Node {id:?} does not exist")));
+        }
+        check_attr(self.get(id).expect("node exists"), &k, &v)?;
+        if k == "id" {
+            if let Some(new) = v.as_str() {
+                if self.ids.get(new).is_some_and(|other| *other != id) {
+                    return Err(UcreError::validate(format!("Duplicate node id '{new}'")));
+                }
+            }
+            if let Some(old) = self
+                .entry_mut(id)
+                .node
+                .attr()
+                .get("id")
+                .and_then(AttributeValue::as_str)
+            {
+                let old = old.to_string();
+                self.ids.remove(&old);
+            }
+            if let Some(new) = v.as_str() {
+                self.ids.insert(new.to_string(), id);
+            }
+        }
+        if self.history.recording() {
+            let path = self.path_of(id);
+            let key = k.clone();
+            let inverse = match self.get(id).and_then(|n| n.attr().get(&k)) {
+                Some(value) => Edit::SetAttribute {
+                    path,
+                    key,
+                    value: value.clone(),
+                },
+                None => Edit::RemoveAttribute { path, key },
+            };
+            self.history.push(inverse);
+        }
+        self.entry_mut(id).node.set_attr(k.clone(), v);
+        self.mark(id, Dirty::NODE);
+        self.record(Mutation::AttributeSet { node: id, key: k });
+        Ok(())
+    }
+
+    /// removes attribute k of id, returning its value if it was set
+    pub fn remove_attr(
+        &mut self,
+        id: NodeId,
+        k: &str,
+    ) -> Result<Option<AttributeValue>, UcreError> {
+        if !self.contains(id) {
+            return Err(UcreError::validate(format!("Node {id:?} does not exist")));
+        }
+        let v = self.entry_mut(id).node.remove_attr(k);
+        if v.is_none() {
+            return Ok(None);
+        }
+        if let ("id", Some(old)) = (k, v.as_ref().and_then(AttributeValue::as_str)) {
+            self.ids.remove(old);
+        }
+        if let (true, Some(value)) = (self.history.recording(), &v) {
+            let path = self.path_of(id);
+            self.history.push(Edit::SetAttribute {
+                path,
+                key: k.to_string(),
+                value: value.clone(),
+            });
+        }
+        self.mark(id, Dirty::NODE);
+        self.record(Mutation::AttributeRemoved {
+            node: id,
+            key: k.to_string(),
+        });
+        Ok(v)
+    }
+
+    /// registers f to be called with every [Mutation] made from now on
+    pub fn subscribe(&mut self, f: impl FnMut(&Mutation) + 'static) -> ObserverId {
+        let id = ObserverId(self.next_observer);
+        self.next_observer += 1;
+        self.observers.push((id, Box::new(f)));
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: ObserverId) {
+        self.observers.retain(|(o, _)| *o != id);
+    }
+
+    /// returns all mutations recorded since the last call
+    pub fn take_mutations(&mut self) -> Vec<Mutation> {
+        std::mem::take(&mut self.mutations)
+    }
+
+    pub fn dirty(&self, id: NodeId) -> Dirty {
+        self.entry(id).map(|e| e.dirty).unwrap_or_default()
+    }
+
+    /// all nodes marked [Dirty::NODE] or [Dirty::CHILDREN], in document order
+    pub fn dirty_nodes(&self) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::once(self.root())
+            .chain(self.descendants(self.root()))
+            .filter(|id| {
+                let d = self.dirty(*id);
+                d.contains(Dirty::NODE) || d.contains(Dirty::CHILDREN)
+            })
+    }
+
+    /// resets the dirty flags of all nodes, e.g. after the renderer caught up
+    pub fn clear_dirty(&mut self) {
+        for e in self.entries.iter_mut().flatten() {
+            e.dirty = Dirty::CLEAN;
+        }
+    }
+
+    /// drops all recorded mutations and clears all dirty flags
+    pub fn mark_clean(&mut self) {
+        self.mutations.clear();
+        self.clear_dirty();
+    }
+
+    /// attach inserts node and its children without recording mutations
+    fn attach(
+        &mut self,
+        parent: NodeId,
+        index: usize,
+        mut node: Box<dyn Node>,
+    ) -> Result<NodeId, UcreError> {
+        self.check_child(parent, node.as_ref())?;
+        node.attr()
+            .iter()
+            .try_for_each(|(k, v)| check_attr(node.as_ref(), k, v))?;
+        if index > self.children(parent).len() {
+            return Err(UcreError::validate(format!(
+                "Can't insert at {index}, {} has {} children",
+                self.name(parent),
+                self.children(parent).len()
+            )));
+        }
+        let children = node.take_children();
+        let id = NodeId(self.entries.len());
+        if let Some(key) = node.attr().get("id").and_then(AttributeValue::as_str) {
+            if self.ids.contains_key(key) {
+                return Err(UcreError::validate(format!("Duplicate node id '{key}'")));
+            }
+            self.ids.insert(key.to_string(), id);
+        }
+        self.entries.push(Some(Entry {
+            node,
+            parent: Some(parent),
+            children: vec![],
+            dirty: Dirty::CLEAN,
+        }));
+        self.entry_mut(parent).children.insert(index, id);
+        self.mark(id, Dirty::NODE);
+        for (i, child) in children.into_iter().enumerate() {
+            if let Err(e) = self.attach(id, i, child) {
+                self.entry_mut(parent).children.remove(index);
+                self.detach(id);
+                return Err(e);
+            }
+        }
+        Ok(id)
+    }
+
+    fn path_of(&self, id: NodeId) -> Path {
+        self.path(id).expect("node exists")
+    }
+
+    /// old_text returns the text of id to be restored by undo, None if the history is disabled
+    fn old_text(&self, id: NodeId) -> Option<Text> {
+        self.history
+            .recording()
+            .then(|| Text::of(self.get(id).expect("node exists")))
+    }
+
+    fn remember_text(&mut self, id: NodeId, old: Option<Text>) {
+        if let Some(text) = old {
+            let path = self.path_of(id);
+            self.history.push(Edit::SetText { path, text });
+        }
+    }
+
+    /// mark sets flag on id and [Dirty::DESCENDANT] on all of its ancestors
+    fn mark(&mut self, id: NodeId, flag: Dirty) {
+        self.entry_mut(id).dirty |= flag;
+        let mut cur = self.parent(id);
+        // the whole chain is walked, a moved subtree may have left marked ancestors behind
+        while let Some(p) = cur {
+            let e = self.entry_mut(p);
+            e.dirty |= Dirty::DESCENDANT;
+            cur = e.parent;
+        }
+    }
+
+    fn record(&mut self, m: Mutation) {
+        for (_, o) in &mut self.observers {
+            o(&m);
+        }
+        self.mutations.push(m);
+    }
+
+    fn index_in_parent(&self, id: NodeId) -> usize {
+        self.parent(id)
+            .and_then(|p| self.children(p).iter().position(|c| *c == id))
+            .expect("non root nodes are children of their parent")
+    }
+
+    fn detach(&mut self, id: NodeId) -> Box<dyn Node> {
+        let mut entry = self.entries[id.0].take().expect("detached node exists");
+        if let Some(key) = entry.node.attr().get("id").and_then(AttributeValue::as_str) {
+            self.ids.remove(key);
+        }
+        let children = entry
+            .children
+            .iter()
+            .map(|c| self.detach(*c))
+            .collect::<Vec<_>>();
+        if !children.is_empty() {
+            // children were valid when inserted, restoring them can't fail
+            let _ = entry.node.set_children(children);
+        }
+        entry.node
+    }
+
+    fn check_child(&self, parent: NodeId, child: &dyn Node) -> Result<(), UcreError> {
+        let p = self
+            .get(parent)
+            .ok_or_else(|| UcreError::validate(format!("Node {parent:?} does not exist")))?;
+        if parent != self.root() && !p.allows_child(child.kind()) {
+            return Err(UcreError::validate(format!(
+                "{} does not allow {} children",
+                p.kind().name(),
+                child.kind().name()
+            )));
+        }
+        Ok(())
+    }
+
+    fn name(&self, id: NodeId) -> String {
+        if id == self.root() {
+            return "doc".into();
+        }
+        self.get(id)
+            .map_or("unknown".into(), |n| n.kind().name().to_string())
+    }
+
+    fn entry(&self, id: NodeId) -> Option<&Entry> {
+        self.entries.get(id.0).and_then(|e| e.as_ref())
+    }
+
+    fn entry_mut(&mut self, id: NodeId) -> &mut Entry {
+        self.entries[id.0].as_mut().expect("node exists")
+    }
+}
+
+impl PartialEq for Document {
+    /// structural equality, see [Document::subtree_eq]
+    fn eq(&self, other: &Self) -> bool {
+        self.subtree_eq(self.root(), other, other.root())
+    }
+}
+
+/// Iterator returned by [Document::descendants]
+pub struct Descendants<'doc> {
+    doc: &'doc Document,
+    stack: Vec<NodeId>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        self.stack
+            .extend(self.doc.children(id).iter().rev().copied());
+        Some(id)
+    }
+}
+
+/// errors if attribute k with value v can't be written as ucre lang, see
+/// [AttributeValue::validate], or is no valid value of k for node, see [Node::validate_attr]
+pub(super) fn check_attr(node: &dyn Node, k: &str, v: &AttributeValue) -> Result<(), UcreError> {
+    if !is_name(k) {
+        return Err(UcreError::validate(format!(
+            "'{k}' is not a valid attribute name"
+        )));
+    }
+    v.validate_in_block()
+        .map_err(|e| UcreError::validate(format!("Invalid value of {k}: {}", e.message())))?;
+    node.validate_attr(k, v)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::Document;
+    use crate::types::uom::{
+        mutation::{Dirty, Mutation},
+        nodes::{Cell, Container, Heading, Image, Paragraph, Row, Table},
+        AttributeValue, Node, NodeKind,
+    };
+
+    fn doc() -> Document {
+        let mut heading = Heading::with_text(1, "Hello");
+        heading.set_attr("id".into(), "hello".into());
+        let mut image = Image::with_href("avatar.webp", "avatar");
+        image.set_attr("id".into(), "avatar".into());
+        Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("text")),
+                Box::new(image),
+            ])),
+        ])
+        .expect("failed to build document")
+    }
+
+    #[test]
+    fn navigation() {
+        let doc = doc();
+        assert_eq!(doc.len(), 4);
+        let image = doc.by_id("avatar").expect("avatar is indexed");
+        let container = doc.parent(image).expect("image has a parent");
+        assert_eq!(
+            doc.get(container).map(|n| n.kind()),
+            Some(NodeKind::Container)
+        );
+        assert_eq!(
+            doc.ancestors(image).collect::<Vec<_>>(),
+            vec![container, doc.root()]
+        );
+        let paragraph = doc
+            .prev_sibling(image)
+            .expect("image has a previous sibling");
+        assert_eq!(doc.next_sibling(paragraph), Some(image));
+        assert_eq!(doc.next_sibling(image), None);
+        let kinds = doc
+            .descendants(doc.root())
+            .map(|id| doc.get(id).map(|n| n.kind()))
+            .collect::<Option<Vec<_>>>();
+        assert_eq!(
+            kinds,
+            Some(vec![
+                NodeKind::Heading,
+                NodeKind::Container,
+                NodeKind::Paragraph,
+                NodeKind::Image
+            ])
+        );
+    }
+
+    #[test]
+    fn mutation() {
+        let mut doc = doc();
+        let hello = doc.by_id("hello").expect("hello is indexed");
+        let image = doc.by_id("avatar").expect("avatar is indexed");
+        let container = doc.parent(image).expect("image has a parent");
+
+        doc.move_node(image, doc.root(), 0).expect("failed to move");
+        assert_eq!(doc.children(doc.root())[0], image);
+        assert_eq!(doc.children(container).len(), 1);
+        assert!(doc.move_node(container, container, 0).is_err());
+
+        let removed = doc.remove(container).expect("failed to remove");
+        assert_eq!(removed.children().map(|c| c.len()), Some(1));
+        assert_eq!(doc.len(), 2);
+        assert!(doc.get(container).is_none());
+
+        assert!(doc.set_attr(hello, "id".into(), "avatar".into()).is_err());
+        doc.set_attr(hello, "id".into(), "greeting".into())
+            .expect("failed to set id");
+        assert_eq!(doc.by_id("greeting"), Some(hello));
+        assert_eq!(doc.by_id("hello"), None);
+    }
+
+    #[test]
+    fn validation() {
+        let mut doc = Document::new();
+        let table = doc
+            .append(doc.root(), Box::new(Table::new()))
+            .expect("failed to append");
+        assert!(doc.append(table, Box::new(Cell::new())).is_err());
+        let row = doc
+            .append(table, Box::new(Row::new()))
+            .expect("failed to append");
+        assert!(doc.insert(row, 5, Box::new(Cell::new())).is_err());
+        assert!(doc.remove(doc.root()).is_err());
+
+        let mut heading = Heading::new();
+        heading.set_attr("id".into(), "a".into());
+        doc.append(doc.root(), Box::new(heading))
+            .expect("failed to append");
+        let mut duplicate = Heading::new();
+        duplicate.set_attr("id".into(), "a".into());
+        assert!(doc.append(doc.root(), Box::new(duplicate)).is_err());
+    }
+
+    #[test]
+    fn attribute_values() {
+        let mut doc = doc();
+        let hello = doc.by_id("hello").expect("hello is indexed");
+        // values without a ucre lang syntax and keys that aren't names are rejected
+        for (k, v) in [
+            ("a", AttributeValue::Number(f64::NAN)),
+            ("a", AttributeValue::List(vec![1.0.into()])),
+            ("a", AttributeValue::List(vec![])),
+            (
+                "a",
+                AttributeValue::List(vec![AttributeValue::Keyword("left".into()), 1.0.into()]),
+            ),
+            ("a", AttributeValue::Keyword("true".into())),
+            ("two words", true.into()),
+        ] {
+            assert!(
+                doc.set_attr(hello, k.into(), v.clone()).is_err(),
+                "{k} {v:?}"
+            );
+            let mut heading = Heading::new();
+            heading.set_attr(k.into(), v);
+            assert!(doc.append(doc.root(), Box::new(heading)).is_err());
+        }
+        assert!(!doc
+            .get(hello)
+            .expect("hello exists")
+            .attr()
+            .contains_key("a"));
+        doc.set_attr(hello, "a".into(), vec![1.0.into(), 2.0.into()].into())
+            .expect("lists of two values are valid");
+    }
+
+    #[test]
+    fn mutations() {
+        let mut doc = doc();
+        doc.mark_clean();
+        let seen = Rc::new(RefCell::new(0));
+        let counter = seen.clone();
+        let observer = doc.subscribe(move |_| *counter.borrow_mut() += 1);
+
+        let image = doc.by_id("avatar").expect("avatar is indexed");
+        let container = doc.parent(image).expect("image has a parent");
+        doc.set_attr(image, "alt".into(), "me".into())
+            .expect("failed to set alt");
+        assert!(doc.dirty(image).contains(Dirty::NODE));
+        assert!(doc.dirty(container).contains(Dirty::DESCENDANT));
+        assert!(doc.dirty(doc.root()).contains(Dirty::DESCENDANT));
+        assert!(doc
+            .dirty(doc.by_id("hello").expect("hello is indexed"))
+            .is_clean());
+
+        let paragraph = doc.children(container)[0];
+        doc.set_text(paragraph, "changed".into())
+            .expect("failed to set text");
+        doc.remove(image).expect("failed to remove");
+        doc.unsubscribe(observer);
+        doc.set_text(paragraph, "unobserved".into())
+            .expect("failed to set text");
+
+        assert_eq!(*seen.borrow(), 3);
+        assert_eq!(
+            doc.take_mutations(),
+            vec![
+                Mutation::AttributeSet {
+                    node: image,
+                    key: "alt".into()
+                },
+                Mutation::TextChanged { node: paragraph },
+                Mutation::ChildRemoved {
+                    parent: container,
+                    child: image,
+                    index: 1
+                },
+                Mutation::TextChanged { node: paragraph },
+            ]
+        );
+        assert_eq!(
+            doc.dirty_nodes().collect::<Vec<_>>(),
+            vec![container, paragraph]
+        );
+        doc.clear_dirty();
+        assert_eq!(doc.dirty_nodes().count(), 0);
+        assert!(doc.take_mutations().is_empty());
+    }
+
+    #[test]
+    fn move_dirty() {
+        let mut doc = Document::from_nodes(vec![
+            Box::new(Container::with_children(vec![Box::new(Container::new())])),
+            Box::new(Container::with_children(vec![Box::new(Container::new())])),
+        ])
+        .expect("failed to build document");
+        let [a, b] = doc.children(doc.root())[..] else {
+            panic!("expected two top-level nodes");
+        };
+        let (from, to) = (doc.children(a)[0], doc.children(b)[0]);
+        let p = doc
+            .append(from, Box::new(Paragraph::with_text("p")))
+            .expect("failed to append");
+        doc.mark_clean();
+
+        doc.set_text(p, "changed".into())
+            .expect("failed to set text");
+        doc.move_node(p, to, 0).expect("failed to move");
+        // the new branch leads to the dirty paragraph, although the root was marked before
+        for id in [b, to] {
+            assert!(doc.dirty(id).contains(Dirty::DESCENDANT), "{id:?}");
+        }
+        assert!(doc.dirty(to).contains(Dirty::CHILDREN));
+        assert!(doc.dirty(p).contains(Dirty::NODE));
+
+        // out of range indices are rejected like those of insert
+        let original = doc.to_string();
+        assert!(doc.move_node(p, from, 1).is_err());
+        assert!(doc.move_node(p, to, 1).is_err());
+        assert_eq!(doc.to_string(), original);
+        doc.move_node(p, from, 0).expect("failed to move");
+        assert_eq!(doc.children(from), [p]);
+    }
+}
@@ -1,26 +1,263 @@
-use std::fmt;
+use std::{borrow::Cow, error::Error, fmt, path::PathBuf};
 
+/// ErrorKind is the stage of ucre an error originates from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// reading or writing files
+    Io,
+    /// turning ucre lang source into tokens
+    Lex,
+    /// turning tokens, JSON or selectors into a document
+    Parse,
+    /// a document, attribute, meta-data entry or argument violates a constraint
+    Validate,
+    /// compiling or executing bytecode
+    Vm,
+    /// running the lua block of a file
+    Lua,
+    /// rendering a document
+    Render,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 7] = [
+        ErrorKind::Io,
+        ErrorKind::Lex,
+        ErrorKind::Parse,
+        ErrorKind::Validate,
+        ErrorKind::Vm,
+        ErrorKind::Lua,
+        ErrorKind::Render,
+    ];
+
+    /// code is stable across releases and can be used to look up or match an error
+    pub const fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Io => "E0001",
+            ErrorKind::Lex => "E0002",
+            ErrorKind::Parse => "E0003",
+            ErrorKind::Validate => "E0004",
+            ErrorKind::Vm => "E0005",
+            ErrorKind::Lua => "E0006",
+            ErrorKind::Render => "E0007",
+        }
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Io => "io",
+            ErrorKind::Lex => "lex",
+            ErrorKind::Parse => "parse",
+            ErrorKind::Validate => "validate",
+            ErrorKind::Vm => "vm",
+            ErrorKind::Lua => "lua",
+            ErrorKind::Render => "render",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// SourceSpan is the position in the source an error refers to, lines and columns start at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl SourceSpan {
+    pub const fn line(line: usize) -> Self {
+        SourceSpan { line, column: None }
+    }
+
+    pub const fn new(line: usize, column: usize) -> Self {
+        SourceSpan {
+            line,
+            column: Some(column),
+        }
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ", column {column}")?;
+        }
+        Ok(())
+    }
+}
+
+/// UcreError is the error returned by every fallible operation of ucre.
+///
+/// Match on [UcreError::kind] or [UcreError::code] instead of comparing messages, the messages
+/// are meant for humans and may change. Display renders the path, the message and the span:
+///
+/// ```text
+/// examples/hello.ucre: ucc: Unknown unit 'px' in line 3, column 12
+/// ```
+///
+/// The error that caused this one, e.g. an [std::io::Error], is available via [Error::source].
 #[derive(Debug)]
-pub enum UcreError {
-    String(String),
-    Str(&'static str),
+pub struct UcreError {
+    kind: ErrorKind,
+    message: Cow<'static, str>,
+    span: Option<SourceSpan>,
+    path: Option<PathBuf>,
+    cause: Option<Box<dyn Error + Send + Sync>>,
 }
 
 impl UcreError {
-    pub fn new(s: String) -> Self {
-        UcreError::String(s)
+    pub fn new(kind: ErrorKind, message: impl Into<Cow<'static, str>>) -> Self {
+        UcreError {
+            kind,
+            message: message.into(),
+            span: None,
+            path: None,
+            cause: None,
+        }
+    }
+
+    pub fn io(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Io, message)
+    }
+
+    pub fn lex(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Lex, message)
+    }
+
+    pub fn parse(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Parse, message)
     }
 
-    pub const fn from_str(s: &'static str) -> Self {
-        UcreError::Str(s)
+    pub fn validate(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Validate, message)
+    }
+
+    pub fn vm(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Vm, message)
+    }
+
+    pub fn lua(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Lua, message)
+    }
+
+    pub fn render(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorKind::Render, message)
+    }
+
+    /// attaches span, keeps an already attached span since it is the more precise one
+    pub fn at(mut self, span: SourceSpan) -> Self {
+        self.span.get_or_insert(span);
+        self
+    }
+
+    /// attaches the path of the file the error occurred in, keeps an already attached path
+    pub fn in_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path.get_or_insert_with(|| path.into());
+        self
+    }
+
+    /// sets the error that caused this one
+    pub fn caused_by(mut self, cause: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        self.cause = Some(cause.into());
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+
+    pub fn path(&self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    /// iterates over this error and all of its causes, outermost first
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn Error + 'static)> {
+        let mut next: Option<&(dyn Error + 'static)> = Some(self);
+        std::iter::from_fn(move || {
+            let cur = next?;
+            next = cur.source();
+            Some(cur)
+        })
     }
 }
 
 impl fmt::Display for UcreError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Self::String(string) => write!(f, "{}", string),
-            Self::Str(str) => write!(f, "{}", str),
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
         }
+        f.write_str(&self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " in {span}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for UcreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|c| c as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, error::Error};
+
+    use super::{ErrorKind, SourceSpan, UcreError};
+
+    #[test]
+    fn codes() {
+        let codes = ErrorKind::ALL
+            .iter()
+            .map(|k| k.code())
+            .collect::<HashSet<_>>();
+        assert_eq!(codes.len(), ErrorKind::ALL.len());
+        assert_eq!(ErrorKind::Parse.code(), "E0003");
+    }
+
+    #[test]
+    fn display_and_chain() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "not found");
+        let err = UcreError::parse("ucc: Unexpected '}'")
+            .at(SourceSpan::new(3, 12))
+            .at(SourceSpan::line(1))
+            .in_file("hello.ucre")
+            .caused_by(UcreError::io("Failed to read file").caused_by(io));
+        assert_eq!(
+            err.to_string(),
+            "hello.ucre: ucc: Unexpected '}' in line 3, column 12"
+        );
+        assert_eq!(err.kind(), ErrorKind::Parse);
+        assert_eq!(
+            err.chain().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec![
+                err.to_string(),
+                "Failed to read file".into(),
+                "not found".into()
+            ]
+        );
+        assert!(err
+            .source()
+            .and_then(|e| e.downcast_ref::<UcreError>())
+            .is_some_and(|e| e.kind() == ErrorKind::Io));
     }
 }
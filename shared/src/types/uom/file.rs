@@ -1,9 +1,93 @@
-use super::Node;
+use super::{Document, MetaData};
+use crate::types::style::Stylesheet;
 
 pub struct File {
-    /// K is specifically of type String to allow custom meta_data keys
-    meta_data: std::collections::HashMap<String, String>,
+    meta_data: MetaData,
     /// lua holds the dynamic portion of the ucre document
     lua: String,
-    doc: Vec<Box<dyn Node>>,
+    doc: Document,
+    style: Stylesheet,
+    /// the style sheets named by the `styles` meta-data and `import` statements, in cascade
+    /// order, loaded by ucc
+    linked: Vec<Stylesheet>,
+    /// the style sheets of the reader, injected at render time
+    user: Vec<Stylesheet>,
+}
+
+impl File {
+    /// creates a File, doc starts out clean and without history, see [Document::mark_clean] and
+    /// [Document::clear_history]
+    pub fn new(meta_data: MetaData, lua: String, mut doc: Document) -> Self {
+        doc.mark_clean();
+        doc.clear_history();
+        File {
+            meta_data,
+            lua,
+            doc,
+            style: Stylesheet::default(),
+            linked: vec![],
+            user: vec![],
+        }
+    }
+
+    pub fn with_style(mut self, style: Stylesheet) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn meta_data(&self) -> &MetaData {
+        &self.meta_data
+    }
+
+    pub fn meta_data_mut(&mut self) -> &mut MetaData {
+        &mut self.meta_data
+    }
+
+    pub fn lua(&self) -> &str {
+        &self.lua
+    }
+
+    pub fn doc(&self) -> &Document {
+        &self.doc
+    }
+
+    pub fn doc_mut(&mut self) -> &mut Document {
+        &mut self.doc
+    }
+
+    pub fn style(&self) -> &Stylesheet {
+        &self.style
+    }
+
+    pub fn style_mut(&mut self) -> &mut Stylesheet {
+        &mut self.style
+    }
+
+    pub fn linked_sheets(&self) -> &[Stylesheet] {
+        &self.linked
+    }
+
+    pub fn set_linked_sheets(&mut self, sheets: Vec<Stylesheet>) {
+        self.linked = sheets;
+    }
+
+    pub fn user_sheets(&self) -> &[Stylesheet] {
+        &self.user
+    }
+
+    /// adds a style sheet of the reader, cascading after the style of the document and user
+    /// sheets added before
+    pub fn add_user_sheet(&mut self, sheet: Stylesheet) {
+        self.user.push(sheet);
+    }
+}
+
+/// files are equal if their source is, linked and user sheets are not part of it
+impl PartialEq for File {
+    fn eq(&self, other: &Self) -> bool {
+        self.meta_data == other.meta_data
+            && self.lua == other.lua
+            && self.doc == other.doc
+            && self.style == other.style
+    }
 }
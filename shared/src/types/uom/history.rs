@@ -0,0 +1,306 @@
+use std::collections::VecDeque;
+
+use super::{Document, Edit, UcreError};
+
+/// DEFAULT_HISTORY_LIMIT is the number of undoable groups a [Document] keeps by default
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// History holds the undo and redo stacks of a [Document].
+///
+/// Every change made via the mutating methods of a Document records the [Edit] reverting it.
+/// Changes made inside a transaction, see [Document::begin], are grouped and undone as one,
+/// changes made outside of a transaction form a group each. Undoing a group applies its edits in
+/// reverse, the edits reverting the undo form the group pushed onto the redo stack.
+pub(super) struct History {
+    /// inverse edits of the open transaction, None if no transaction is open
+    open: Option<Vec<Edit>>,
+    undo: VecDeque<Vec<Edit>>,
+    redo: Vec<Vec<Edit>>,
+    /// maximum number of groups on the undo stack, 0 disables undo but not transactions
+    limit: usize,
+    /// collects the inverse edits while undoing or redoing instead of the stacks
+    replay: Option<Vec<Edit>>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            open: None,
+            undo: VecDeque::new(),
+            redo: vec![],
+            limit: DEFAULT_HISTORY_LIMIT,
+            replay: None,
+        }
+    }
+}
+
+impl History {
+    /// recording reports whether inverse edits are collected at all, used to skip computing them.
+    /// An open transaction always collects them, its rollback needs them even without history.
+    pub(super) fn recording(&self) -> bool {
+        self.limit != 0 || self.open.is_some() || self.replay.is_some()
+    }
+
+    /// push records inverse, the edit reverting a change that was just made
+    pub(super) fn push(&mut self, inverse: Edit) {
+        if let Some(replay) = &mut self.replay {
+            replay.push(inverse);
+        } else if let Some(open) = &mut self.open {
+            open.push(inverse);
+        } else if self.limit != 0 {
+            self.push_group(vec![inverse]);
+        }
+    }
+
+    fn push_group(&mut self, group: Vec<Edit>) {
+        if group.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push_back(group);
+        while self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl Document {
+    /// opens a transaction, all changes until [Document::commit] or [Document::rollback] are
+    /// grouped into a single undo step. Transactions can't be nested.
+    pub fn begin(&mut self) -> Result<(), UcreError> {
+        if self.history.open.is_some() {
+            return Err(UcreError::validate("A transaction is already open"));
+        }
+        self.history.open = Some(vec![]);
+        Ok(())
+    }
+
+    /// closes the open transaction, pushing its changes onto the undo stack
+    pub fn commit(&mut self) -> Result<(), UcreError> {
+        let group = self
+            .history
+            .open
+            .take()
+            .ok_or(UcreError::validate("No open transaction to commit"))?;
+        self.history.push_group(group);
+        Ok(())
+    }
+
+    /// closes the open transaction, reverting all of its changes
+    pub fn rollback(&mut self) -> Result<(), UcreError> {
+        let group = self
+            .history
+            .open
+            .take()
+            .ok_or(UcreError::validate("No open transaction to roll back"))?;
+        self.replay(group)?;
+        Ok(())
+    }
+
+    /// runs f inside a transaction, committing it if f succeeds and rolling it back otherwise
+    pub fn transaction<T>(
+        &mut self,
+        f: impl FnOnce(&mut Document) -> Result<T, UcreError>,
+    ) -> Result<T, UcreError> {
+        self.begin()?;
+        match f(self) {
+            Ok(t) => {
+                self.commit()?;
+                Ok(t)
+            }
+            Err(e) => {
+                self.rollback()?;
+                Err(e)
+            }
+        }
+    }
+
+    /// reverts the last group of changes, returns false if there was nothing to undo
+    pub fn undo(&mut self) -> Result<bool, UcreError> {
+        self.check_closed()?;
+        let Some(group) = self.history.undo.pop_back() else {
+            return Ok(false);
+        };
+        let redo = self.replay(group)?;
+        if !redo.is_empty() {
+            self.history.redo.push(redo);
+        }
+        Ok(true)
+    }
+
+    /// reapplies the last undone group of changes, returns false if there was nothing to redo
+    pub fn redo(&mut self) -> Result<bool, UcreError> {
+        self.check_closed()?;
+        let Some(group) = self.history.redo.pop() else {
+            return Ok(false);
+        };
+        let undo = self.replay(group)?;
+        if !undo.is_empty() {
+            self.history.undo.push_back(undo);
+        }
+        Ok(true)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.redo.is_empty()
+    }
+
+    /// sets the number of undo steps kept, older steps are dropped, 0 disables the history.
+    /// Transactions are still rolled back without history.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history.limit = limit;
+        while self.history.undo.len() > limit {
+            self.history.undo.pop_front();
+        }
+        if limit == 0 {
+            self.history.redo.clear();
+        }
+    }
+
+    /// drops the undo and redo stacks, an open transaction stays open
+    pub fn clear_history(&mut self) {
+        self.history.undo.clear();
+        self.history.redo.clear();
+    }
+
+    /// replay applies the inverse edits of group in reverse and returns the edits reverting them
+    fn replay(&mut self, group: Vec<Edit>) -> Result<Vec<Edit>, UcreError> {
+        self.history.replay = Some(vec![]);
+        let result = group.iter().rev().try_for_each(|e| self.apply(e));
+        let inverse = self.history.replay.take().unwrap_or_default();
+        result.map(|_| inverse)
+    }
+
+    fn check_closed(&self) -> Result<(), UcreError> {
+        match self.history.open {
+            Some(_) => Err(UcreError::validate(
+                "Can't undo or redo inside of a transaction",
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::uom::{
+        nodes::{Container, Heading, Paragraph},
+        AttributeValue, Document, Node, UcreError,
+    };
+
+    fn doc() -> Document {
+        let mut heading = Heading::with_text(1, "Hello");
+        heading.set_attr("id".into(), "hello".into());
+        let doc = Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![Box::new(
+                Paragraph::with_text("text"),
+            )])),
+        ])
+        .expect("failed to build document");
+        assert!(!doc.can_undo());
+        doc
+    }
+
+    #[test]
+    fn undo_redo() {
+        let original = doc();
+        let mut doc = doc();
+        let h = doc.by_id("hello").expect("heading exists");
+        let container = doc.children(doc.root())[1];
+        doc.set_text(h, "Changed".into()).expect("set text");
+        doc.set_attr(h, "level".into(), AttributeValue::Number(3.0))
+            .expect("set attr");
+        doc.remove_attr(h, "id").expect("remove attr");
+        doc.move_node(h, container, 1).expect("move");
+        let p = doc.children(container)[0];
+        doc.remove(p).expect("remove");
+        doc.append(doc.root(), Box::new(Paragraph::with_text("new")))
+            .expect("append");
+        let changed = doc.to_string();
+
+        while doc.undo().expect("undo") {}
+        assert!(doc == original);
+        assert!(doc.by_id("hello").is_some());
+        while doc.redo().expect("redo") {}
+        assert_eq!(doc.to_string(), changed);
+        assert!(doc.by_id("hello").is_none());
+    }
+
+    #[test]
+    fn transactions() {
+        let original = doc();
+        let mut doc = doc();
+        let h = doc.by_id("hello").expect("heading exists");
+
+        doc.begin().expect("begin");
+        assert!(doc.begin().is_err());
+        doc.set_text(h, "a".into()).expect("set text");
+        doc.set_text(h, "b".into()).expect("set text");
+        assert!(doc.undo().is_err());
+        doc.commit().expect("commit");
+        assert!(doc.undo().expect("undo"));
+        assert!(doc == original);
+        assert!(!doc.can_undo());
+        assert!(doc.redo().expect("redo"));
+        assert_eq!(doc.get(h).and_then(|n| n.text()), Some("b"));
+
+        let result = doc.transaction(|doc| {
+            doc.set_text(h, "c".into())?;
+            doc.remove(h)?;
+            Err::<(), _>(UcreError::validate("abort"))
+        });
+        assert!(result.is_err());
+        assert_eq!(doc.len(), original.len());
+        let h = doc.by_id("hello").expect("heading restored");
+        assert_eq!(doc.get(h).and_then(|n| n.text()), Some("b"));
+    }
+
+    #[test]
+    fn limit() {
+        let mut doc = doc();
+        let h = doc.by_id("hello").expect("heading exists");
+        doc.set_history_limit(2);
+        for text in ["a", "b", "c"] {
+            doc.set_text(h, text.into()).expect("set text");
+        }
+        assert!(doc.undo().expect("undo"));
+        assert!(doc.undo().expect("undo"));
+        assert!(!doc.undo().expect("undo"));
+        assert_eq!(doc.get(h).and_then(|n| n.text()), Some("a"));
+
+        doc.set_history_limit(0);
+        doc.set_text(h, "d".into()).expect("set text");
+        assert!(!doc.can_undo() && !doc.can_redo());
+    }
+
+    #[test]
+    fn rollback_without_history() {
+        let mut doc = doc();
+        doc.set_history_limit(0);
+        let original = doc.to_string();
+        let h = doc.by_id("hello").expect("heading exists");
+
+        doc.begin().expect("begin");
+        doc.set_text(h, "a".into()).expect("set text");
+        doc.remove_attr(h, "id").expect("remove attr");
+        doc.rollback().expect("rollback");
+        assert_eq!(doc.to_string(), original);
+
+        let result = doc.transaction(|doc| {
+            doc.remove(h)?;
+            Err::<(), _>(UcreError::validate("abort"))
+        });
+        assert!(result.is_err());
+        assert_eq!(doc.to_string(), original);
+
+        let h = doc.by_id("hello").expect("heading restored");
+        doc.transaction(|doc| doc.set_text(h, "b".into()))
+            .expect("commit");
+        assert!(!doc.can_undo() && !doc.can_redo());
+    }
+}
@@ -0,0 +1,146 @@
+/// Inline is a single run of formatted text inside a node supporting inline content, written in an
+/// `inline {}` block in ucre lang:
+///
+/// ```text
+/// paragraph {
+///     inline {
+///         text "This is "
+///         strong "bold"
+///         emphasis {
+///             text "very "
+///             strong "important"
+///         }
+///         code "x + 1"
+///         link "https://xnacly.me" "a link"
+///         break
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Emphasis(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Code(String),
+    Link { href: String, content: Vec<Inline> },
+    LineBreak,
+}
+
+impl Inline {
+    /// the name of the run in ucre lang
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Text(_) => "text",
+            Self::Emphasis(_) => "emphasis",
+            Self::Strong(_) => "strong",
+            Self::Code(_) => "code",
+            Self::Link { .. } => "link",
+            Self::LineBreak => "break",
+        }
+    }
+}
+
+/// plain_text flattens runs into their text without formatting, a [Inline::LineBreak] becomes `\n`
+pub fn plain_text(runs: &[Inline]) -> String {
+    let mut s = String::new();
+    fn walk(runs: &[Inline], s: &mut String) {
+        for run in runs {
+            match run {
+                Inline::Text(t) | Inline::Code(t) => s.push_str(t),
+                Inline::Emphasis(c) | Inline::Strong(c) | Inline::Link { content: c, .. } => {
+                    walk(c, s)
+                }
+                Inline::LineBreak => s.push('\n'),
+            }
+        }
+    }
+    walk(runs, &mut s);
+    s
+}
+
+/// RichText holds the inline runs of a node and their flattened plain text, so [super::Node::text]
+/// stays a fast path for nodes with inline content.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RichText {
+    plain: String,
+    runs: Vec<Inline>,
+}
+
+impl RichText {
+    pub fn as_str(&self) -> &str {
+        &self.plain
+    }
+
+    pub fn runs(&self) -> &[Inline] {
+        &self.runs
+    }
+
+    /// replaces all runs with a single unformatted [Inline::Text]
+    pub fn set_plain(&mut self, s: String) {
+        self.runs = if s.is_empty() {
+            vec![]
+        } else {
+            vec![Inline::Text(s.clone())]
+        };
+        self.plain = s;
+    }
+
+    /// replaces all runs, empty [Inline::Text] runs are dropped
+    pub fn set_runs(&mut self, mut runs: Vec<Inline>) {
+        runs.retain(|r| !matches!(r, Inline::Text(t) if t.is_empty()));
+        self.plain = plain_text(&runs);
+        self.runs = runs;
+    }
+
+    /// true if the runs contain formatting, false if they are expressible as plain text
+    pub fn is_formatted(&self) -> bool {
+        !matches!(self.runs.as_slice(), [] | [Inline::Text(_)])
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(s: &str) -> Self {
+        let mut r = RichText::default();
+        r.set_plain(s.to_string());
+        r
+    }
+}
+
+impl From<Vec<Inline>> for RichText {
+    fn from(runs: Vec<Inline>) -> Self {
+        let mut r = RichText::default();
+        r.set_runs(runs);
+        r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{plain_text, Inline, RichText};
+
+    #[test]
+    fn flatten() {
+        let runs = vec![
+            Inline::Text("This is ".into()),
+            Inline::Strong(vec![Inline::Emphasis(vec![Inline::Text("bold".into())])]),
+            Inline::LineBreak,
+            Inline::Link {
+                href: "https://xnacly.me".into(),
+                content: vec![Inline::Code("x".into())],
+            },
+        ];
+        assert_eq!(plain_text(&runs), "This is bold\nx");
+        let r = RichText::from(runs);
+        assert_eq!(r.as_str(), "This is bold\nx");
+        assert!(r.is_formatted());
+    }
+
+    #[test]
+    fn plain() {
+        let mut r = RichText::from("text");
+        assert_eq!(r.runs(), &[Inline::Text("text".into())]);
+        assert!(!r.is_formatted());
+        r.set_plain(String::new());
+        assert!(r.runs().is_empty());
+    }
+}
@@ -0,0 +1,640 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    nodes, AttributeValue, Color, Document, File, Inline, MetaData, NodeId, NodeKind, Predicate,
+    UcreError, Unit,
+};
+use crate::types::style::{
+    Condition, Declaration, Keyframe, Keyframes, Rule, State, Stylesheet, Theme,
+};
+
+/// JSON_VERSION is the version of the JSON schema, bumped on incompatible changes
+pub const JSON_VERSION: u64 = 1;
+
+/// JSON import and export of a [File].
+///
+/// The schema, all keys except `version` are optional on import:
+///
+/// ```text
+/// file     = { "version": 1, "meta-data": { key: value }, "doc": [node], "style": [rule],
+///              "imports": [string],
+///              "variables": [declaration], "themes": [theme], "keyframes": [keyframes],
+///              "lua": string }
+/// node     = { "kind": string, "attributes": { key: value }, "text": string,
+///              "inline": [run], "children": [node] }
+/// value    = string | number | boolean | [value]
+///          | { "length": number, "unit": "px" | "pt" | "mm" | "em" | "%" | "vw" | "vh" | "ms" | "s" }
+///          | { "color": "#rrggbb" | "#rrggbbaa" }
+///          | { "ref": string } | { "var": string } | { "keyword": string }
+/// run      = "break"
+///          | { "text": string } | { "code": string }
+///          | { "strong": [run] } | { "emphasis": [run] }
+///          | { "link": string, "content": [run] }
+/// rule     = { "kind": string, "matches": [[key] | [key, value]],
+///              "states": ["hover" | "focus" | "active" | "visited"],
+///              "declarations": [declaration], "rules": [rule], "when": [[name, value]] }
+/// declaration = [property, value]
+/// theme    = { "name": string, "variables": [declaration] }
+/// keyframes = { "name": string, "frames": [{ "offset": number, "declarations": [declaration] }] }
+/// ```
+///
+/// `kind` is the node name used in ucre lang, e.g. `list-item`. A node holds either `text` or,
+/// for formatted text, `inline`; both are omitted if the node has no text.
+impl File {
+    pub fn to_json(&self) -> String {
+        let meta_data = self
+            .meta_data()
+            .iter()
+            .map(|(k, v)| (k.to_string(), value_to_json(v)))
+            .collect::<Map<_, _>>();
+        let doc = self.doc();
+        let value = json!({
+            "version": JSON_VERSION,
+            "meta-data": meta_data,
+            "doc": doc.children(doc.root()).iter().map(|c| node_to_json(doc, *c)).collect::<Vec<_>>(),
+            "style": self.style().rules.iter().map(rule_to_json).collect::<Vec<_>>(),
+            "imports": self.style().imports,
+            "variables": declarations_to_json(&self.style().variables),
+            "themes": self.style().themes.iter().map(|t| json!({
+                "name": t.name,
+                "variables": declarations_to_json(&t.variables),
+            })).collect::<Vec<_>>(),
+            "keyframes": self.style().keyframes.iter().map(|k| json!({
+                "name": k.name,
+                "frames": k.frames.iter().map(|f| json!({
+                    "offset": value_to_json(&AttributeValue::Number(f.offset)),
+                    "declarations": declarations_to_json(&f.declarations),
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+            "lua": self.lua(),
+        });
+        serde_json::to_string_pretty(&value).expect("serializing a Value can't fail")
+    }
+
+    pub fn from_json(s: &str) -> Result<File, UcreError> {
+        let value: Value =
+            serde_json::from_str(s).map_err(|e| UcreError::parse(format!("json: {e}")))?;
+        let obj = value
+            .as_object()
+            .ok_or_else(|| error("file has to be an object"))?;
+        match obj.get("version").and_then(Value::as_u64) {
+            Some(JSON_VERSION) => {}
+            Some(v) => return Err(UcreError::parse(format!("json: Unsupported version {v}"))),
+            None => return Err(error("file is missing its version")),
+        }
+
+        let mut meta_data = MetaData::new();
+        if let Some(m) = obj.get("meta-data") {
+            let m = m
+                .as_object()
+                .ok_or_else(|| error("meta-data has to be an object"))?;
+            for (k, v) in m {
+                meta_data.set(k.clone(), value_from_json(v)?)?;
+            }
+        }
+
+        let mut doc = Document::new();
+        let root = doc.root();
+        for node in array(obj.get("doc"), "doc")? {
+            node_from_json(&mut doc, root, node)?;
+        }
+
+        let style = Stylesheet {
+            imports: array(obj.get("imports"), "imports")?
+                .iter()
+                .map(|i| {
+                    i.as_str()
+                        .map(String::from)
+                        .ok_or_else(|| error("import has to be a string"))
+                })
+                .collect::<Result<_, _>>()?,
+            rules: array(obj.get("style"), "style")?
+                .iter()
+                .map(rule_from_json)
+                .collect::<Result<_, _>>()?,
+            variables: declarations_from_json(obj.get("variables"), "variables")?,
+            themes: array(obj.get("themes"), "themes")?
+                .iter()
+                .map(theme_from_json)
+                .collect::<Result<_, _>>()?,
+            keyframes: array(obj.get("keyframes"), "keyframes")?
+                .iter()
+                .map(keyframes_from_json)
+                .collect::<Result<_, _>>()?,
+        };
+
+        let lua = match obj.get("lua") {
+            None => String::new(),
+            Some(l) => l
+                .as_str()
+                .ok_or_else(|| error("lua has to be a string"))?
+                .to_string(),
+        };
+        Ok(File::new(meta_data, lua, doc).with_style(style))
+    }
+}
+
+fn error(msg: &str) -> UcreError {
+    UcreError::parse(format!("json: {msg}"))
+}
+
+/// array returns the elements of v, an absent v is an empty array
+fn array<'v>(v: Option<&'v Value>, what: &str) -> Result<&'v [Value], UcreError> {
+    match v {
+        None => Ok(&[]),
+        Some(Value::Array(a)) => Ok(a),
+        Some(_) => Err(UcreError::parse(format!("json: {what} has to be an array"))),
+    }
+}
+
+fn node_to_json(doc: &Document, id: NodeId) -> Value {
+    let Some(node) = doc.get(id) else {
+        return Value::Null;
+    };
+    let mut obj = Map::new();
+    obj.insert("kind".into(), node.kind().name().into());
+    let mut attr = node.attr().iter().collect::<Vec<_>>();
+    attr.sort_by_key(|(k, _)| *k);
+    obj.insert(
+        "attributes".into(),
+        attr.into_iter()
+            .map(|(k, v)| (k.clone(), value_to_json(v)))
+            .collect::<Map<_, _>>()
+            .into(),
+    );
+    match node.inline() {
+        Some(runs @ [_, ..]) if !matches!(runs, [Inline::Text(_)]) => {
+            obj.insert("inline".into(), runs_to_json(runs));
+        }
+        _ => {
+            if let Some(text) = node.text().filter(|t| !t.is_empty()) {
+                obj.insert("text".into(), text.into());
+            }
+        }
+    }
+    let children = doc.children(id);
+    if !children.is_empty() {
+        obj.insert(
+            "children".into(),
+            children.iter().map(|c| node_to_json(doc, *c)).collect(),
+        );
+    }
+    obj.into()
+}
+
+fn node_from_json(doc: &mut Document, parent: NodeId, v: &Value) -> Result<(), UcreError> {
+    let obj = v
+        .as_object()
+        .ok_or_else(|| error("node has to be an object"))?;
+    let name = obj
+        .get("kind")
+        .and_then(Value::as_str)
+        .ok_or_else(|| error("node is missing its kind"))?;
+    let kind = NodeKind::resolve(name)
+        .ok_or_else(|| UcreError::parse(format!("json: Unknown node kind '{name}'")))?;
+    let mut node = nodes::new(kind);
+    if let Some(attr) = obj.get("attributes") {
+        let attr = attr
+            .as_object()
+            .ok_or_else(|| error("attributes have to be an object"))?;
+        for (k, v) in attr {
+            node.set_attr(k.clone(), value_from_json(v)?);
+        }
+    }
+    if let Some(text) = obj.get("text") {
+        let text = text
+            .as_str()
+            .ok_or_else(|| error("text has to be a string"))?;
+        node.set_text(text.to_string())?;
+    }
+    if let Some(runs) = obj.get("inline") {
+        node.set_inline(runs_from_json(runs)?)?;
+    }
+    let id = doc.append(parent, node)?;
+    for child in array(obj.get("children"), "children")? {
+        node_from_json(doc, id, child)?;
+    }
+    Ok(())
+}
+
+fn rule_to_json(rule: &Rule) -> Value {
+    let matches = rule
+        .matches
+        .iter()
+        .map(|p| match p {
+            Predicate::Exists(k) => json!([k]),
+            Predicate::Equals(k, v) => json!([k, value_to_json(v)]),
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "kind": rule.kind.name(),
+        "matches": matches,
+        "states": rule.states.iter().map(State::name).collect::<Vec<_>>(),
+        "declarations": declarations_to_json(&rule.declarations),
+        "rules": rule.rules.iter().map(rule_to_json).collect::<Vec<_>>(),
+        "when": rule
+            .conditions
+            .iter()
+            .map(|c| json!([c.name(), value_to_json(&c.value())]))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn rule_from_json(v: &Value) -> Result<Rule, UcreError> {
+    let obj = v
+        .as_object()
+        .ok_or_else(|| error("rule has to be an object"))?;
+    let name = obj
+        .get("kind")
+        .and_then(Value::as_str)
+        .ok_or_else(|| error("rule is missing its kind"))?;
+    let kind = NodeKind::resolve(name)
+        .ok_or_else(|| UcreError::parse(format!("json: Invalid node kind '{name}'")))?;
+    let mut rule = Rule::new(kind);
+    for p in array(obj.get("matches"), "matches")? {
+        rule.matches.push(match p.as_array().map(Vec::as_slice) {
+            Some([Value::String(k)]) => Predicate::Exists(k.clone()),
+            Some([Value::String(k), v]) => Predicate::Equals(k.clone(), value_from_json(v)?),
+            _ => return Err(error("predicate has to be [key] or [key, value]")),
+        });
+    }
+    for s in array(obj.get("states"), "states")? {
+        let name = s
+            .as_str()
+            .ok_or_else(|| error("state has to be a string"))?;
+        rule.states.push(
+            State::from_name(name)
+                .ok_or_else(|| UcreError::parse(format!("json: Unknown state '{name}'")))?,
+        );
+    }
+    rule.declarations = declarations_from_json(obj.get("declarations"), "declarations")?;
+    for nested in array(obj.get("rules"), "rules")? {
+        rule.rules.push(rule_from_json(nested)?);
+    }
+    for c in array(obj.get("when"), "when")? {
+        rule.conditions.push(match c.as_array().map(Vec::as_slice) {
+            Some([Value::String(name), v]) => Condition::new(name, &value_from_json(v)?)?,
+            _ => return Err(error("condition has to be [name, value]")),
+        });
+    }
+    Ok(rule)
+}
+
+fn theme_from_json(v: &Value) -> Result<Theme, UcreError> {
+    let obj = v
+        .as_object()
+        .ok_or_else(|| error("theme has to be an object"))?;
+    Ok(Theme {
+        name: obj
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| error("theme is missing its name"))?
+            .to_string(),
+        variables: declarations_from_json(obj.get("variables"), "variables")?,
+    })
+}
+
+fn keyframes_from_json(v: &Value) -> Result<Keyframes, UcreError> {
+    let obj = v
+        .as_object()
+        .ok_or_else(|| error("keyframes have to be an object"))?;
+    let name = obj
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| error("keyframes are missing their name"))?;
+    let mut frames = vec![];
+    for f in array(obj.get("frames"), "frames")? {
+        let offset = f
+            .get("offset")
+            .and_then(Value::as_f64)
+            .ok_or_else(|| error("keyframe is missing its offset"))?;
+        frames.push(Keyframe {
+            offset,
+            declarations: declarations_from_json(f.get("declarations"), "declarations")?,
+        });
+    }
+    Ok(Keyframes {
+        name: name.to_string(),
+        frames,
+    })
+}
+
+fn declarations_to_json(declarations: &[Declaration]) -> Value {
+    declarations
+        .iter()
+        .map(|d| json!([d.property, value_to_json(&d.value)]))
+        .collect()
+}
+
+fn declarations_from_json(v: Option<&Value>, what: &str) -> Result<Vec<Declaration>, UcreError> {
+    array(v, what)?
+        .iter()
+        .map(|d| match d.as_array().map(Vec::as_slice) {
+            Some([Value::String(p), v]) => Ok(Declaration::new(p, value_from_json(v)?)),
+            _ => Err(error("declaration has to be [property, value]")),
+        })
+        .collect()
+}
+
+fn value_to_json(v: &AttributeValue) -> Value {
+    match v {
+        AttributeValue::String(s) => s.as_str().into(),
+        // integral numbers are written without a fraction, `5` instead of `5.0`
+        AttributeValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => (*n as i64).into(),
+        AttributeValue::Number(n) => (*n).into(),
+        AttributeValue::Boolean(b) => (*b).into(),
+        AttributeValue::List(l) => l.iter().map(value_to_json).collect(),
+        AttributeValue::Length(n, u) => json!({ "length": n, "unit": u.suffix() }),
+        AttributeValue::Color(c) => json!({ "color": c.to_string() }),
+        AttributeValue::NodeRef(r) => json!({ "ref": r }),
+        AttributeValue::Var(v) => json!({ "var": v }),
+        AttributeValue::Keyword(k) => json!({ "keyword": k }),
+    }
+}
+
+fn value_from_json(v: &Value) -> Result<AttributeValue, UcreError> {
+    Ok(match v {
+        Value::String(s) => AttributeValue::String(s.clone()),
+        Value::Number(n) => AttributeValue::Number(
+            n.as_f64()
+                .ok_or_else(|| UcreError::parse(format!("json: {n} is not a valid number")))?,
+        ),
+        Value::Bool(b) => AttributeValue::Boolean(*b),
+        Value::Array(a) => {
+            AttributeValue::List(a.iter().map(value_from_json).collect::<Result<_, _>>()?)
+        }
+        Value::Object(o) => {
+            if let (Some(n), Some(u)) = (o.get("length"), o.get("unit")) {
+                let n = n
+                    .as_f64()
+                    .ok_or_else(|| error("length has to be a number"))?;
+                let u = u
+                    .as_str()
+                    .and_then(Unit::from_suffix)
+                    .ok_or_else(|| UcreError::parse(format!("json: Unknown unit {u}")))?;
+                AttributeValue::Length(n, u)
+            } else if let Some(c) = o.get("color") {
+                let c = c
+                    .as_str()
+                    .and_then(|c| c.strip_prefix('#'))
+                    .and_then(Color::from_hex)
+                    .ok_or_else(|| UcreError::parse(format!("json: Invalid color {c}")))?;
+                AttributeValue::Color(c)
+            } else if let Some(r) = o.get("ref") {
+                AttributeValue::NodeRef(
+                    r.as_str()
+                        .ok_or_else(|| error("ref has to be a string"))?
+                        .to_string(),
+                )
+            } else if let Some(name) = o.get("var") {
+                AttributeValue::Var(
+                    name.as_str()
+                        .ok_or_else(|| error("var has to be a string"))?
+                        .to_string(),
+                )
+            } else if let Some(k) = o.get("keyword") {
+                AttributeValue::Keyword(
+                    k.as_str()
+                        .ok_or_else(|| error("keyword has to be a string"))?
+                        .to_string(),
+                )
+            } else {
+                return Err(UcreError::parse(format!("json: Unknown value {v}")));
+            }
+        }
+        Value::Null => return Err(error("null is not a valid value")),
+    })
+}
+
+fn runs_to_json(runs: &[Inline]) -> Value {
+    runs.iter()
+        .map(|r| match r {
+            Inline::LineBreak => "break".into(),
+            Inline::Text(s) | Inline::Code(s) => json!({ r.name(): s }),
+            Inline::Strong(c) | Inline::Emphasis(c) => json!({ r.name(): runs_to_json(c) }),
+            Inline::Link { href, content } => {
+                json!({ "link": href, "content": runs_to_json(content) })
+            }
+        })
+        .collect()
+}
+
+fn runs_from_json(v: &Value) -> Result<Vec<Inline>, UcreError> {
+    let runs = v
+        .as_array()
+        .ok_or_else(|| error("inline has to be an array"))?;
+    runs.iter()
+        .map(|r| {
+            if r.as_str() == Some("break") {
+                return Ok(Inline::LineBreak);
+            }
+            let o = r
+                .as_object()
+                .ok_or_else(|| UcreError::parse(format!("json: Unknown inline run {r}")))?;
+            let string = |k: &str| {
+                o[k].as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| UcreError::parse(format!("json: {k} has to be a string")))
+            };
+            if o.contains_key("text") {
+                Ok(Inline::Text(string("text")?))
+            } else if o.contains_key("code") {
+                Ok(Inline::Code(string("code")?))
+            } else if let Some(c) = o.get("strong") {
+                Ok(Inline::Strong(runs_from_json(c)?))
+            } else if let Some(c) = o.get("emphasis") {
+                Ok(Inline::Emphasis(runs_from_json(c)?))
+            } else if o.contains_key("link") {
+                Ok(Inline::Link {
+                    href: string("link")?,
+                    content: match o.get("content") {
+                        Some(c) => runs_from_json(c)?,
+                        None => vec![],
+                    },
+                })
+            } else {
+                Err(UcreError::parse(format!("json: Unknown inline run {r}")))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{
+        style::{Condition, Declaration, Keyframe, Keyframes, Rule, State, Stylesheet, Theme},
+        uom::{
+            nodes::{Container, Heading, Image, Paragraph},
+            AttributeValue, Color, Document, File, Inline, MetaData, Node, NodeKind, Predicate,
+            Unit,
+        },
+    };
+
+    fn file() -> File {
+        let mut heading = Heading::with_text(5, "Hello World");
+        heading.set_attr("id".into(), "hello-world".into());
+        heading.set_attr("fontSize".into(), AttributeValue::Length(12.0, Unit::Pt));
+        heading.set_attr(
+            "color".into(),
+            AttributeValue::Color(Color::rgba(0xf5, 0x23, 0xad, 255)),
+        );
+        heading.set_attr(
+            "misc".into(),
+            AttributeValue::List(vec![
+                true.into(),
+                AttributeValue::NodeRef("hello-world".into()),
+            ]),
+        );
+        let mut paragraph = Paragraph::new();
+        paragraph
+            .set_inline(vec![
+                Inline::Strong(vec![Inline::Text("a".into())]),
+                Inline::LineBreak,
+                Inline::Link {
+                    href: "https://xnacly.me".into(),
+                    content: vec![Inline::Code("b".into())],
+                },
+            ])
+            .expect("paragraphs support inline content");
+        let doc = Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![
+                Box::new(paragraph),
+                Box::new(Image::with_href("avatar.webp", "avatar")),
+            ])),
+        ])
+        .expect("failed to build document");
+        let mut meta_data = MetaData::new();
+        meta_data.set_title("Hello World");
+        meta_data.set_keywords(&["hello", "world"]);
+        let mut rule = Rule::new(NodeKind::Container);
+        rule.matches = vec![
+            Predicate::Exists("id".into()),
+            Predicate::Equals("text".into(), "a".into()),
+        ];
+        rule.states = vec![State::Hover, State::Visited];
+        rule.declarations = vec![
+            Declaration::new("radius", AttributeValue::Length(50.0, Unit::Percent)),
+            Declaration::new("radius", AttributeValue::Number(1.0)),
+            Declaration::new("color", AttributeValue::Var("accent".into())),
+        ];
+        rule.rules.push(Rule::new(NodeKind::Image));
+        rule.conditions = vec![
+            Condition::MinWidth(40.0, Unit::Em),
+            Condition::MaxDensity(1.5),
+        ];
+        File::new(meta_data, "print(1)".into(), doc).with_style(Stylesheet {
+            imports: vec!["brand.ustyle".into()],
+            rules: vec![rule],
+            variables: vec![Declaration::new("accent", "black".into())],
+            themes: vec![Theme {
+                name: "dark".into(),
+                variables: vec![Declaration::new("accent", "white".into())],
+            }],
+            keyframes: vec![Keyframes {
+                name: "pulse".into(),
+                frames: vec![Keyframe {
+                    offset: 12.5,
+                    declarations: vec![Declaration::new(
+                        "width",
+                        AttributeValue::Length(0.5, Unit::S),
+                    )],
+                }],
+            }],
+        })
+    }
+
+    #[test]
+    fn round_trip() {
+        let file = file();
+        let json = file.to_json();
+        let imported = File::from_json(&json).expect("failed to import");
+        assert!(file == imported);
+        assert_eq!(json, imported.to_json());
+    }
+
+    #[test]
+    fn schema() {
+        let file = File::from_json(
+            r##"{
+                "version": 1,
+                "doc": [{
+                    "kind": "heading",
+                    "attributes": { "size": { "length": 50, "unit": "%" }, "color": { "color": "#fff" } },
+                    "text": "Hi"
+                }]
+            }"##,
+        )
+        .expect("failed to import");
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.children(doc.root())[0])
+            .expect("heading exists");
+        assert_eq!(heading.text(), Some("Hi"));
+        assert_eq!(
+            heading.attr()["size"],
+            AttributeValue::Length(50.0, Unit::Percent)
+        );
+        assert_eq!(
+            heading.attr()["color"],
+            AttributeValue::Color(Color::rgba(255, 255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(File::from_json("[]").is_err());
+        assert!(File::from_json(r#"{ "version": 2 }"#).is_err());
+        assert!(File::from_json(r#"{ "version": 1, "doc": [{ "kind": "Blink!" }] }"#).is_err());
+        assert!(
+            File::from_json(r#"{ "version": 1, "doc": [{ "kind": "image", "text": "a" }] }"#)
+                .is_err()
+        );
+        assert!(File::from_json(
+            r#"{ "version": 1, "doc": [{ "kind": "heading", "attributes": { "a": null } }] }"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn values() {
+        let mut meta_data = MetaData::new();
+        let values = [
+            AttributeValue::Number(1e-7),
+            AttributeValue::Number(1e21),
+            AttributeValue::Number(-0.5),
+            AttributeValue::Number(4503599627370497.0),
+            AttributeValue::String("\"ä\"\n\t{]\\".into()),
+            AttributeValue::String(String::new()),
+            AttributeValue::Length(0.25, Unit::Vh),
+            AttributeValue::Color(Color::rgba(1, 2, 3, 4)),
+            AttributeValue::List(vec![false.into(), AttributeValue::Var("a".into())]),
+            AttributeValue::Keyword("ease-out".into()),
+        ];
+        for (i, v) in values.iter().enumerate() {
+            meta_data
+                .set(format!("key{i}"), v.clone())
+                .expect("custom keys accept any value");
+        }
+        let file = File::new(meta_data, String::new(), Document::new());
+        let imported = File::from_json(&file.to_json()).expect("failed to import");
+        for (i, v) in values.iter().enumerate() {
+            assert_eq!(imported.meta_data().get(&format!("key{i}")), Some(v));
+        }
+        assert!(imported.doc().is_empty());
+        assert!(imported.lua().is_empty());
+    }
+
+    #[test]
+    fn invalid_values() {
+        // lists need two values and can't be nested, see [AttributeValue::validate]
+        for value in ["[]", "[1]", "[[1, 2], 3]", r#"{ "ref": "a b" }"#] {
+            let attr = format!(
+                r#"{{ "version": 1, "doc": [{{ "kind": "heading", "attributes": {{ "a": {value} }} }}] }}"#
+            );
+            assert!(File::from_json(&attr).is_err(), "{value}");
+            let meta_data = format!(r#"{{ "version": 1, "meta-data": {{ "a": {value} }} }}"#);
+            assert!(File::from_json(&meta_data).is_err(), "{value}");
+        }
+    }
+}
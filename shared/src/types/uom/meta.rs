@@ -0,0 +1,463 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use super::{AttributeValue, UcreError};
+
+/// MetaData holds the `meta-data {}` block of a ucre file:
+///
+/// ```text
+/// meta-data {
+///     title "Hello World"
+///     author "xnacly"
+///     description "A greeting"
+///     language "en-US"
+///     date "2024-02-29"
+///     keywords "hello" "world"
+///     version "1.0.0"
+///     theme "dark"
+///     styles "brand.ustyle" "print.ustyle"
+///     luaInstructions 1000000
+///     luaMemory 16
+///     custom-key 12
+/// }
+/// ```
+///
+/// The well-known keys above have typed accessors and are validated when set: language has to be
+/// a well-formed BCP 47 tag, date a calendar date formatted as `YYYY-MM-DD`, keywords and styles
+/// one or more strings, luaInstructions and luaMemory (in MiB) positive integers, all others a
+/// string. Any other key is kept as is.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetaData {
+    values: HashMap<String, AttributeValue>,
+}
+
+impl MetaData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&AttributeValue> {
+        self.values.get(key)
+    }
+
+    /// sets key to value, errors if key is well-known and value is not valid for it
+    pub fn set(&mut self, key: String, value: AttributeValue) -> Result<(), UcreError> {
+        value.validate_in_block()?;
+        validate(&key, &value)?;
+        self.values.insert(key, value);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<AttributeValue> {
+        self.values.remove(key)
+    }
+
+    /// iterates over all entries sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        let mut entries = self
+            .values
+            .iter()
+            .map(|(k, v)| (k.as_str(), v))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _)| *k);
+        entries.into_iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.get("title").and_then(AttributeValue::as_str)
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.values.insert("title".into(), title.into());
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.get("author").and_then(AttributeValue::as_str)
+    }
+
+    pub fn set_author(&mut self, author: &str) {
+        self.values.insert("author".into(), author.into());
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.get("description").and_then(AttributeValue::as_str)
+    }
+
+    pub fn set_description(&mut self, description: &str) {
+        self.values.insert("description".into(), description.into());
+    }
+
+    /// the BCP 47 language tag of the document, e.g. `en-US`
+    pub fn language(&self) -> Option<&str> {
+        self.get("language").and_then(AttributeValue::as_str)
+    }
+
+    pub fn set_language(&mut self, language: &str) -> Result<(), UcreError> {
+        self.set("language".into(), language.into())
+    }
+
+    pub fn date(&self) -> Option<Date> {
+        self.get("date")
+            .and_then(AttributeValue::as_str)
+            .and_then(|d| d.parse().ok())
+    }
+
+    pub fn set_date(&mut self, date: Date) {
+        self.values.insert("date".into(), date.to_string().into());
+    }
+
+    pub fn keywords(&self) -> Vec<&str> {
+        self.strings("keywords")
+    }
+
+    /// sets the keywords, a single keyword is stored as a string and no keywords remove the key,
+    /// so the meta-data is written as ucre lang that parses back into the same value
+    pub fn set_keywords(&mut self, keywords: &[&str]) {
+        if keywords.is_empty() {
+            self.values.remove("keywords");
+            return;
+        }
+        let value = keywords
+            .iter()
+            .map(|k| AttributeValue::from(*k))
+            .collect::<Vec<_>>()
+            .into();
+        self.set("keywords".into(), value)
+            .expect("keywords are strings");
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.get("version").and_then(AttributeValue::as_str)
+    }
+
+    pub fn set_version(&mut self, version: &str) {
+        self.values.insert("version".into(), version.into());
+    }
+
+    /// the name of the style theme the document is displayed in, see [crate::types::style::Theme]
+    pub fn theme(&self) -> Option<&str> {
+        self.get("theme").and_then(AttributeValue::as_str)
+    }
+
+    /// the paths of the style sheet files the document is styled with, relative to the document,
+    /// see [crate::types::style::Stylesheet]
+    pub fn styles(&self) -> Vec<&str> {
+        self.strings("styles")
+    }
+
+    /// the number of instructions the lua of the document asks to be allowed to run
+    pub fn lua_instructions(&self) -> Option<u64> {
+        self.get("luaInstructions").and_then(count)
+    }
+
+    /// the memory in MiB the lua of the document asks to be allowed to use
+    pub fn lua_memory(&self) -> Option<u64> {
+        self.get("luaMemory").and_then(count)
+    }
+
+    fn strings(&self, key: &str) -> Vec<&str> {
+        match self.get(key) {
+            Some(AttributeValue::List(l)) => l.iter().filter_map(AttributeValue::as_str).collect(),
+            Some(v) => v.as_str().into_iter().collect(),
+            None => vec![],
+        }
+    }
+}
+
+/// validate checks value against the format of the well-known key
+fn validate(key: &str, value: &AttributeValue) -> Result<(), UcreError> {
+    let string = || {
+        value
+            .as_str()
+            .ok_or_else(|| UcreError::validate(format!("meta-data: {key} has to be a string")))
+    };
+    match key {
+        "title" | "author" | "description" | "version" | "theme" => {
+            string()?;
+        }
+        "language" => {
+            let tag = string()?;
+            if !is_language_tag(tag) {
+                return Err(UcreError::validate(format!(
+                    "meta-data: '{tag}' is not a valid BCP 47 language tag"
+                )));
+            }
+        }
+        "date" => {
+            string()?.parse::<Date>()?;
+        }
+        "luaInstructions" | "luaMemory" if count(value).is_none() => {
+            return Err(UcreError::validate(format!(
+                "meta-data: {key} has to be a positive integer"
+            )));
+        }
+        "keywords" | "styles" => match value {
+            AttributeValue::String(_) | AttributeValue::Keyword(_) => {}
+            AttributeValue::List(l) if l.iter().all(|k| k.as_str().is_some()) => {}
+            _ => {
+                return Err(UcreError::validate(format!(
+                    "meta-data: {key} have to be one or more strings"
+                )))
+            }
+        },
+        _ => {}
+    }
+    Ok(())
+}
+
+/// count converts value to a positive integer, None if it isn't one or doesn't fit in a u64
+fn count(value: &AttributeValue) -> Option<u64> {
+    match value {
+        // u64::MAX as f64 is 2^64, the first number not fitting
+        AttributeValue::Number(n) if *n >= 1.0 && n.fract() == 0.0 && *n < u64::MAX as f64 => {
+            Some(*n as u64)
+        }
+        _ => None,
+    }
+}
+
+/// is_language_tag checks whether tag is a well-formed BCP 47 language tag (RFC 5646 section
+/// 2.1), irregular grandfathered tags are not supported
+pub fn is_language_tag(tag: &str) -> bool {
+    fn alpha(s: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+        len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphabetic())
+    }
+    fn alnum(s: &str, len: std::ops::RangeInclusive<usize>) -> bool {
+        len.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_alphanumeric())
+    }
+    fn private_use(subtags: &[&str]) -> bool {
+        !subtags.is_empty() && subtags.iter().all(|s| alnum(s, 1..=8))
+    }
+
+    let subtags = tag.split('-').collect::<Vec<_>>();
+    if subtags[0].eq_ignore_ascii_case("x") {
+        return private_use(&subtags[1..]);
+    }
+    if !alpha(subtags[0], 2..=8) || subtags[0].len() == 4 {
+        return false;
+    }
+    let mut i = 1;
+    let at = |i: usize| subtags.get(i).copied().unwrap_or_default();
+    if subtags[0].len() <= 3 {
+        // up to three extended language subtags
+        let start = i;
+        while i - start < 3 && alpha(at(i), 3..=3) {
+            i += 1;
+        }
+    }
+    if alpha(at(i), 4..=4) {
+        // script
+        i += 1;
+    }
+    if alpha(at(i), 2..=2) || (at(i).len() == 3 && at(i).bytes().all(|b| b.is_ascii_digit())) {
+        // region
+        i += 1;
+    }
+    while alnum(at(i), 5..=8) || (alnum(at(i), 4..=4) && at(i).as_bytes()[0].is_ascii_digit()) {
+        // variants
+        i += 1;
+    }
+    while at(i).len() == 1 && alnum(at(i), 1..=1) && !at(i).eq_ignore_ascii_case("x") {
+        // extensions, a singleton followed by at least one subtag
+        i += 1;
+        if !alnum(at(i), 2..=8) {
+            return false;
+        }
+        while alnum(at(i), 2..=8) {
+            i += 1;
+        }
+    }
+    if at(i).eq_ignore_ascii_case("x") {
+        return private_use(&subtags[i + 1..]);
+    }
+    i == subtags.len()
+}
+
+/// Date is a calendar date, written as `YYYY-MM-DD`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// creates a Date, errors if day does not exist in month of year
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, UcreError> {
+        let leap =
+            year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => {
+                return Err(UcreError::validate(format!(
+                    "meta-data: Invalid month {month}"
+                )))
+            }
+        };
+        if day == 0 || day > days {
+            return Err(UcreError::validate(format!(
+                "meta-data: Invalid day {day} for {year}-{month:02}"
+            )));
+        }
+        Ok(Date { year, month, day })
+    }
+}
+
+impl FromStr for Date {
+    type Err = UcreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            || UcreError::validate(format!("meta-data: '{s}' is not a date like 2024-02-29"));
+        let parts = s.split('-').collect::<Vec<_>>();
+        let [y, m, d] = parts.as_slice() else {
+            return Err(invalid());
+        };
+        if y.len() != 4 || m.len() != 2 || d.len() != 2 {
+            return Err(invalid());
+        }
+        let number = |p: &str| {
+            p.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| p.parse().ok())
+                .flatten()
+                .ok_or_else(invalid)
+        };
+        Date::new(number(y)?, number(m)? as u8, number(d)? as u8)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_language_tag, Date, MetaData};
+    use crate::types::uom::AttributeValue;
+
+    #[test]
+    fn language_tags() {
+        for tag in [
+            "en",
+            "en-US",
+            "de-DE-1996",
+            "zh-Hant-TW",
+            "es-419",
+            "sr-Latn",
+            "zh-yue-HK",
+            "en-a-bbb-x-a-ccc",
+            "x-whatever",
+            "hy-Latn-IT-arevela",
+        ] {
+            assert!(is_language_tag(tag), "{tag}");
+        }
+        for tag in [
+            "",
+            "e",
+            "en-",
+            "en--US",
+            "toolongtag",
+            "en-US-a",
+            "en-x",
+            "english!",
+            "de-419-DE",
+        ] {
+            assert!(!is_language_tag(tag), "{tag}");
+        }
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            "2024-02-29".parse::<Date>().ok(),
+            Some(Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            })
+        );
+        for date in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-1-01",
+            "24-01-01",
+            "2024-04-31",
+            "a",
+        ] {
+            assert!(date.parse::<Date>().is_err(), "{date}");
+        }
+        assert_eq!(
+            Date::new(2000, 2, 29).map(|d| d.to_string()).ok(),
+            Some("2000-02-29".into())
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        let mut m = MetaData::new();
+        m.set_keywords(&["only"]);
+        assert_eq!(m.get("keywords"), Some(&AttributeValue::from("only")));
+        assert_eq!(m.keywords(), vec!["only"]);
+        m.set_keywords(&[]);
+        assert!(m.get("keywords").is_none() && m.is_empty());
+    }
+
+    #[test]
+    fn accessors() {
+        let mut m = MetaData::new();
+        m.set_title("Hello");
+        m.set_keywords(&["a", "b"]);
+        assert!(m.set_language("en-US").is_ok());
+        assert!(m.set_language("en_US").is_err());
+        assert!(m.set("date".into(), "2024-02-30".into()).is_err());
+        assert!(m.set("title".into(), AttributeValue::Number(1.0)).is_err());
+        assert!(m.set("styles".into(), AttributeValue::Number(1.0)).is_err());
+        assert!(m
+            .set("luaMemory".into(), AttributeValue::Number(0.5))
+            .is_err());
+        assert!(m.set("luaInstructions".into(), "many".into()).is_err());
+        assert!(m
+            .set("luaInstructions".into(), AttributeValue::Number(1e20))
+            .is_err());
+        m.set("luaMemory".into(), AttributeValue::Number(16.0))
+            .expect("valid memory");
+        m.set("styles".into(), "brand.ustyle".into())
+            .expect("valid styles");
+        assert!(m.set("custom".into(), AttributeValue::Number(1.0)).is_ok());
+        m.set("date".into(), "2024-02-29".into())
+            .expect("valid date");
+
+        assert_eq!(m.title(), Some("Hello"));
+        assert_eq!(m.keywords(), vec!["a", "b"]);
+        assert_eq!(m.styles(), vec!["brand.ustyle"]);
+        assert_eq!(m.lua_memory(), Some(16));
+        assert_eq!(m.lua_instructions(), None);
+        assert_eq!(m.language(), Some("en-US"));
+        assert_eq!(m.date().map(|d| d.day), Some(29));
+        assert_eq!(
+            m.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            vec![
+                "custom",
+                "date",
+                "keywords",
+                "language",
+                "luaMemory",
+                "styles",
+                "title"
+            ]
+        );
+    }
+}
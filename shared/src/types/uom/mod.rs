@@ -1,15 +1,114 @@
+use std::sync::Arc;
+
+mod a11y;
+mod color;
+mod diff;
+mod document;
 mod err;
 mod file;
+mod history;
+mod inline;
+mod json;
+mod meta;
+mod mutation;
 mod node;
 pub mod nodes;
+mod printer;
+mod query;
+mod registry;
+mod value;
+mod visit;
 
+pub use self::a11y::{AccessNode, AccessTree, Issue, IssueKind, Role};
+pub use self::color::Color;
+pub use self::diff::{Edit, Path, Subtree, Text};
+pub use self::document::{Descendants, Document, NodeId};
 #[allow(unused_imports)]
-pub use self::err::UcreError;
+pub use self::err::{ErrorKind, SourceSpan, UcreError};
 pub use self::file::File;
-pub use self::node::Node;
+pub use self::history::DEFAULT_HISTORY_LIMIT;
+pub use self::inline::{plain_text, Inline, RichText};
+pub use self::meta::{is_language_tag, Date, MetaData};
+pub use self::mutation::{Dirty, Mutation, Observer, ObserverId};
+pub use self::node::{AttributeKey, Attributes, Node};
+pub use self::query::{Combinator, Compound, Predicate, Selector};
+pub use self::registry::{is_node_name, lookup, register, NodeType, ParsedHook};
+pub use self::value::{AttributeValue, Unit};
+pub use self::visit::{Control, Fold, Visitor, VisitorMut};
 
-/// defines all Kinds of nodes available for usage in ucre
+/// defines all Kinds of nodes available for usage in ucre, applications add their own kinds via
+/// [register]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NodeKind {
     Heading,
+    Paragraph,
+    Container,
     Image,
+    Link,
+    List,
+    ListItem,
+    CodeBlock,
+    Quote,
+    Table,
+    Row,
+    Cell,
+    Divider,
+    Span,
+    /// a kind registered via [register] or an unknown kind, see [nodes::Generic]
+    Custom(Arc<str>),
+}
+
+impl NodeKind {
+    /// all builtin kinds
+    pub const ALL: [NodeKind; 14] = [
+        Self::Heading,
+        Self::Paragraph,
+        Self::Container,
+        Self::Image,
+        Self::Link,
+        Self::List,
+        Self::ListItem,
+        Self::CodeBlock,
+        Self::Quote,
+        Self::Table,
+        Self::Row,
+        Self::Cell,
+        Self::Divider,
+        Self::Span,
+    ];
+
+    /// maps the node name used in ucre lang to a builtin or registered kind
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.name() == s)
+            .or_else(|| lookup(s).map(|t| Self::Custom(t.name.into())))
+    }
+
+    /// like [NodeKind::from_name], but maps any other valid node name to an unregistered
+    /// [NodeKind::Custom] kind, so documents using kinds of plugins not loaded stay usable
+    pub fn resolve(s: &str) -> Option<Self> {
+        Self::from_name(s).or_else(|| is_node_name(s).then(|| Self::Custom(s.into())))
+    }
+
+    /// the node name used in ucre lang
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Heading => "heading",
+            Self::Paragraph => "paragraph",
+            Self::Container => "container",
+            Self::Image => "image",
+            Self::Link => "link",
+            Self::List => "list",
+            Self::ListItem => "list-item",
+            Self::CodeBlock => "code-block",
+            Self::Quote => "quote",
+            Self::Table => "table",
+            Self::Row => "row",
+            Self::Cell => "cell",
+            Self::Divider => "divider",
+            Self::Span => "span",
+            Self::Custom(name) => name,
+        }
+    }
 }
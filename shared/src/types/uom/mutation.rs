@@ -0,0 +1,88 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use super::{AttributeKey, NodeId};
+
+/// Mutation records a single change made to a [super::Document] via its mutating methods
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mutation {
+    /// the text or the inline runs of node changed
+    TextChanged { node: NodeId },
+    /// attribute key of node was set
+    AttributeSet { node: NodeId, key: AttributeKey },
+    /// attribute key of node was removed
+    AttributeRemoved { node: NodeId, key: AttributeKey },
+    /// child was inserted as the index-th child of parent, including all its descendants
+    ChildInserted {
+        parent: NodeId,
+        child: NodeId,
+        index: usize,
+    },
+    /// child was removed from being the index-th child of parent, including all its descendants
+    ChildRemoved {
+        parent: NodeId,
+        child: NodeId,
+        index: usize,
+    },
+}
+
+/// Dirty flags of a node, set by mutations and cleared via [super::Document::clear_dirty]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dirty(u8);
+
+impl Dirty {
+    pub const CLEAN: Dirty = Dirty(0);
+    /// the node itself changed (text or attributes) or was newly inserted
+    pub const NODE: Dirty = Dirty(1);
+    /// children were inserted, removed or reordered
+    pub const CHILDREN: Dirty = Dirty(1 << 1);
+    /// any node below this one is dirty, propagated to all ancestors
+    pub const DESCENDANT: Dirty = Dirty(1 << 2);
+
+    pub fn contains(&self, other: Dirty) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Dirty {
+    type Output = Dirty;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Dirty(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Dirty {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+/// Observer is called with every [Mutation] made to the [super::Document] it is subscribed to
+pub type Observer = Box<dyn FnMut(&Mutation)>;
+
+/// ObserverId identifies an observer registered via [super::Document::subscribe]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(pub(super) usize);
+
+#[cfg(test)]
+mod test {
+    use super::Dirty;
+
+    #[test]
+    fn dirty() {
+        let mut d = Dirty::CLEAN;
+        assert!(d.is_clean());
+        assert!(d.contains(Dirty::CLEAN));
+        d |= Dirty::NODE;
+        assert!(d.contains(Dirty::NODE));
+        assert!(!d.contains(Dirty::CHILDREN));
+        let d = d | Dirty::DESCENDANT;
+        assert!(d.contains(Dirty::NODE | Dirty::DESCENDANT));
+        assert!(!d.contains(Dirty::NODE | Dirty::CHILDREN));
+        assert!(!d.is_clean());
+    }
+}
@@ -1,20 +1,24 @@
 use std::collections::HashMap;
 
+use super::nodes;
+use super::AttributeValue;
+use super::Inline;
 use super::NodeKind;
 use super::UcreError;
 
 pub type AttributeKey = String;
-pub type AttributeValue = Vec<String>;
 pub type Attributes = HashMap<AttributeKey, AttributeValue>;
 
 /// Node holds a singular node, its attributes, its children and other meta_data inside the UOM (ucre object model)
 ///
 /// A Node is written in ucre lang as follows:
 ///
-///     node_name {
-///         attribute_name attribute_value attribute_value
-///         ...
-///     }
+/// ```text
+/// node_name {
+///     attribute_name attribute_value attribute_value
+///     ...
+/// }
+/// ```
 ///
 /// Said node and its attributes can be modified via Node::set_*, while the access is done by Node::*
 pub trait Node {
@@ -23,15 +27,34 @@ pub trait Node {
     /// sets the contained type to s, if not supported, UcreDocumentError with cause dispatched
     fn set_text(&mut self, s: String) -> Result<(), UcreError>;
 
+    /// returns the formatted inline runs [Node::text] is the plain text of
+    fn inline(&self) -> Option<&[Inline]>;
+    /// replaces the inline runs and with them the text, if not supported, UcreError with cause dispatched
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError>;
+
     /// returns all attributes, K is specifically of type String to allow custom attribute keys
     fn attr(&self) -> &Attributes;
     /// sets attribute k with v, if k already set the value is overwritten to v
     fn set_attr(&mut self, k: AttributeKey, v: AttributeValue);
+    /// checks whether v is a valid value of attribute k of this node, called by
+    /// [super::Document] and ucc before [Node::set_attr]
+    fn validate_attr(&self, _k: &str, _v: &AttributeValue) -> Result<(), UcreError> {
+        Ok(())
+    }
+    /// removes attribute k, returning its value if it was set
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue>;
 
     /// returns all children
     fn children(&self) -> Option<&Vec<Box<dyn Node>>>;
     /// attempts to set the children of the node to c, if not supported, UcreDocumentError with cause dispatched
     fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError>;
+    /// removes and returns all children, used to move them into a [super::Document]
+    fn take_children(&mut self) -> Vec<Box<dyn Node>>;
 
     fn kind(&self) -> NodeKind;
+
+    /// reports whether a node of kind child may be a child of this node
+    fn allows_child(&self, child: NodeKind) -> bool {
+        nodes::allows_child(&self.kind(), &child)
+    }
 }
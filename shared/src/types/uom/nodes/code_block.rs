@@ -0,0 +1,105 @@
+use crate::types::uom::{
+    inline::Inline,
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// CodeBlock holds preformatted source code, defined via `code-block {}` in ucre lang.
+///
+/// [`CodeBlock::text`] holds the code, [`CodeBlock::language`] is a fast path mirroring the
+/// `language` attribute.
+#[derive(Default)]
+pub struct CodeBlock {
+    attr: Attributes,
+    text: String,
+    language: Option<String>,
+}
+
+impl CodeBlock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_code(language: Option<&str>, code: &str) -> Self {
+        let mut c = Self::new();
+        if let Some(language) = language {
+            c.set_attr("language".into(), language.into());
+        }
+        c.text = code.to_string();
+        c
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+}
+
+impl Node for CodeBlock {
+    fn text(&self) -> Option<&str> {
+        Some(&self.text)
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text = s;
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("CodeBlock has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("language", Some(s)) = (k.as_str(), v.as_str()) {
+            self.language = Some(s.to_string());
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "language" {
+            self.language = None;
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::CodeBlock
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        None
+    }
+
+    fn set_children(&mut self, _: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        Err(UcreError::validate("CodeBlock has no children"))
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CodeBlock;
+    use crate::types::uom::{Inline, Node};
+
+    #[test]
+    fn language() {
+        let mut c = CodeBlock::with_code(None, "fn main() {}");
+        assert_eq!((c.language(), c.text()), (None, Some("fn main() {}")));
+        c.set_attr("language".into(), "rust".into());
+        assert_eq!(c.language(), Some("rust"));
+        c.remove_attr("language");
+        assert_eq!(c.language(), None);
+        assert!(c.set_inline(vec![Inline::Text("a".into())]).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::types::uom::{
+    inline::Inline,
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Container groups other nodes, defined via `container {}` in ucre lang.
+///
+/// A Container has no text, only children.
+#[derive(Default)]
+pub struct Container {
+    attr: Attributes,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Container {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_children(children: Vec<Box<dyn Node>>) -> Self {
+        Container {
+            attr: Attributes::new(),
+            children,
+        }
+    }
+}
+
+impl Node for Container {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate("Container has no text"))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Container has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Container
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
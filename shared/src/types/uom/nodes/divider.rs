@@ -0,0 +1,63 @@
+use crate::types::uom::{
+    inline::Inline,
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Divider is a thematic break between blocks, defined via `divider {}` in ucre lang.
+#[derive(Default)]
+pub struct Divider {
+    attr: Attributes,
+}
+
+impl Divider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Node for Divider {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate("Divider has no text"))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Divider has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Divider
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        None
+    }
+
+    fn set_children(&mut self, _: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Divider has no children"))
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        vec![]
+    }
+}
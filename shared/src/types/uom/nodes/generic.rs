@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Generic is the node of a custom kind without a registered [crate::types::uom::NodeType], e.g.
+/// `chart {}` parsed without the plugin defining charts.
+///
+/// It behaves like a [super::Container] that also holds text, so documents using unknown kinds
+/// are kept intact and printed back as written.
+pub struct Generic {
+    name: Arc<str>,
+    attr: Attributes,
+    text: RichText,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Generic {
+    pub fn new(name: impl Into<Arc<str>>) -> Self {
+        Generic {
+            name: name.into(),
+            attr: Attributes::new(),
+            text: RichText::default(),
+            children: vec![],
+        }
+    }
+}
+
+impl Node for Generic {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Custom(self.name.clone())
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Generic;
+    use crate::types::uom::{nodes::Container, Node, NodeKind};
+
+    #[test]
+    fn custom() {
+        let mut g = Generic::new("chart");
+        assert_eq!(g.kind(), NodeKind::Custom("chart".into()));
+        assert_eq!(g.kind().name(), "chart");
+        g.set_text("a".into()).expect("generic nodes hold text");
+        assert!(g
+            .set_children(vec![
+                Box::new(Container::new()),
+                Box::new(Generic::new("bar"))
+            ])
+            .is_ok());
+        assert_eq!(g.take_children().len(), 2);
+        assert_eq!(g.text(), Some("a"));
+    }
+}
@@ -1,24 +1,63 @@
 use crate::types::uom::{
-    node::{AttributeKey, AttributeValue, Attributes},
-    Node, NodeKind, UcreError,
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
 };
 
 /// Heading signifies a Heading defined via `heading {}` in ucre lang.
 ///
 /// This means it only holds the [Heading::text] field (not the [Node::text] method).
 /// [`Heading::text`] is a fast path replacing the [Attributes::get()] call with the `text` [AttributeKey]
+/// [`Heading::level`] mirrors the `level` attribute, defaulting to 1
 pub struct Heading {
     attr: Attributes,
-    text: String,
+    text: RichText,
+    level: u8,
+}
+
+impl Default for Heading {
+    fn default() -> Self {
+        Heading {
+            attr: Attributes::new(),
+            text: RichText::default(),
+            level: 1,
+        }
+    }
+}
+
+impl Heading {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(level: u8, text: &str) -> Self {
+        let mut h = Self::new();
+        h.text = text.into();
+        h.set_attr("level".into(), AttributeValue::Number(level as f64));
+        h
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
 }
 
 impl Node for Heading {
     fn text(&self) -> Option<&str> {
-        Some(&self.text)
+        Some(self.text.as_str())
     }
 
     fn set_text(&mut self, s: String) -> Result<(), UcreError> {
-        self.text = s;
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
         Ok(())
     }
 
@@ -27,9 +66,30 @@ impl Node for Heading {
     }
 
     fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("level", Some(n)) = (k.as_str(), v.as_number()) {
+            if n.is_finite() {
+                self.level = n.clamp(1.0, 6.0) as u8;
+            }
+        }
         self.attr.insert(k, v);
     }
 
+    fn validate_attr(&self, k: &str, v: &AttributeValue) -> Result<(), UcreError> {
+        match (k, v.as_number()) {
+            ("level", Some(n)) if !n.is_finite() => Err(UcreError::validate(format!(
+                "Heading level has to be a finite number, got {n}"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "level" {
+            self.level = 1;
+        }
+        self.attr.remove(k)
+    }
+
     fn kind(&self) -> NodeKind {
         NodeKind::Heading
     }
@@ -39,6 +99,48 @@ impl Node for Heading {
     }
 
     fn set_children(&mut self, _: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
-        Err(UcreError::from_str("Heading has no children"))
+        Err(UcreError::validate("Heading has no children"))
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Heading;
+    use crate::types::uom::{AttributeValue, Document, ErrorKind, Node};
+
+    #[test]
+    fn level() {
+        let mut h = Heading::with_text(9, "a");
+        assert_eq!(h.level(), 6);
+        h.set_attr("level".into(), AttributeValue::Number(0.0));
+        assert_eq!(h.level(), 1);
+        h.set_attr("level".into(), AttributeValue::Number(f64::NAN));
+        assert_eq!(h.level(), 1, "non-finite levels keep the level");
+        h.remove_attr("level");
+        assert_eq!(h.level(), 1);
+
+        for n in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let err = Heading::new()
+                .validate_attr("level", &AttributeValue::Number(n))
+                .expect_err("non-finite level");
+            assert_eq!(err.kind(), ErrorKind::Validate);
+        }
+        assert!(Heading::new()
+            .validate_attr("level", &AttributeValue::Number(2.0))
+            .is_ok());
+
+        let mut nan = Heading::new();
+        nan.set_attr("level".into(), AttributeValue::Number(f64::NAN));
+        assert!(Document::from_nodes(vec![Box::new(nan)]).is_err());
+        let mut doc = Document::from_nodes(vec![Box::new(Heading::with_text(2, "a"))])
+            .expect("failed to build document");
+        let h = doc.children(doc.root())[0];
+        assert!(doc
+            .set_attr(h, "level".into(), AttributeValue::Number(f64::NAN))
+            .is_err());
     }
 }
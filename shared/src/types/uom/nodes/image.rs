@@ -0,0 +1,112 @@
+use crate::types::uom::{
+    inline::Inline,
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Image embeds an image defined via `image {}` in ucre lang.
+///
+/// [`Image::href`] and [`Image::alt`] are fast paths mirroring the `href` and `alt` attributes.
+#[derive(Default)]
+pub struct Image {
+    attr: Attributes,
+    href: String,
+    alt: String,
+}
+
+impl Image {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_href(href: &str, alt: &str) -> Self {
+        let mut i = Self::new();
+        i.set_attr("href".into(), href.into());
+        i.set_attr("alt".into(), alt.into());
+        i
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+
+    pub fn alt(&self) -> &str {
+        &self.alt
+    }
+}
+
+impl Node for Image {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate(
+            "Image has no text, use the alt attribute",
+        ))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Image has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        match (k.as_str(), v.as_str()) {
+            ("href", Some(s)) => self.href = s.to_string(),
+            ("alt", Some(s)) => self.alt = s.to_string(),
+            _ => {}
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        match k {
+            "href" => self.href.clear(),
+            "alt" => self.alt.clear(),
+            _ => {}
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Image
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        None
+    }
+
+    fn set_children(&mut self, _: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Image has no children"))
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Image;
+    use crate::types::uom::Node;
+
+    #[test]
+    fn attributes() {
+        let mut i = Image::new();
+        i.set_attr("href".into(), "a.png".into());
+        i.set_attr("alt".into(), "a".into());
+        assert_eq!((i.href(), i.alt()), ("a.png", "a"));
+        i.remove_attr("alt");
+        assert_eq!((i.href(), i.alt()), ("a.png", ""));
+        assert!(i.set_text("a".into()).is_err());
+        assert!(i.set_children(vec![]).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+use super::check_children;
+
+/// Link points to href, defined via `link {}` in ucre lang.
+///
+/// [`Link::href`] is a fast path mirroring the `href` attribute, [`Link::text`] holds the text
+/// displayed for the link.
+#[derive(Default)]
+pub struct Link {
+    attr: Attributes,
+    href: String,
+    text: RichText,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Link {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_href(href: &str, text: &str) -> Self {
+        let mut l = Self::new();
+        l.set_attr("href".into(), href.into());
+        l.text = text.into();
+        l
+    }
+
+    pub fn href(&self) -> &str {
+        &self.href
+    }
+}
+
+impl Node for Link {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("href", Some(s)) = (k.as_str(), v.as_str()) {
+            self.href = s.to_string();
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "href" {
+            self.href.clear();
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Link
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        check_children(NodeKind::Link, &c)?;
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Link;
+    use crate::types::uom::{Inline, Node};
+
+    #[test]
+    fn href() {
+        let mut l = Link::with_href("https://xnacly.me", "blog");
+        l.remove_attr("href");
+        assert_eq!(l.href(), "");
+        l.set_attr("href".into(), "/posts".into());
+        assert_eq!(l.href(), "/posts");
+        l.set_inline(vec![Inline::Strong(vec![Inline::Text("bold".into())])])
+            .expect("links support inline content");
+        assert_eq!(l.text(), Some("bold"));
+    }
+}
@@ -0,0 +1,185 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+use super::check_children;
+
+/// List holds [ListItem]s, defined via `list {}` in ucre lang.
+///
+/// [`List::ordered`] is a fast path mirroring the `ordered` attribute.
+#[derive(Default)]
+pub struct List {
+    attr: Attributes,
+    ordered: bool,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl List {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_items(ordered: bool, items: Vec<ListItem>) -> Self {
+        let mut l = Self::new();
+        l.set_attr("ordered".into(), ordered.into());
+        l.children = items
+            .into_iter()
+            .map(|i| Box::new(i) as Box<dyn Node>)
+            .collect();
+        l
+    }
+
+    pub fn ordered(&self) -> bool {
+        self.ordered
+    }
+}
+
+impl Node for List {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate("List has no text"))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("List has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("ordered", Some(b)) = (k.as_str(), v.as_bool()) {
+            self.ordered = b;
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "ordered" {
+            self.ordered = false;
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::List
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        check_children(NodeKind::List, &c)?;
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+/// ListItem is a single entry of a [List], defined via `list-item {}` in ucre lang.
+///
+/// [`ListItem::text`] holds the text of the item, children allow nesting lists or other content.
+#[derive(Default)]
+pub struct ListItem {
+    attr: Attributes,
+    text: RichText,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl ListItem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: &str) -> Self {
+        ListItem {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Node for ListItem {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::ListItem
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{List, ListItem};
+    use crate::types::uom::{nodes::Paragraph, Node};
+
+    #[test]
+    fn ordered() {
+        let mut l = List::with_items(true, vec![ListItem::with_text("a")]);
+        assert!(l.ordered());
+        l.remove_attr("ordered");
+        assert!(!l.ordered());
+        assert!(l.set_text("a".into()).is_err());
+
+        // items nest arbitrary content
+        let mut item = ListItem::with_text("b");
+        assert!(item
+            .set_children(vec![Box::new(List::new()), Box::new(Paragraph::new())])
+            .is_ok());
+        assert!(l.set_children(vec![Box::new(item)]).is_ok());
+    }
+}
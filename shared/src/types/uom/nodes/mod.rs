@@ -1,4 +1,145 @@
+mod code_block;
+mod container;
+mod divider;
+mod generic;
 mod heading;
+mod image;
+mod link;
+mod list;
+mod paragraph;
+mod quote;
+mod span;
+mod table;
 
-#[allow(unused_imports)]
-use self::heading::*;
+pub use self::code_block::CodeBlock;
+pub use self::container::Container;
+pub use self::divider::Divider;
+pub use self::generic::Generic;
+pub use self::heading::Heading;
+pub use self::image::Image;
+pub use self::link::Link;
+pub use self::list::{List, ListItem};
+pub use self::paragraph::Paragraph;
+pub use self::quote::Quote;
+pub use self::span::Span;
+pub use self::table::{Cell, Row, Table};
+
+use super::{lookup, Node, NodeKind, UcreError};
+
+/// creates an empty node of the given kind, a [Generic] node for unregistered custom kinds
+pub fn new(kind: NodeKind) -> Box<dyn Node> {
+    match kind {
+        NodeKind::Heading => Box::new(Heading::new()),
+        NodeKind::Paragraph => Box::new(Paragraph::new()),
+        NodeKind::Container => Box::new(Container::new()),
+        NodeKind::Image => Box::new(Image::new()),
+        NodeKind::Link => Box::new(Link::new()),
+        NodeKind::List => Box::new(List::new()),
+        NodeKind::ListItem => Box::new(ListItem::new()),
+        NodeKind::CodeBlock => Box::new(CodeBlock::new()),
+        NodeKind::Quote => Box::new(Quote::new()),
+        NodeKind::Table => Box::new(Table::new()),
+        NodeKind::Row => Box::new(Row::new()),
+        NodeKind::Cell => Box::new(Cell::new()),
+        NodeKind::Divider => Box::new(Divider::new()),
+        NodeKind::Span => Box::new(Span::new()),
+        NodeKind::Custom(name) => match lookup(&name) {
+            Some(ty) => (ty.new)(),
+            None => Box::new(Generic::new(name)),
+        },
+    }
+}
+
+/// allows_child reports whether a node of kind parent may hold a child of kind child, custom
+/// kinds decide via [Node::allows_child], this is the default allowing any child
+pub fn allows_child(parent: &NodeKind, child: &NodeKind) -> bool {
+    match parent {
+        NodeKind::Heading
+        | NodeKind::Image
+        | NodeKind::CodeBlock
+        | NodeKind::Divider
+        | NodeKind::Span => false,
+        NodeKind::Paragraph | NodeKind::Link => matches!(child, NodeKind::Span | NodeKind::Link),
+        NodeKind::List => *child == NodeKind::ListItem,
+        NodeKind::Table => *child == NodeKind::Row,
+        NodeKind::Row => *child == NodeKind::Cell,
+        NodeKind::Container
+        | NodeKind::ListItem
+        | NodeKind::Quote
+        | NodeKind::Cell
+        | NodeKind::Custom(_) => true,
+    }
+}
+
+/// errors if any of c is not allowed as a child of parent
+fn check_children(parent: NodeKind, c: &[Box<dyn Node>]) -> Result<(), UcreError> {
+    match c.iter().find(|n| !allows_child(&parent, &n.kind())) {
+        Some(n) => Err(UcreError::validate(format!(
+            "{} does not allow {} children",
+            parent.name(),
+            n.kind().name()
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::uom::AttributeValue;
+
+    #[test]
+    fn new_matches_kind() {
+        for kind in NodeKind::ALL {
+            assert_eq!(new(kind.clone()).kind(), kind);
+            assert_eq!(NodeKind::from_name(kind.name()), Some(kind));
+        }
+    }
+
+    #[test]
+    fn fast_paths() {
+        let h = Heading::with_text(3, "Hello");
+        assert_eq!((h.level(), h.text()), (3, Some("Hello")));
+
+        let i = Image::with_href("avatar.webp", "avatar");
+        assert_eq!((i.href(), i.alt()), ("avatar.webp", "avatar"));
+        assert_eq!(i.attr()["alt"], AttributeValue::from("avatar"));
+
+        let l = Link::with_href("https://xnacly.me", "blog");
+        assert_eq!((l.href(), l.text()), ("https://xnacly.me", Some("blog")));
+
+        let c = CodeBlock::with_code(Some("rust"), "fn main() {}");
+        assert_eq!(c.language(), Some("rust"));
+
+        let mut q = Quote::with_text("quoted");
+        q.set_attr("cite".into(), "someone".into());
+        assert_eq!(q.cite(), Some("someone"));
+
+        let mut cell = Cell::with_text("a");
+        cell.set_attr("header".into(), true.into());
+        assert!(cell.header());
+
+        let list = List::with_items(true, vec![ListItem::with_text("a")]);
+        assert!(list.ordered());
+        assert_eq!(list.children().map(|c| c.len()), Some(1));
+    }
+
+    #[test]
+    fn children() {
+        let mut table = Table::new();
+        assert!(table.set_children(vec![Box::new(Cell::new())]).is_err());
+        assert!(table
+            .set_children(vec![Box::new(Row::with_cells(vec![Cell::with_text("a")]))])
+            .is_ok());
+
+        let mut list = List::new();
+        assert!(list.set_children(vec![Box::new(Paragraph::new())]).is_err());
+
+        let mut p = Paragraph::with_text("text");
+        assert!(p.set_children(vec![Box::new(Span::with_text("a"))]).is_ok());
+        assert!(p.set_children(vec![Box::new(Container::new())]).is_err());
+
+        assert!(Divider::new().set_children(vec![]).is_err());
+        assert!(Container::new().set_text("a".into()).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+use super::check_children;
+
+/// Paragraph signifies a block of text defined via `paragraph {}` in ucre lang.
+///
+/// [`Paragraph::text`] holds the text, its children are restricted to inline nodes ([super::Span]
+/// and [super::Link]) following said text.
+#[derive(Default)]
+pub struct Paragraph {
+    attr: Attributes,
+    text: RichText,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Paragraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: &str) -> Self {
+        Paragraph {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Node for Paragraph {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Paragraph
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        check_children(NodeKind::Paragraph, &c)?;
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Quote signifies a quotation, defined via `quote {}` in ucre lang.
+///
+/// [`Quote::text`] holds the quoted text, [`Quote::cite`] is a fast path mirroring the `cite`
+/// attribute naming the source of the quote.
+#[derive(Default)]
+pub struct Quote {
+    attr: Attributes,
+    text: RichText,
+    cite: Option<String>,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Quote {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: &str) -> Self {
+        Quote {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn cite(&self) -> Option<&str> {
+        self.cite.as_deref()
+    }
+}
+
+impl Node for Quote {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("cite", Some(s)) = (k.as_str(), v.as_str()) {
+            self.cite = Some(s.to_string());
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "cite" {
+            self.cite = None;
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Quote
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Quote;
+    use crate::types::uom::Node;
+
+    #[test]
+    fn cite() {
+        let mut q = Quote::with_text("quoted");
+        assert_eq!(q.cite(), None);
+        q.set_attr("cite".into(), "someone".into());
+        assert_eq!(q.cite(), Some("someone"));
+        q.remove_attr("cite");
+        assert_eq!(q.cite(), None);
+        assert!(q.attr().is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+/// Span is a run of inline text inside a [super::Paragraph] or [super::Link], defined via
+/// `span {}` in ucre lang.
+#[derive(Default)]
+pub struct Span {
+    attr: Attributes,
+    text: RichText,
+}
+
+impl Span {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: &str) -> Self {
+        Span {
+            attr: Attributes::new(),
+            text: text.into(),
+        }
+    }
+}
+
+impl Node for Span {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Span
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        None
+    }
+
+    fn set_children(&mut self, _: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Span has no children"))
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        vec![]
+    }
+}
@@ -0,0 +1,257 @@
+use crate::types::uom::{
+    inline::{Inline, RichText},
+    node::{AttributeKey, Attributes},
+    AttributeValue, Node, NodeKind, UcreError,
+};
+
+use super::check_children;
+
+/// Table holds [Row]s of [Cell]s, defined via `table {}` in ucre lang.
+#[derive(Default)]
+pub struct Table {
+    attr: Attributes,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rows(rows: Vec<Row>) -> Self {
+        Table {
+            attr: Attributes::new(),
+            children: rows
+                .into_iter()
+                .map(|r| Box::new(r) as Box<dyn Node>)
+                .collect(),
+        }
+    }
+}
+
+impl Node for Table {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate("Table has no text"))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Table has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Table
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        check_children(NodeKind::Table, &c)?;
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+/// Row is a single row of a [Table], defined via `row {}` in ucre lang.
+#[derive(Default)]
+pub struct Row {
+    attr: Attributes,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Row {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_cells(cells: Vec<Cell>) -> Self {
+        Row {
+            attr: Attributes::new(),
+            children: cells
+                .into_iter()
+                .map(|c| Box::new(c) as Box<dyn Node>)
+                .collect(),
+        }
+    }
+}
+
+impl Node for Row {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn set_text(&mut self, _: String) -> Result<(), UcreError> {
+        Err(UcreError::validate("Row has no text"))
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        None
+    }
+
+    fn set_inline(&mut self, _: Vec<Inline>) -> Result<(), UcreError> {
+        Err(UcreError::validate("Row has no inline content"))
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Row
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        check_children(NodeKind::Row, &c)?;
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+/// Cell is a single cell of a [Row], defined via `cell {}` in ucre lang.
+///
+/// [`Cell::header`] is a fast path mirroring the `header` attribute.
+#[derive(Default)]
+pub struct Cell {
+    attr: Attributes,
+    text: RichText,
+    header: bool,
+    children: Vec<Box<dyn Node>>,
+}
+
+impl Cell {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_text(text: &str) -> Self {
+        Cell {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn header(&self) -> bool {
+        self.header
+    }
+}
+
+impl Node for Cell {
+    fn text(&self) -> Option<&str> {
+        Some(self.text.as_str())
+    }
+
+    fn set_text(&mut self, s: String) -> Result<(), UcreError> {
+        self.text.set_plain(s);
+        Ok(())
+    }
+
+    fn inline(&self) -> Option<&[Inline]> {
+        Some(self.text.runs())
+    }
+
+    fn set_inline(&mut self, runs: Vec<Inline>) -> Result<(), UcreError> {
+        self.text.set_runs(runs);
+        Ok(())
+    }
+
+    fn attr(&self) -> &Attributes {
+        &self.attr
+    }
+
+    fn set_attr(&mut self, k: AttributeKey, v: AttributeValue) {
+        if let ("header", Some(b)) = (k.as_str(), v.as_bool()) {
+            self.header = b;
+        }
+        self.attr.insert(k, v);
+    }
+
+    fn remove_attr(&mut self, k: &str) -> Option<AttributeValue> {
+        if k == "header" {
+            self.header = false;
+        }
+        self.attr.remove(k)
+    }
+
+    fn kind(&self) -> NodeKind {
+        NodeKind::Cell
+    }
+
+    fn children(&self) -> Option<&Vec<Box<dyn Node>>> {
+        Some(&self.children)
+    }
+
+    fn set_children(&mut self, c: Vec<Box<dyn Node>>) -> Result<(), UcreError> {
+        self.children = c;
+        Ok(())
+    }
+
+    fn take_children(&mut self) -> Vec<Box<dyn Node>> {
+        std::mem::take(&mut self.children)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cell, Row, Table};
+    use crate::types::uom::Node;
+
+    #[test]
+    fn structure() {
+        let table = Table::with_rows(vec![Row::with_cells(vec![
+            Cell::with_text("a"),
+            Cell::with_text("b"),
+        ])]);
+        let row = &table.children().expect("tables have rows")[0];
+        assert_eq!(row.children().map(|c| c.len()), Some(2));
+
+        let mut row = Row::new();
+        assert!(row.set_children(vec![Box::new(Row::new())]).is_err());
+        assert!(row.set_text("a".into()).is_err());
+
+        let mut cell = Cell::with_text("a");
+        cell.set_attr("header".into(), true.into());
+        cell.remove_attr("header");
+        assert!(!cell.header());
+    }
+}
@@ -0,0 +1,512 @@
+use std::fmt::{self, Write};
+
+use super::{AttributeValue, Document, File, Inline, NodeId, Predicate, Unit};
+use crate::types::style::{Declaration, Rule, Stylesheet};
+
+const INDENT: &str = "    ";
+
+/// Printer writes a [File] as canonically formatted ucre lang source: sections in the order
+/// meta-data, doc, style and lua, four spaces of indentation, attributes sorted by key and the text of a
+/// node after its attributes and before its children. Empty sections are omitted.
+struct Printer<'w, W: Write> {
+    w: &'w mut W,
+    depth: usize,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn line(&mut self, s: &str) -> fmt::Result {
+        for _ in 0..self.depth {
+            self.w.write_str(INDENT)?;
+        }
+        writeln!(self.w, "{s}")
+    }
+
+    fn open(&mut self, s: &str) -> fmt::Result {
+        self.line(&format!("{s} {{"))?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn close(&mut self) -> fmt::Result {
+        self.depth -= 1;
+        self.line("}")
+    }
+
+    fn file(&mut self, file: &File) -> fmt::Result {
+        let mut sections = 0;
+        if !file.meta_data().is_empty() {
+            self.open("meta-data")?;
+            for (k, v) in file.meta_data().iter() {
+                self.line(&format!("{k} {v}"))?;
+            }
+            self.close()?;
+            sections += 1;
+        }
+        if !file.doc().is_empty() {
+            if sections != 0 {
+                self.w.write_char('\n')?;
+            }
+            self.document(file.doc())?;
+            sections += 1;
+        }
+        if !file.style().is_empty() {
+            if sections != 0 {
+                self.w.write_char('\n')?;
+            }
+            self.stylesheet(file.style())?;
+            sections += 1;
+        }
+        if !file.lua().is_empty() {
+            if sections != 0 {
+                self.w.write_char('\n')?;
+            }
+            self.line("lua [")?;
+            self.depth += 1;
+            self.line(file.lua())?;
+            self.depth -= 1;
+            self.line("]")?;
+        }
+        Ok(())
+    }
+
+    fn document(&mut self, doc: &Document) -> fmt::Result {
+        self.open("doc")?;
+        for (i, child) in doc.children(doc.root()).iter().enumerate() {
+            if i != 0 {
+                self.w.write_char('\n')?;
+            }
+            self.node(doc, *child)?;
+        }
+        self.close()
+    }
+
+    fn node(&mut self, doc: &Document, id: NodeId) -> fmt::Result {
+        let Some(node) = doc.get(id) else {
+            return Ok(());
+        };
+        self.open(node.kind().name())?;
+        let mut attr = node.attr().iter().collect::<Vec<_>>();
+        attr.sort_by_key(|(k, _)| *k);
+        for (k, v) in attr {
+            if !matches!(v, AttributeValue::List(l) if l.is_empty()) {
+                self.line(&format!("{k} {v}"))?;
+            }
+        }
+        match node.inline() {
+            Some(runs @ [_, ..]) if !matches!(runs, [Inline::Text(_)]) => {
+                self.open("inline")?;
+                self.runs(runs)?;
+                self.close()?;
+            }
+            _ => {
+                if let Some(text) = node.text().filter(|t| !t.is_empty()) {
+                    self.line(&format!("text {}", AttributeValue::from(text)))?;
+                }
+            }
+        }
+        for child in doc.children(id) {
+            self.node(doc, *child)?;
+        }
+        self.close()
+    }
+
+    fn stylesheet(&mut self, style: &Stylesheet) -> fmt::Result {
+        self.open("style")?;
+        let mut first = true;
+        let mut separate = |w: &mut W| {
+            if !std::mem::take(&mut first) {
+                w.write_char('\n')?;
+            }
+            Ok(())
+        };
+        if !style.imports.is_empty() {
+            separate(self.w)?;
+            for path in &style.imports {
+                self.line(&format!("import {}", AttributeValue::from(path.as_str())))?;
+            }
+        }
+        if !style.variables.is_empty() {
+            separate(self.w)?;
+            self.declarations("vars", &style.variables)?;
+        }
+        for theme in &style.themes {
+            separate(self.w)?;
+            self.declarations(&format!("theme {}", theme.name), &theme.variables)?;
+        }
+        for keyframes in &style.keyframes {
+            separate(self.w)?;
+            self.open(&format!("keyframes {}", keyframes.name))?;
+            for frame in &keyframes.frames {
+                let offset = AttributeValue::Length(frame.offset, Unit::Percent);
+                self.declarations(&offset.to_string(), &frame.declarations)?;
+            }
+            self.close()?;
+        }
+        // consecutive rules of the same conditions share a `when` block
+        for group in style.rules.chunk_by(|a, b| a.conditions == b.conditions) {
+            let conditions = &group[0].conditions;
+            if conditions.is_empty() {
+                for rule in group {
+                    separate(self.w)?;
+                    self.rule(rule)?;
+                }
+                continue;
+            }
+            separate(self.w)?;
+            let conditions = conditions.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+            self.open(&format!("when {}", conditions.join(" ")))?;
+            for (i, rule) in group.iter().enumerate() {
+                if i != 0 {
+                    self.w.write_char('\n')?;
+                }
+                self.rule(rule)?;
+            }
+            self.close()?;
+        }
+        self.close()
+    }
+
+    fn declarations(&mut self, name: &str, declarations: &[Declaration]) -> fmt::Result {
+        self.open(name)?;
+        for d in declarations {
+            self.line(&format!("{} {}", d.property, d.value))?;
+        }
+        self.close()
+    }
+
+    /// writes rule, declarations keep their order since later ones override earlier ones
+    fn rule(&mut self, rule: &Rule) -> fmt::Result {
+        self.open(rule.kind.name())?;
+        if !rule.matches.is_empty() || !rule.states.is_empty() {
+            self.open("matches")?;
+            for p in &rule.matches {
+                match p {
+                    Predicate::Exists(k) => self.line(k)?,
+                    Predicate::Equals(k, v) => self.line(&format!("{k} {v}"))?,
+                }
+            }
+            for s in &rule.states {
+                self.line(&format!(":{}", s.name()))?;
+            }
+            self.close()?;
+        }
+        for d in &rule.declarations {
+            self.line(&format!("{} {}", d.property, d.value))?;
+        }
+        for nested in &rule.rules {
+            self.rule(nested)?;
+        }
+        self.close()
+    }
+
+    fn runs(&mut self, runs: &[Inline]) -> fmt::Result {
+        for run in runs {
+            match run {
+                Inline::Text(s) | Inline::Code(s) => self.line(&format!(
+                    "{} {}",
+                    run.name(),
+                    AttributeValue::from(s.as_str())
+                ))?,
+                Inline::LineBreak => self.line(run.name())?,
+                Inline::Emphasis(content) | Inline::Strong(content) => {
+                    self.content(run.name(), content)?
+                }
+                Inline::Link { href, content } => {
+                    let head = format!("link {}", AttributeValue::from(href.as_str()));
+                    self.content(&head, content)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// content writes head followed by a single string if content is a single text run or a
+    /// nested block of runs otherwise
+    fn content(&mut self, head: &str, content: &[Inline]) -> fmt::Result {
+        if let [Inline::Text(s)] = content {
+            return self.line(&format!("{head} {}", AttributeValue::from(s.as_str())));
+        }
+        self.open(head)?;
+        self.runs(content)?;
+        self.close()
+    }
+}
+
+/// writes id and its descendants at depth levels of indentation
+pub(super) fn write_node<W: Write>(
+    w: &mut W,
+    doc: &Document,
+    id: NodeId,
+    depth: usize,
+) -> fmt::Result {
+    Printer { w, depth }.node(doc, id)
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Printer { w: f, depth: 0 }.file(self)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Printer { w: f, depth: 0 }.document(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{
+        style::{
+            Condition, Declaration, Keyframe, Keyframes, Orientation, Rule, Stylesheet, Theme,
+        },
+        uom::{
+            nodes::{Container, Heading, Image, Paragraph},
+            AttributeValue, Color, Document, File, Inline, MetaData, Node, NodeKind, Predicate,
+            Unit,
+        },
+    };
+
+    #[test]
+    fn print() {
+        let mut heading = Heading::with_text(5, "Hello \"World\"");
+        heading.set_attr("id".into(), "hello-world".into());
+        let mut paragraph = Paragraph::new();
+        paragraph
+            .set_inline(vec![
+                Inline::Text("a ".into()),
+                Inline::Strong(vec![Inline::Emphasis(vec![Inline::Text("b".into())])]),
+                Inline::Link {
+                    href: "https://xnacly.me".into(),
+                    content: vec![Inline::Text("c".into())],
+                },
+                Inline::LineBreak,
+            ])
+            .expect("paragraphs support inline content");
+        let doc = Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![
+                Box::new(paragraph),
+                Box::new(Image::with_href("avatar.webp", "avatar")),
+            ])),
+        ])
+        .expect("failed to build document");
+        let mut meta_data = MetaData::new();
+        meta_data.set_title("Hello World");
+        let mut heading = Rule::new(NodeKind::Heading);
+        heading.matches = vec![
+            Predicate::Equals("id".into(), "hello-world".into()),
+            Predicate::Exists("text".into()),
+        ];
+        heading.declarations = vec![
+            Declaration::new("fontSize", AttributeValue::Length(12.0, Unit::Pt)),
+            Declaration::new(
+                "color",
+                AttributeValue::Color(Color::rgba(0xf5, 0x23, 0xad, 255)),
+            ),
+            Declaration::new("background", AttributeValue::Var("text".into())),
+        ];
+        let mut container = Rule::new(NodeKind::Container);
+        let mut image = Rule::new(NodeKind::Image);
+        image.declarations = vec![Declaration::new(
+            "radius",
+            AttributeValue::Length(50.0, Unit::Percent),
+        )];
+        container.rules.push(image);
+        let mut narrow = Rule::new(NodeKind::Heading);
+        narrow.conditions = vec![
+            Condition::MaxWidth(600.0, Unit::Px),
+            Condition::Orientation(Orientation::Portrait),
+        ];
+        narrow.declarations = vec![Declaration::new(
+            "fontSize",
+            AttributeValue::Length(10.0, Unit::Pt),
+        )];
+        let mut narrow_image = Rule::new(NodeKind::Image);
+        narrow_image.conditions = narrow.conditions.clone();
+        let file = File::new(meta_data, "print(1)".into(), doc).with_style(Stylesheet {
+            imports: vec!["brand.ustyle".into(), "print.ustyle".into()],
+            rules: vec![heading, container, narrow, narrow_image],
+            variables: vec![Declaration::new("text", "black".into())],
+            themes: vec![Theme {
+                name: "dark".into(),
+                variables: vec![Declaration::new("text", "white".into())],
+            }],
+            keyframes: vec![Keyframes {
+                name: "pulse".into(),
+                frames: vec![
+                    Keyframe {
+                        offset: 0.0,
+                        declarations: vec![Declaration::new("width", AttributeValue::Number(0.0))],
+                    },
+                    Keyframe {
+                        offset: 50.0,
+                        declarations: vec![],
+                    },
+                ],
+            }],
+        });
+        assert_eq!(
+            file.to_string(),
+            r#"meta-data {
+    title "Hello World"
+}
+
+doc {
+    heading {
+        id "hello-world"
+        level 5
+        text "Hello \"World\""
+    }
+
+    container {
+        paragraph {
+            inline {
+                text "a "
+                strong {
+                    emphasis "b"
+                }
+                link "https://xnacly.me" "c"
+                break
+            }
+        }
+        image {
+            alt "avatar"
+            href "avatar.webp"
+        }
+    }
+}
+
+style {
+    import "brand.ustyle"
+    import "print.ustyle"
+
+    vars {
+        text "black"
+    }
+
+    theme dark {
+        text "white"
+    }
+
+    keyframes pulse {
+        0% {
+            width 0
+        }
+        50% {
+        }
+    }
+
+    heading {
+        matches {
+            id "hello-world"
+            text
+        }
+        fontSize 12pt
+        color #f523ad
+        background $text
+    }
+
+    container {
+        image {
+            radius 50%
+        }
+    }
+
+    when maxWidth 600px orientation portrait {
+        heading {
+            fontSize 10pt
+        }
+
+        image {
+        }
+    }
+}
+
+lua [
+    print(1)
+]
+"#
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let file = File::new(MetaData::new(), String::new(), Document::new());
+        assert_eq!(file.to_string(), "");
+        let file = File::new(MetaData::new(), "print(1)".into(), Document::new());
+        assert_eq!(file.to_string(), "lua [\n    print(1)\n]\n");
+    }
+
+    #[test]
+    fn text() {
+        let mut single = Paragraph::new();
+        single
+            .set_inline(vec![Inline::Text("a".into())])
+            .expect("paragraphs support inline content");
+        let mut code = Paragraph::new();
+        code.set_inline(vec![Inline::Code("b".into())])
+            .expect("paragraphs support inline content");
+        let doc = Document::from_nodes(vec![
+            Box::new(single),
+            Box::new(Paragraph::with_text("")),
+            Box::new(code),
+        ])
+        .expect("failed to build document");
+        // a single text run is written as plain text, empty text is omitted
+        assert_eq!(
+            doc.to_string(),
+            r#"doc {
+    paragraph {
+        text "a"
+    }
+
+    paragraph {
+    }
+
+    paragraph {
+        inline {
+            code "b"
+        }
+    }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let mut heading = Heading::with_text(1, "");
+        for (k, n) in [
+            ("a", 1e-7),
+            ("b", 1e21),
+            ("c", -0.5),
+            ("d", 4503599627370497.0),
+        ] {
+            heading.set_attr(k.into(), AttributeValue::Number(n));
+        }
+        heading.set_attr(
+            "e".into(),
+            AttributeValue::List(vec![
+                AttributeValue::Length(0.25, Unit::Em),
+                AttributeValue::Color(Color::rgba(0, 0, 0, 0x80)),
+            ]),
+        );
+        let doc = Document::from_nodes(vec![Box::new(heading)]).expect("failed to build document");
+        // numbers are written without an exponent, the lexer has no syntax for one
+        assert_eq!(
+            doc.to_string(),
+            r#"doc {
+    heading {
+        a 0.0000001
+        b 1000000000000000000000
+        c -0.5
+        d 4503599627370497
+        e 0.25em #00000080
+        level 1
+    }
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,473 @@
+use std::{fmt, str::FromStr};
+
+use super::{AttributeValue, Color, Document, Node, NodeId, NodeKind, UcreError, Unit};
+
+/// Predicate matches a single attribute of a node, the `text` key matches [Node::text].
+///
+/// Predicates mirror the `matches { key value }` blocks of the style language, where each line is
+/// a [Predicate::Equals].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// attribute is set, regardless of its value
+    Exists(String),
+    /// attribute is set to the value, see [AttributeValue::matches]
+    Equals(String, AttributeValue),
+}
+
+impl Predicate {
+    pub fn matches(&self, node: &dyn Node) -> bool {
+        match self {
+            Self::Exists(k) if k == "text" => node.text().is_some_and(|t| !t.is_empty()),
+            Self::Exists(k) => node.attr().contains_key(k),
+            Self::Equals(k, v) if k == "text" => node.text().is_some() && node.text() == v.as_str(),
+            Self::Equals(k, v) => node.attr().get(k).is_some_and(|a| a.matches(v)),
+        }
+    }
+}
+
+/// Compound matches a single node by its kind, id and attribute predicates
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound {
+    /// None matches any kind
+    pub kind: Option<NodeKind>,
+    pub predicates: Vec<Predicate>,
+}
+
+impl Compound {
+    pub fn matches(&self, node: &dyn Node) -> bool {
+        self.kind.as_ref().is_none_or(|k| *k == node.kind())
+            && self.predicates.iter().all(|p| p.matches(node))
+    }
+}
+
+/// Combinator defines the relation between two [Compound]s of a [Selector]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combinator {
+    /// `a b`: b is anywhere below a, equivalent to nesting rules in the style language
+    Descendant,
+    /// `a > b`: b is a direct child of a
+    Child,
+}
+
+/// Selector selects nodes of a [Document], its syntax:
+///
+/// ```text
+/// heading                      all headings
+/// *                            all nodes
+/// #hello-world                 the node with the id hello-world
+/// heading[level=5]             headings with the level attribute set to 5
+/// image[alt]                   images with the alt attribute set
+/// heading[text="Hello World"]  headings with the text Hello World
+/// container image              images anywhere inside a container
+/// container > image            images directly inside a container
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    /// the first compound has no combinator, each following one is related to its predecessor
+    parts: Vec<(Combinator, Compound)>,
+}
+
+impl Selector {
+    pub fn new(first: Compound) -> Self {
+        Selector {
+            parts: vec![(Combinator::Descendant, first)],
+        }
+    }
+
+    /// appends compound, related to the current last compound by combinator
+    pub fn then(mut self, combinator: Combinator, compound: Compound) -> Self {
+        self.parts.push((combinator, compound));
+        self
+    }
+
+    pub fn parts(&self) -> &[(Combinator, Compound)] {
+        &self.parts
+    }
+
+    /// matches checks whether id in doc is selected by the selector
+    pub fn matches(&self, doc: &Document, id: NodeId) -> bool {
+        self.matches_where(doc, id, &|_, _| true)
+    }
+
+    /// matches_where is [Selector::matches] with filter as an additional condition for the node
+    /// matched by each compound, given the index of the compound in [Selector::parts]
+    pub fn matches_where(
+        &self,
+        doc: &Document,
+        id: NodeId,
+        filter: &dyn Fn(usize, NodeId) -> bool,
+    ) -> bool {
+        self.matches_at(doc, id, self.parts.len() - 1, filter)
+    }
+
+    fn matches_at(
+        &self,
+        doc: &Document,
+        id: NodeId,
+        part: usize,
+        filter: &dyn Fn(usize, NodeId) -> bool,
+    ) -> bool {
+        let (combinator, compound) = &self.parts[part];
+        if id == doc.root()
+            || !doc.get(id).is_some_and(|n| compound.matches(n))
+            || !filter(part, id)
+        {
+            return false;
+        }
+        if part == 0 {
+            return true;
+        }
+        match combinator {
+            Combinator::Child => doc
+                .parent(id)
+                .is_some_and(|p| self.matches_at(doc, p, part - 1, filter)),
+            Combinator::Descendant => doc
+                .ancestors(id)
+                .any(|a| self.matches_at(doc, a, part - 1, filter)),
+        }
+    }
+}
+
+impl FromStr for Selector {
+    type Err = UcreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SelectorParser {
+            chars: s.chars().collect(),
+            pos: 0,
+        }
+        .parse()
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (combinator, compound)) in self.parts.iter().enumerate() {
+            if i != 0 {
+                match combinator {
+                    Combinator::Descendant => write!(f, " ")?,
+                    Combinator::Child => write!(f, " > ")?,
+                }
+            }
+            match &compound.kind {
+                Some(k) => write!(f, "{}", k.name())?,
+                None if compound.predicates.is_empty() => write!(f, "*")?,
+                None => {}
+            }
+            for p in &compound.predicates {
+                match p {
+                    Predicate::Exists(k) => write!(f, "[{k}]")?,
+                    Predicate::Equals(k, v) => write!(f, "[{k}={v}]")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct SelectorParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl SelectorParser {
+    fn parse(mut self) -> Result<Selector, UcreError> {
+        self.skip_whitespace();
+        let mut selector = Selector::new(self.compound()?);
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.cur() {
+                None => break,
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(c) => return Err(self.error(format!("Unexpected '{c}'"))),
+            };
+            selector = selector.then(combinator, self.compound()?);
+        }
+        Ok(selector)
+    }
+
+    fn compound(&mut self) -> Result<Compound, UcreError> {
+        let mut compound = Compound::default();
+        match self.cur() {
+            Some('*') => self.pos += 1,
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.ident();
+                compound.kind = Some(
+                    NodeKind::resolve(&name)
+                        .ok_or_else(|| self.error(format!("Invalid node kind '{name}'")))?,
+                );
+            }
+            Some('#' | '[') => {}
+            _ => return Err(self.error("Expected a node kind, '*', '#' or '['".into())),
+        }
+        loop {
+            match self.cur() {
+                Some('#') => {
+                    self.pos += 1;
+                    let id = self.ident();
+                    if id.is_empty() {
+                        return Err(self.error("Missing id after '#'".into()));
+                    }
+                    compound
+                        .predicates
+                        .push(Predicate::Equals("id".into(), AttributeValue::String(id)));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    let key = self.ident();
+                    if key.is_empty() {
+                        return Err(self.error("Missing attribute name after '['".into()));
+                    }
+                    let predicate = if self.cur() == Some('=') {
+                        self.pos += 1;
+                        Predicate::Equals(key, self.value()?)
+                    } else {
+                        Predicate::Exists(key)
+                    };
+                    if self.cur() != Some(']') {
+                        return Err(self.error("Missing ']'".into()));
+                    }
+                    self.pos += 1;
+                    compound.predicates.push(predicate);
+                }
+                _ => return Ok(compound),
+            }
+        }
+    }
+
+    /// value parses an attribute value the same way ucre lang writes it
+    fn value(&mut self) -> Result<AttributeValue, UcreError> {
+        if self.cur() == Some('"') {
+            self.pos += 1;
+            let mut s = String::new();
+            loop {
+                match self.cur() {
+                    None => return Err(self.error("Unterminated string".into())),
+                    Some('"') => break,
+                    Some('\\') => {
+                        self.pos += 1;
+                        match self.cur() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some(c) => s.push(c),
+                            None => return Err(self.error("Unterminated string".into())),
+                        }
+                    }
+                    Some(c) => s.push(c),
+                }
+                self.pos += 1;
+            }
+            self.pos += 1;
+            return Ok(AttributeValue::String(s));
+        }
+        let start = self.pos;
+        while self.cur().is_some_and(|c| c != ']') {
+            self.pos += 1;
+        }
+        let raw = self.chars[start..self.pos].iter().collect::<String>();
+        Ok(parse_bare_value(raw.trim()))
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .cur()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// skips whitespace, returns true if any was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.cur().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        start != self.pos
+    }
+
+    fn cur(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn error(&self, msg: String) -> UcreError {
+        UcreError::parse(format!("query: {msg} at position {}", self.pos))
+    }
+}
+
+/// parses an unquoted value: colors, node references, booleans, numbers and lengths, falling back
+/// to a string
+fn parse_bare_value(s: &str) -> AttributeValue {
+    if let Some(c) = s.strip_prefix('#').and_then(Color::from_hex) {
+        return AttributeValue::Color(c);
+    }
+    if let Some(r) = s.strip_prefix('@') {
+        return AttributeValue::NodeRef(r.to_string());
+    }
+    if let Some(v) = s.strip_prefix('$') {
+        return AttributeValue::Var(v.to_string());
+    }
+    match s {
+        "true" => return AttributeValue::Boolean(true),
+        "false" => return AttributeValue::Boolean(false),
+        _ => {}
+    }
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(s.len());
+    if let Ok(n) = s[..split].parse::<f64>() {
+        if split == s.len() {
+            return AttributeValue::Number(n);
+        }
+        if let Some(u) = Unit::from_suffix(&s[split..]) {
+            return AttributeValue::Length(n, u);
+        }
+    }
+    AttributeValue::String(s.to_string())
+}
+
+impl Document {
+    /// alias for [Document::by_id], mirroring `doc.queryId` in lua
+    pub fn query_id(&self, id: &str) -> Option<NodeId> {
+        self.by_id(id)
+    }
+
+    /// all nodes of kind in document order
+    pub fn query_kind(&self, kind: NodeKind) -> impl Iterator<Item = NodeId> + '_ {
+        self.descendants(self.root())
+            .filter(move |id| self.get(*id).is_some_and(|n| n.kind() == kind))
+    }
+
+    /// all nodes with attribute key set to a value matching predicate, in document order
+    pub fn query_attr<'doc, F>(
+        &'doc self,
+        key: &'doc str,
+        predicate: F,
+    ) -> impl Iterator<Item = NodeId> + 'doc
+    where
+        F: Fn(&AttributeValue) -> bool + 'doc,
+    {
+        self.descendants(self.root()).filter(move |id| {
+            self.get(*id)
+                .and_then(|n| n.attr().get(key))
+                .is_some_and(&predicate)
+        })
+    }
+
+    /// all nodes selected by selector, in document order
+    pub fn select(&self, selector: Selector) -> impl Iterator<Item = NodeId> + '_ {
+        self.descendants(self.root())
+            .filter(move |id| selector.matches(self, *id))
+    }
+
+    /// parses selector and returns all nodes selected by it, see [Selector] for the syntax
+    pub fn query(&self, selector: &str) -> Result<impl Iterator<Item = NodeId> + '_, UcreError> {
+        Ok(self.select(selector.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Combinator, Compound, Predicate, Selector};
+    use crate::types::uom::{
+        nodes::{Container, Heading, Image, Paragraph},
+        AttributeValue, Document, Node, NodeKind, Unit,
+    };
+
+    fn doc() -> Document {
+        let mut heading = Heading::with_text(5, "Hello World");
+        heading.set_attr("id".into(), "hello-world".into());
+        let mut nested = Container::new();
+        nested
+            .set_children(vec![Box::new(Image::with_href("b.webp", ""))])
+            .expect("container allows images");
+        Document::from_nodes(vec![
+            Box::new(heading),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("This is a paragraph")),
+                Box::new(Image::with_href("a.webp", "avatar")),
+                Box::new(nested),
+            ])),
+        ])
+        .expect("failed to build document")
+    }
+
+    fn kinds(doc: &Document, selector: &str) -> Vec<NodeKind> {
+        doc.query(selector)
+            .expect("failed to parse selector")
+            .filter_map(|id| doc.get(id).map(|n| n.kind()))
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        let s = "container > image[alt=\"avatar\"] heading[level=5][id]"
+            .parse::<Selector>()
+            .expect("failed to parse");
+        assert_eq!(s.parts().len(), 3);
+        assert_eq!(s.parts()[1].0, Combinator::Child);
+        assert_eq!(
+            s.parts()[2],
+            (
+                Combinator::Descendant,
+                Compound {
+                    kind: Some(NodeKind::Heading),
+                    predicates: vec![
+                        Predicate::Equals("level".into(), AttributeValue::Number(5.0)),
+                        Predicate::Exists("id".into()),
+                    ]
+                }
+            )
+        );
+        assert_eq!(
+            s.to_string(),
+            "container > image[alt=\"avatar\"] heading[level=5][id]"
+        );
+        assert_eq!(
+            "*[size=12pt]"
+                .parse::<Selector>()
+                .expect("failed to parse")
+                .parts()[0]
+                .1
+                .predicates,
+            vec![Predicate::Equals(
+                "size".into(),
+                AttributeValue::Length(12.0, Unit::Pt)
+            )]
+        );
+        assert!("Blink".parse::<Selector>().is_err());
+        assert!("image[alt".parse::<Selector>().is_err());
+        assert!("image]".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn select() {
+        let doc = doc();
+        assert_eq!(kinds(&doc, "image"), vec![NodeKind::Image, NodeKind::Image]);
+        assert_eq!(kinds(&doc, "container > image").len(), 2);
+        assert_eq!(kinds(&doc, "container container > image").len(), 1);
+        assert_eq!(kinds(&doc, "#hello-world"), vec![NodeKind::Heading]);
+        assert_eq!(kinds(&doc, "heading[text=\"Hello World\"]").len(), 1);
+        assert_eq!(kinds(&doc, "image[alt=avatar]").len(), 1);
+        assert_eq!(kinds(&doc, "*").len(), 6);
+    }
+
+    #[test]
+    fn query() {
+        let doc = doc();
+        assert!(doc.query_id("hello-world").is_some());
+        assert_eq!(doc.query_kind(NodeKind::Container).count(), 2);
+        assert_eq!(
+            doc.query_attr("level", |v| v.as_number() == Some(5.0))
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+use super::{Node, NodeKind, UcreError};
+
+/// ParsedHook validates or completes a custom node once ucc parsed it
+pub type ParsedHook = fn(&mut dyn Node) -> Result<(), UcreError>;
+
+/// NodeType describes a custom node kind, registered via [register]
+#[derive(Debug, Clone, Copy)]
+pub struct NodeType {
+    /// the node name used in ucre lang, e.g. `chart`
+    pub name: &'static str,
+    /// creates an empty node, its [Node::kind] has to be `NodeKind::Custom(name)`
+    pub new: fn() -> Box<dyn Node>,
+    /// called by ucc after the node, its attributes, text and children are parsed, an error is
+    /// reported at the line of the node
+    pub parsed: Option<ParsedHook>,
+}
+
+/// names ucc reserves inside of the `doc {}` and `style {}` blocks and at the top level
+const RESERVED: &[&str] = &[
+    "inline",
+    "matches",
+    "meta-data",
+    "doc",
+    "style",
+    "lua",
+    "vars",
+    "theme",
+    "when",
+    "keyframes",
+    "import",
+];
+
+fn types() -> &'static RwLock<HashMap<&'static str, NodeType>> {
+    static TYPES: OnceLock<RwLock<HashMap<&'static str, NodeType>>> = OnceLock::new();
+    TYPES.get_or_init(Default::default)
+}
+
+/// register makes ty known to every [super::Document], ucc and the exporters of this process.
+/// Errors if the name is not a valid node name, is a builtin kind or was registered before.
+pub fn register(ty: NodeType) -> Result<(), UcreError> {
+    if !is_node_name(ty.name) || RESERVED.contains(&ty.name) {
+        return Err(UcreError::validate(format!(
+            "registry: '{}' is not a valid node name",
+            ty.name
+        )));
+    }
+    if NodeKind::ALL.iter().any(|k| k.name() == ty.name) {
+        return Err(UcreError::validate(format!(
+            "registry: '{}' is a builtin node kind",
+            ty.name
+        )));
+    }
+    if (ty.new)().kind() != NodeKind::Custom(ty.name.into()) {
+        return Err(UcreError::validate(format!(
+            "registry: nodes created for '{}' have to be of kind Custom(\"{}\")",
+            ty.name, ty.name
+        )));
+    }
+    let mut types = types().write().expect("registry lock poisoned");
+    if types.contains_key(ty.name) {
+        return Err(UcreError::validate(format!(
+            "registry: '{}' is already registered",
+            ty.name
+        )));
+    }
+    types.insert(ty.name, ty);
+    Ok(())
+}
+
+/// returns the registered type of the custom kind name
+pub fn lookup(name: &str) -> Option<NodeType> {
+    types()
+        .read()
+        .expect("registry lock poisoned")
+        .get(name)
+        .copied()
+}
+
+/// is_node_name checks whether s is usable as a node name: a lowercase ascii letter followed by
+/// lowercase ascii letters, digits and dashes
+pub fn is_node_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lookup, register, NodeType};
+    use crate::types::uom::{
+        nodes::{self, Generic},
+        Document, Node, NodeKind, UcreError,
+    };
+
+    fn chart() -> Box<dyn Node> {
+        Box::new(Generic::new("chart"))
+    }
+
+    fn parsed(node: &mut dyn Node) -> Result<(), UcreError> {
+        match node.attr().get("data") {
+            Some(_) => Ok(()),
+            None => Err(UcreError::validate("chart requires data")),
+        }
+    }
+
+    #[test]
+    fn register_custom() {
+        let chart_type = NodeType {
+            name: "chart",
+            new: chart,
+            parsed: Some(parsed),
+        };
+        register(chart_type).expect("failed to register");
+        assert!(register(chart_type).is_err());
+        assert!(lookup("chart").is_some_and(|t| t.parsed.is_some()));
+        assert_eq!(
+            NodeKind::from_name("chart"),
+            Some(NodeKind::Custom("chart".into()))
+        );
+
+        for name in ["heading", "inline", "Chart", "1chart", ""] {
+            assert!(register(NodeType { name, ..chart_type }).is_err(), "{name}");
+        }
+        assert!(register(NodeType {
+            name: "callout",
+            new: chart,
+            parsed: None,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_kinds() {
+        assert_eq!(NodeKind::from_name("sparkline"), None);
+        let kind = NodeKind::resolve("sparkline").expect("valid node name");
+        assert_eq!(kind.name(), "sparkline");
+        assert_eq!(NodeKind::resolve("Not A Name"), None);
+
+        let mut doc = Document::new();
+        let root = doc.root();
+        let generic = doc.append(root, nodes::new(kind.clone())).expect("append");
+        doc.append(generic, nodes::new(NodeKind::Heading))
+            .expect("generic nodes allow any child");
+        doc.set_text(generic, "text".into())
+            .expect("generic nodes hold text");
+        assert_eq!(doc.get(generic).map(|n| n.kind()), Some(kind));
+    }
+}
@@ -0,0 +1,368 @@
+use std::fmt;
+
+use super::{Color, UcreError};
+
+/// Unit of a [AttributeValue::Length], written directly after the number in ucre lang, e.g. `12pt`.
+/// Besides lengths this includes the durations `ms` and `s` used by style transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Px,
+    Pt,
+    Mm,
+    Em,
+    Percent,
+    Vw,
+    Vh,
+    Ms,
+    S,
+}
+
+impl Unit {
+    pub fn from_suffix(s: &str) -> Option<Self> {
+        Some(match s {
+            "px" => Self::Px,
+            "pt" => Self::Pt,
+            "mm" => Self::Mm,
+            "em" => Self::Em,
+            "%" => Self::Percent,
+            "vw" => Self::Vw,
+            "vh" => Self::Vh,
+            "ms" => Self::Ms,
+            "s" => Self::S,
+            _ => return None,
+        })
+    }
+
+    pub const fn suffix(&self) -> &'static str {
+        match self {
+            Self::Px => "px",
+            Self::Pt => "pt",
+            Self::Mm => "mm",
+            Self::Em => "em",
+            Self::Percent => "%",
+            Self::Vw => "vw",
+            Self::Vh => "vh",
+            Self::Ms => "ms",
+            Self::S => "s",
+        }
+    }
+
+    pub const fn is_duration(&self) -> bool {
+        matches!(self, Self::Ms | Self::S)
+    }
+}
+
+/// AttributeValue holds the typed value of a node attribute.
+///
+/// The parser produces a single value for attributes with one value and a [AttributeValue::List]
+/// for attributes with more than one:
+///
+/// ```text
+/// id "hello-world"          // String
+/// level 5                   // Number
+/// fontSize 12pt             // Length
+/// color #f523ad             // Color
+/// hidden false              // Boolean
+/// padding 2px 4px           // List
+/// labelFor @hello-world     // NodeRef
+/// color $accent             // Var
+/// textAlign left            // Keyword
+/// ```
+///
+/// [fmt::Display] writes the value back out as ucre lang source, values passing
+/// [AttributeValue::validate] are parsed back into the same value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Number(f64),
+    Length(f64, Unit),
+    Color(Color),
+    Boolean(bool),
+    List(Vec<AttributeValue>),
+    /// NodeRef references another node by its `id` attribute
+    NodeRef(String),
+    /// Var references a style variable by its name, resolved by the style cascade
+    Var(String),
+    /// Keyword is a bare identifier such as `left` or `ease-out`, written without quotes
+    Keyword(String),
+}
+
+impl AttributeValue {
+    /// the text of a string or a keyword
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) | Self::Keyword(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_length(&self) -> Option<(f64, Unit)> {
+        match self {
+            Self::Length(n, u) => Some((*n, *u)),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Self::Color(c) => Some(*c),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_node_ref(&self) -> Option<&str> {
+        match self {
+            Self::NodeRef(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn as_var(&self) -> Option<&str> {
+        match self {
+            Self::Var(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_keyword(&self) -> Option<&str> {
+        match self {
+            Self::Keyword(k) => Some(k),
+            _ => None,
+        }
+    }
+
+    /// compares self to other like `==`, except that strings and keywords of the same text are
+    /// equal, so `align left` matches `align "left"`
+    pub fn matches(&self, other: &AttributeValue) -> bool {
+        match (self, other) {
+            (Self::List(a), Self::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.matches(b))
+            }
+            (Self::String(a) | Self::Keyword(a), Self::String(b) | Self::Keyword(b)) => a == b,
+            (a, b) => a == b,
+        }
+    }
+
+    /// checks that the value has a ucre lang syntax: numbers and lengths are finite, lists hold at
+    /// least two values and no lists, node references and variables are names of letters, digits,
+    /// `-` and `_`, keywords are such names starting with a letter other than `true`, `false` and
+    /// `lua`. Documents and meta-data reject other values.
+    pub fn validate(&self) -> Result<(), UcreError> {
+        match self {
+            Self::Number(n) | Self::Length(n, _) if !n.is_finite() => {
+                Err(UcreError::validate(format!("{n} is not a finite number")))
+            }
+            Self::List(l) if l.len() < 2 => Err(UcreError::validate(
+                "Lists need at least two values, use the value itself for one",
+            )),
+            Self::List(l) => l.iter().try_for_each(|v| match v {
+                Self::List(_) => Err(UcreError::validate("Lists can't be nested")),
+                v => v.validate(),
+            }),
+            Self::NodeRef(name) | Self::Var(name) if !is_name(name) => {
+                Err(UcreError::validate(format!("'{name}' is not a valid name")))
+            }
+            Self::Keyword(k)
+                if !is_name(k)
+                    || !k.starts_with(|c: char| c.is_ascii_alphabetic())
+                    || matches!(k.as_str(), "true" | "false" | "lua") =>
+            {
+                Err(UcreError::validate(format!("'{k}' is not a valid keyword")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// checks that the value can be written in a `doc {}` or `meta-data {}` block: a bare
+    /// identifier followed by a value there starts the next attribute, so keywords of a list can
+    /// only be followed by other keywords
+    pub(super) fn validate_in_block(&self) -> Result<(), UcreError> {
+        self.validate()?;
+        let l = self.as_list();
+        match l.iter().position(|v| matches!(v, Self::Keyword(_))) {
+            Some(i) if l[i..].iter().any(|v| !matches!(v, Self::Keyword(_))) => {
+                Err(UcreError::validate(format!(
+                    "Keywords have to follow all other values in '{self}'"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// returns the values of a [AttributeValue::List], any other value is viewed as a list of one
+    pub fn as_list(&self) -> &[AttributeValue] {
+        match self {
+            Self::List(l) => l,
+            v => std::slice::from_ref(v),
+        }
+    }
+}
+
+/// is_name checks whether s is usable as an attribute key, node reference or variable name
+pub(super) fn is_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+impl From<String> for AttributeValue {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+impl From<f64> for AttributeValue {
+    fn from(n: f64) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(b: bool) -> Self {
+        Self::Boolean(b)
+    }
+}
+
+impl From<Color> for AttributeValue {
+    fn from(c: Color) -> Self {
+        Self::Color(c)
+    }
+}
+
+impl From<Vec<AttributeValue>> for AttributeValue {
+    fn from(mut l: Vec<AttributeValue>) -> Self {
+        if l.len() == 1 {
+            l.remove(0)
+        } else {
+            Self::List(l)
+        }
+    }
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::String(s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        '\t' => write!(f, "\\t")?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Length(n, u) => write!(f, "{n}{}", u.suffix()),
+            Self::Color(c) => write!(f, "{c}"),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::List(l) => {
+                for (i, v) in l.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                Ok(())
+            }
+            Self::NodeRef(r) => write!(f, "@{r}"),
+            Self::Var(v) => write!(f, "${v}"),
+            Self::Keyword(k) => write!(f, "{k}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AttributeValue, Color, Unit};
+    use crate::types::uom::ErrorKind;
+
+    #[test]
+    fn display() {
+        let v = AttributeValue::List(vec![
+            AttributeValue::String("a \"quoted\"\nstring".into()),
+            AttributeValue::Number(5.0),
+            AttributeValue::Length(12.5, Unit::Pt),
+            AttributeValue::Length(50.0, Unit::Percent),
+            AttributeValue::Color(Color::rgba(0xf5, 0x23, 0xad, 255)),
+            AttributeValue::Boolean(true),
+            AttributeValue::NodeRef("hello-world".into()),
+            AttributeValue::Var("accent".into()),
+        ]);
+        assert_eq!(
+            v.to_string(),
+            r#""a \"quoted\"\nstring" 5 12.5pt 50% #f523ad true @hello-world $accent"#
+        );
+    }
+
+    #[test]
+    fn from_vec() {
+        assert_eq!(
+            AttributeValue::from(vec![AttributeValue::Number(1.0)]),
+            AttributeValue::Number(1.0)
+        );
+        let l = AttributeValue::from(vec![1.0.into(), 2.0.into()]);
+        assert_eq!(l.as_list().len(), 2);
+        assert_eq!(AttributeValue::from("a").as_list().len(), 1);
+    }
+
+    #[test]
+    fn validate() {
+        for v in [
+            AttributeValue::Number(f64::NAN),
+            AttributeValue::Length(f64::INFINITY, Unit::Px),
+            AttributeValue::List(vec![]),
+            AttributeValue::List(vec![1.0.into()]),
+            AttributeValue::List(vec![1.0.into(), vec![2.0.into(), 3.0.into()].into()]),
+            AttributeValue::List(vec![1.0.into(), f64::NEG_INFINITY.into()]),
+            AttributeValue::NodeRef("two words".into()),
+            AttributeValue::Var(String::new()),
+            AttributeValue::Keyword("7up".into()),
+            AttributeValue::Keyword("lua".into()),
+        ] {
+            let err = v.validate().expect_err(&format!("{v:?}"));
+            assert_eq!(err.kind(), ErrorKind::Validate);
+        }
+        assert!(AttributeValue::List(vec![1.0.into(), "a".into()])
+            .validate()
+            .is_ok());
+        assert!(AttributeValue::Var("accent-2".into()).validate().is_ok());
+        assert!(AttributeValue::Keyword("ease-out".into())
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn matches() {
+        let keyword = AttributeValue::Keyword("left".into());
+        assert!(keyword.matches(&"left".into()));
+        assert!(!keyword.matches(&"right".into()));
+        assert!(AttributeValue::List(vec![keyword.clone(), 1.0.into()])
+            .matches(&AttributeValue::List(vec!["left".into(), 1.0.into()])));
+        assert_ne!(keyword, "left".into());
+        assert_eq!(keyword.to_string(), "left");
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::HashSet;
+
+use super::{document::check_attr, AttributeValue, Document, Node, NodeId, UcreError};
+
+/// Control decides how a walk continues after a hook returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Control {
+    #[default]
+    Continue,
+    /// do not descend into the children of the current node, only meaningful for enter hooks
+    SkipChildren,
+    /// end the walk, no further hooks are called
+    Stop,
+}
+
+/// Visitor is called for every node of a walk over a [Document], see [Document::walk]. depth is
+/// 0 for top-level nodes.
+pub trait Visitor {
+    /// pre-order hook, called before the children of id are visited
+    fn enter(&mut self, _doc: &Document, _id: NodeId, _node: &dyn Node, _depth: usize) -> Control {
+        Control::Continue
+    }
+
+    /// post-order hook, called after the children of id were visited, not called if enter
+    /// returned [Control::Stop]
+    fn leave(&mut self, _doc: &Document, _id: NodeId, _node: &dyn Node, _depth: usize) -> Control {
+        Control::Continue
+    }
+}
+
+/// VisitorMut is the mutable counterpart of [Visitor], see [Document::walk_mut]. Hooks change
+/// the document via its mutating methods, so changes are recorded and undoable. The children of
+/// id are read after enter returns, enter may therefore insert or remove children of id.
+pub trait VisitorMut {
+    fn enter(&mut self, _doc: &mut Document, _id: NodeId, _depth: usize) -> Control {
+        Control::Continue
+    }
+
+    fn leave(&mut self, _doc: &mut Document, _id: NodeId, _depth: usize) -> Control {
+        Control::Continue
+    }
+}
+
+/// Fold rewrites a subtree bottom-up, see [Document::fold]
+pub trait Fold {
+    /// called with node after its children were folded, the returned nodes replace node: an
+    /// empty Vec removes it, multiple nodes are spliced into its parent in order
+    fn fold(&mut self, node: Box<dyn Node>, depth: usize) -> Result<Vec<Box<dyn Node>>, UcreError>;
+}
+
+impl<F> Fold for F
+where
+    F: FnMut(Box<dyn Node>, usize) -> Result<Vec<Box<dyn Node>>, UcreError>,
+{
+    fn fold(&mut self, node: Box<dyn Node>, depth: usize) -> Result<Vec<Box<dyn Node>>, UcreError> {
+        self(node, depth)
+    }
+}
+
+impl Document {
+    /// depth of id, 0 for top-level nodes and the root
+    pub fn depth(&self, id: NodeId) -> usize {
+        self.ancestors(id).count().saturating_sub(1)
+    }
+
+    /// walks id and its descendants in document order, calling the hooks of visitor. Walking the
+    /// root visits all top-level nodes but not the root itself. Returns [Control::Stop] if the
+    /// walk was stopped by a hook.
+    pub fn walk(&self, id: NodeId, visitor: &mut impl Visitor) -> Control {
+        if id == self.root() {
+            return walk_children(self, id, 0, visitor);
+        }
+        walk_node(self, id, self.depth(id), visitor)
+    }
+
+    /// walks id and its descendants like [Document::walk], allowing the hooks to change the
+    /// document
+    pub fn walk_mut(&mut self, id: NodeId, visitor: &mut impl VisitorMut) -> Control {
+        if id == self.root() {
+            return walk_children_mut(self, id, 0, visitor);
+        }
+        let depth = self.depth(id);
+        walk_node_mut(self, id, depth, visitor)
+    }
+
+    /// fold rewrites the subtree at id via folder and replaces it with the result, returning the
+    /// ids of the replacing nodes. Folding the root rewrites all top-level nodes.
+    ///
+    /// The subtree is folded as a copy, the document is left untouched if folder fails or the
+    /// result is invalid in place of id. The replacement is recorded as removing the old and
+    /// inserting the new nodes, all nodes of the subtree therefore get new ids.
+    pub fn fold(&mut self, id: NodeId, folder: &mut impl Fold) -> Result<Vec<NodeId>, UcreError> {
+        if !self.contains(id) {
+            return Err(UcreError::validate(format!("Node {id:?} does not exist")));
+        }
+        let (parent, targets) = match self.parent(id) {
+            None => (id, self.children(id).to_vec()),
+            Some(parent) => (parent, vec![id]),
+        };
+        let index = match targets.first() {
+            Some(first) => self
+                .children(parent)
+                .iter()
+                .position(|c| c == first)
+                .unwrap_or(0),
+            None => return Ok(vec![]),
+        };
+        let depth = self.depth(targets[0]);
+
+        let mut folded = vec![];
+        for t in &targets {
+            let copy = self.subtree(*t).expect("targets exist").into_node()?;
+            folded.extend(fold_node(copy, depth, folder)?);
+        }
+
+        // validate the replacement before touching the document
+        let replaced = targets
+            .iter()
+            .flat_map(|t| std::iter::once(*t).chain(self.descendants(*t)))
+            .collect::<HashSet<_>>();
+        let mut ids = HashSet::new();
+        for node in &folded {
+            if parent != self.root() {
+                let p = self.get(parent).expect("parent exists");
+                if !p.allows_child(node.kind()) {
+                    return Err(UcreError::validate(format!(
+                        "{} does not allow {} children",
+                        p.kind().name(),
+                        node.kind().name()
+                    )));
+                }
+            }
+            let mut ok = true;
+            visit_ids(node.as_ref(), &mut |key| {
+                ok &= ids.insert(key.to_string())
+                    && self
+                        .by_id(key)
+                        .is_none_or(|other| replaced.contains(&other));
+            });
+            if !ok {
+                return Err(UcreError::validate("Folding produced duplicate node ids"));
+            }
+            check_attrs(node.as_ref())?;
+        }
+
+        // the replacement is undone as a single step, unless it is part of an open transaction
+        let grouped = self.begin().is_ok();
+        let result = targets
+            .into_iter()
+            .try_for_each(|t| self.remove(t).map(|_| ()))
+            .and_then(|_| {
+                folded
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, node)| self.insert(parent, index + i, node))
+                    .collect()
+            });
+        if grouped {
+            match result {
+                Ok(_) => self.commit()?,
+                Err(_) => self.rollback()?,
+            }
+        }
+        result
+    }
+}
+
+fn walk_node(doc: &Document, id: NodeId, depth: usize, v: &mut impl Visitor) -> Control {
+    let Some(node) = doc.get(id) else {
+        return Control::Continue;
+    };
+    match v.enter(doc, id, node, depth) {
+        Control::Stop => return Control::Stop,
+        Control::SkipChildren => {}
+        Control::Continue => {
+            if walk_children(doc, id, depth + 1, v) == Control::Stop {
+                return Control::Stop;
+            }
+        }
+    }
+    match v.leave(doc, id, node, depth) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
+
+fn walk_children(doc: &Document, id: NodeId, depth: usize, v: &mut impl Visitor) -> Control {
+    for c in doc.children(id) {
+        if walk_node(doc, *c, depth, v) == Control::Stop {
+            return Control::Stop;
+        }
+    }
+    Control::Continue
+}
+
+fn walk_node_mut(doc: &mut Document, id: NodeId, depth: usize, v: &mut impl VisitorMut) -> Control {
+    match v.enter(doc, id, depth) {
+        Control::Stop => return Control::Stop,
+        Control::SkipChildren => {}
+        Control::Continue => {
+            if walk_children_mut(doc, id, depth + 1, v) == Control::Stop {
+                return Control::Stop;
+            }
+        }
+    }
+    if !doc.contains(id) {
+        return Control::Continue;
+    }
+    match v.leave(doc, id, depth) {
+        Control::Stop => Control::Stop,
+        _ => Control::Continue,
+    }
+}
+
+fn walk_children_mut(
+    doc: &mut Document,
+    id: NodeId,
+    depth: usize,
+    v: &mut impl VisitorMut,
+) -> Control {
+    for c in doc.children(id).to_vec() {
+        // an earlier sibling's hooks may have moved or removed c
+        if doc.parent(c) != Some(id) {
+            continue;
+        }
+        if walk_node_mut(doc, c, depth, v) == Control::Stop {
+            return Control::Stop;
+        }
+    }
+    Control::Continue
+}
+
+fn fold_node(
+    mut node: Box<dyn Node>,
+    depth: usize,
+    folder: &mut impl Fold,
+) -> Result<Vec<Box<dyn Node>>, UcreError> {
+    let children = node.take_children();
+    if !children.is_empty() {
+        let mut folded = vec![];
+        for c in children {
+            folded.extend(fold_node(c, depth + 1, folder)?);
+        }
+        if !folded.is_empty() {
+            node.set_children(folded)?;
+        }
+    }
+    folder.fold(node, depth)
+}
+
+/// visit_ids calls f with the id attribute of node and all of its descendants
+fn visit_ids(node: &dyn Node, f: &mut impl FnMut(&str)) {
+    if let Some(id) = node.attr().get("id").and_then(AttributeValue::as_str) {
+        f(id);
+    }
+    for c in node.children().into_iter().flatten() {
+        visit_ids(c.as_ref(), f);
+    }
+}
+
+/// check_attrs errors if an attribute of node or one of its descendants is invalid, see
+/// [check_attr]
+fn check_attrs(node: &dyn Node) -> Result<(), UcreError> {
+    node.attr()
+        .iter()
+        .try_for_each(|(k, v)| check_attr(node, k, v))?;
+    node.children()
+        .into_iter()
+        .flatten()
+        .try_for_each(|c| check_attrs(c.as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Control, Visitor, VisitorMut};
+    use crate::types::uom::{
+        nodes::{Container, Divider, Heading, List, ListItem, Paragraph, Quote},
+        AttributeValue, Document, Node, NodeId, NodeKind, UcreError,
+    };
+
+    fn doc() -> Document {
+        Document::from_nodes(vec![
+            Box::new(Heading::with_text(1, "a")),
+            Box::new(Container::with_children(vec![
+                Box::new(Paragraph::with_text("b")),
+                Box::new(Divider::new()),
+                Box::new(Container::with_children(vec![Box::new(
+                    Paragraph::with_text("c"),
+                )])),
+            ])),
+            Box::new(Paragraph::with_text("d")),
+        ])
+        .expect("failed to build document")
+    }
+
+    /// Trace records every hook call as `+name:depth` and `-name:depth`
+    #[derive(Default)]
+    struct Trace {
+        calls: Vec<String>,
+        stop_at: Option<&'static str>,
+    }
+
+    impl Visitor for Trace {
+        fn enter(&mut self, _: &Document, _: NodeId, node: &dyn Node, depth: usize) -> Control {
+            let kind = node.kind();
+            let name = node.text().filter(|t| !t.is_empty()).unwrap_or(kind.name());
+            self.calls.push(format!("+{name}:{depth}"));
+            if self.stop_at == Some(name) {
+                return Control::Stop;
+            }
+            if node.kind() == NodeKind::Container && depth == 1 {
+                return Control::SkipChildren;
+            }
+            Control::Continue
+        }
+
+        fn leave(&mut self, _: &Document, _: NodeId, node: &dyn Node, depth: usize) -> Control {
+            let kind = node.kind();
+            let name = node.text().filter(|t| !t.is_empty()).unwrap_or(kind.name());
+            self.calls.push(format!("-{name}:{depth}"));
+            Control::Continue
+        }
+    }
+
+    #[test]
+    fn walk() {
+        let doc = doc();
+        let mut trace = Trace::default();
+        assert_eq!(doc.walk(doc.root(), &mut trace), Control::Continue);
+        assert_eq!(
+            trace.calls.join(" "),
+            "+a:0 -a:0 +container:0 +b:1 -b:1 +divider:1 -divider:1 +container:1 -container:1 \
+             -container:0 +d:0 -d:0"
+        );
+
+        let mut trace = Trace {
+            stop_at: Some("b"),
+            ..Default::default()
+        };
+        assert_eq!(doc.walk(doc.root(), &mut trace), Control::Stop);
+        assert_eq!(trace.calls.join(" "), "+a:0 -a:0 +container:0 +b:1");
+
+        let container = doc.children(doc.root())[1];
+        let nested = doc.children(container)[2];
+        let mut trace = Trace::default();
+        doc.walk(nested, &mut trace);
+        assert_eq!(trace.calls.join(" "), "+container:1 -container:1");
+    }
+
+    #[test]
+    fn walk_mut() {
+        struct Upper;
+        impl VisitorMut for Upper {
+            fn enter(&mut self, doc: &mut Document, id: NodeId, _: usize) -> Control {
+                let kind = doc.get(id).map(|n| n.kind());
+                if kind == Some(NodeKind::Divider) {
+                    doc.remove(id).expect("remove divider");
+                    return Control::SkipChildren;
+                }
+                if let Some(text) = doc.get(id).and_then(|n| n.text()).map(str::to_uppercase) {
+                    if !text.is_empty() {
+                        doc.set_text(id, text).expect("set text");
+                    }
+                }
+                Control::Continue
+            }
+        }
+        let mut doc = doc();
+        doc.walk_mut(doc.root(), &mut Upper);
+        let texts = doc
+            .descendants(doc.root())
+            .filter_map(|id| doc.get(id).and_then(|n| n.text()))
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["A", "B", "C", "D"]);
+        assert!(doc
+            .descendants(doc.root())
+            .all(|id| doc.get(id).map(|n| n.kind()) != Some(NodeKind::Divider)));
+    }
+
+    #[test]
+    fn fold() {
+        let mut doc = doc();
+        let original = doc.to_string();
+        // drops dividers and wraps paragraphs into quotes
+        let mut folder = |node: Box<dyn Node>, _: usize| -> Result<Vec<Box<dyn Node>>, UcreError> {
+            Ok(match node.kind() {
+                NodeKind::Divider => vec![],
+                NodeKind::Paragraph => {
+                    let mut quote = Quote::new();
+                    quote.set_children(vec![node])?;
+                    vec![Box::new(quote)]
+                }
+                _ => vec![node],
+            })
+        };
+        let ids = doc.fold(doc.root(), &mut folder).expect("failed to fold");
+        assert_eq!(ids.len(), 3);
+        let kinds = doc
+            .descendants(doc.root())
+            .filter_map(|id| doc.get(id).map(|n| n.kind().name().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "heading",
+                "container",
+                "quote",
+                "paragraph",
+                "container",
+                "quote",
+                "paragraph",
+                "quote",
+                "paragraph"
+            ]
+        );
+        assert!(doc.undo().expect("undo"));
+        assert_eq!(doc.to_string(), original);
+
+        // paragraphs are not allowed inside of lists, the document stays untouched
+        let mut doc = Document::from_nodes(vec![Box::new(List::with_items(
+            false,
+            vec![ListItem::with_text("a")],
+        ))])
+        .expect("failed to build document");
+        let before = doc.to_string();
+        let mut invalid =
+            |node: Box<dyn Node>, _: usize| -> Result<Vec<Box<dyn Node>>, UcreError> {
+                Ok(match node.kind() {
+                    NodeKind::ListItem => vec![Box::new(Paragraph::with_text("a"))],
+                    _ => vec![node],
+                })
+            };
+        assert!(doc.fold(doc.root(), &mut invalid).is_err());
+        assert_eq!(doc.to_string(), before);
+    }
+
+    #[test]
+    fn fold_invalid_attributes() {
+        let mut doc = doc();
+        let before = doc.to_string();
+        let len = doc.descendants(doc.root()).count();
+        // a nested node with a level that can't be written as ucre lang
+        let mut nan = |node: Box<dyn Node>, _: usize| -> Result<Vec<Box<dyn Node>>, UcreError> {
+            if node.kind() != NodeKind::Paragraph || node.text() != Some("c") {
+                return Ok(vec![node]);
+            }
+            let mut heading = Heading::new();
+            heading.set_attr("level".into(), AttributeValue::Number(f64::NAN));
+            Ok(vec![Box::new(heading)])
+        };
+        assert!(doc.fold(doc.root(), &mut nan).is_err());
+        assert_eq!(doc.to_string(), before);
+        assert_eq!(doc.descendants(doc.root()).count(), len);
+        assert!(!doc.undo().expect("undo"), "nothing was changed");
+    }
+}
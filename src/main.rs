@@ -1,29 +1,214 @@
-use shared::types::uom::UcreError;
+use shared::types::{
+    style::Viewport,
+    uom::{AttributeValue, File, UcreError},
+};
 
 fn execution(args: Vec<String>) -> Result<(), UcreError> {
     let cmd = match args.get(1) {
         Some(c) => c,
-        None => return Err(UcreError::from_str("ucre: Failed to get a subcommand")),
+        None => return Err(UcreError::validate("ucre: Failed to get a subcommand")),
     };
     match cmd.as_str() {
         "ucc" => {
             let filename = match args.get(2) {
                 Some(name) => name,
                 None => {
-                    return Err(UcreError::from_str(
+                    return Err(UcreError::validate(
                         "ucre: Failed to get a filename, ucc requires one",
                     ))
                 }
             };
-            ucc::run(&filename)
+            ucc::run(filename)
         }
-        c @ _ => Err(UcreError::new(format!("{c} is not a valid subcommand"))),
+        "fmt" => {
+            let filename = match args.get(2) {
+                Some(name) => name,
+                None => {
+                    return Err(UcreError::validate(
+                        "ucre: Failed to get a filename, fmt requires one",
+                    ))
+                }
+            };
+            print!("{}", ucc::parse_file(filename)?);
+            Ok(())
+        }
+        "run" => {
+            let filename = match args.get(2) {
+                Some(name) => name,
+                None => {
+                    return Err(UcreError::validate(
+                        "ucre: Failed to get a filename, run requires one",
+                    ))
+                }
+            };
+            let mut file = ucc::parse_file(filename)?;
+            lua::run(&mut file).map_err(|e| e.in_file(filename))?;
+            print!("{file}");
+            Ok(())
+        }
+        "query" => {
+            let (filename, selector) = match (args.get(2), args.get(3)) {
+                (Some(name), Some(selector)) => (name, selector),
+                _ => {
+                    return Err(UcreError::validate(
+                        "ucre: Failed to get a filename and a selector, query requires both",
+                    ))
+                }
+            };
+            let file = ucc::parse_file(filename)?;
+            let doc = file.doc();
+            for id in doc.query(selector)? {
+                let Some(node) = doc.get(id) else { continue };
+                let mut line = node.kind().name().to_string();
+                if let Some(id) = node.attr().get("id").and_then(AttributeValue::as_str) {
+                    line.push_str(&format!("#{id}"));
+                }
+                if let Some(text) = node.text().filter(|t| !t.is_empty()) {
+                    line.push_str(&format!(" {}", AttributeValue::from(text)));
+                }
+                println!("{line}");
+            }
+            Ok(())
+        }
+        "diff" => {
+            let (a, b) = match (args.get(2), args.get(3)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => {
+                    return Err(UcreError::validate(
+                        "ucre: Failed to get two filenames, diff requires both",
+                    ))
+                }
+            };
+            let (a, b) = (ucc::parse_file(a)?, ucc::parse_file(b)?);
+            for edit in a.doc().diff(b.doc()) {
+                println!("{edit}");
+            }
+            Ok(())
+        }
+        "export" | "import" => {
+            let usage = match cmd.as_str() {
+                "export" => "--json|--a11y",
+                _ => "--json",
+            };
+            let (format, filename) = match (args.get(2).map(String::as_str), args.get(3)) {
+                (Some(f), Some(name)) if usage.split('|').any(|u| u == f) => (f, name),
+                _ => {
+                    return Err(UcreError::validate(format!(
+                        "ucre: Failed to get a format and a filename, usage: {cmd} {usage} <file>"
+                    )))
+                }
+            };
+            if cmd == "export" {
+                let file = ucc::parse_file(filename)?;
+                match format {
+                    "--a11y" => println!("{}", file.doc().access_tree().to_json()),
+                    _ => println!("{}", file.to_json()),
+                }
+            } else {
+                let json = std::fs::read_to_string(filename).map_err(|e| {
+                    UcreError::io("Failed to read file")
+                        .in_file(filename)
+                        .caused_by(e)
+                })?;
+                print!(
+                    "{}",
+                    File::from_json(&json).map_err(|e| e.in_file(filename))?
+                );
+            }
+            Ok(())
+        }
+        "a11y" => {
+            let filename = match args.get(2) {
+                Some(name) => name,
+                None => {
+                    return Err(UcreError::validate(
+                        "ucre: Failed to get a filename, a11y requires one",
+                    ))
+                }
+            };
+            let issues = ucc::parse_file(filename)?.doc().check_accessibility();
+            for issue in &issues {
+                println!("{issue}");
+            }
+            match issues.len() {
+                0 => Ok(()),
+                n => Err(
+                    UcreError::validate(format!("ucre: Found {n} accessibility issues"))
+                        .in_file(filename),
+                ),
+            }
+        }
+        "style" => {
+            let usage = || {
+                UcreError::validate(
+                    "ucre: usage: style <file> [--theme <name>] [--viewport <width>x<height>[@<density>]] [--user-style <file>]...",
+                )
+            };
+            let filename = args.get(2).ok_or_else(usage)?;
+            let mut file = ucc::parse_file(filename)?;
+            let (mut theme, mut viewport_size) = (None, None);
+            let mut options = args[3..].iter();
+            while let Some(option) = options.next() {
+                let value = options.next().ok_or_else(usage)?;
+                match option.as_str() {
+                    "--theme" => theme = Some(value),
+                    "--viewport" => viewport_size = Some(viewport(value).ok_or_else(usage)?),
+                    // user sheets cascade after the document, in the order given
+                    "--user-style" => {
+                        for sheet in ucc::parse_style_file(value)? {
+                            file.add_user_sheet(sheet);
+                        }
+                    }
+                    _ => return Err(usage()),
+                }
+            }
+            let mut cascade = file.cascade().map_err(|e| e.in_file(filename))?;
+            if let Some(theme) = theme {
+                cascade
+                    .set_theme(Some(theme))
+                    .map_err(|e| e.in_file(filename))?;
+            }
+            if let Some(viewport) = viewport_size {
+                cascade.set_viewport(viewport);
+            }
+            let styles = cascade.compute(file.doc());
+            let doc = file.doc();
+            for id in doc.descendants(doc.root()) {
+                let (Some(node), Some(path), Some(style)) =
+                    (doc.get(id), doc.path(id), styles.get(id))
+                else {
+                    continue;
+                };
+                println!("{path} {}", node.kind().name());
+                for (property, value) in style.iter() {
+                    println!("    {property} {value}");
+                }
+            }
+            Ok(())
+        }
+        c => Err(UcreError::validate(format!(
+            "{c} is not a valid subcommand"
+        ))),
     }
 }
 
+/// viewport parses `<width>x<height>[@<density>]`, e.g. `390x844@3`
+fn viewport(s: &str) -> Option<Viewport> {
+    let (size, density) = match s.split_once('@') {
+        Some((size, density)) => (size, density.parse().ok().filter(|d| *d > 0.0)?),
+        None => (s, 1.0),
+    };
+    let (width, height) = size.split_once('x')?;
+    Some(Viewport::new(width.parse().ok()?, height.parse().ok()?).with_density(density))
+}
+
 fn main() {
     let args: Vec<_> = std::env::args().collect();
     if let Err(err) = execution(args) {
-        println!("{}, exiting", err)
+        eprintln!("error[{}]: {err}", err.code());
+        for cause in err.chain().skip(1) {
+            eprintln!("  caused by: {cause}");
+        }
+        std::process::exit(1);
     }
 }
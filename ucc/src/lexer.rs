@@ -1,9 +1,16 @@
-use shared::types::{token::TokenType, uom::UcreError};
+use std::borrow::Cow;
+
+use shared::types::{
+    token::{Token, TokenType},
+    uom::{Color, SourceSpan, UcreError, Unit},
+};
 
 pub struct Lexer<'lexer> {
     source: &'lexer [u8],
     pos: usize,
     line: usize,
+    /// position of the first byte of the current line, used to compute columns
+    line_start: usize,
 }
 
 impl<'lexer> Lexer<'lexer> {
@@ -12,127 +19,231 @@ impl<'lexer> Lexer<'lexer> {
             source: i,
             pos: 0,
             line: 1,
+            line_start: 0,
         }
     }
 
-    pub fn run(&mut self) -> Result<Vec<TokenType>, UcreError> {
+    pub fn run(&mut self) -> Result<Vec<Token>, UcreError> {
         let mut r = vec![];
         while !self.is_eof() {
-            let t;
             self.skip_whitespace()?;
             if self.is_eof() {
                 break;
             }
-            match self.cur()? {
-                '{' => t = TokenType::CurlyLeft,
-                '}' => t = TokenType::CurlyRight,
-                '"' => {
+            let line = self.line;
+            let t = match self.cur()? {
+                '{' => {
                     self.advance();
-                    let start = self.pos;
-                    while !self.is_eof() && self.cur()? != '"' {
-                        self.advance();
-                    }
-                    if self.is_eof() {
-                        return Err(UcreError::new(format!(
-                            "ucc: Unterminated String in line {}",
-                            self.line - 1
-                        )));
-                    }
-                    let bytes = self.source.get(start..self.pos).ok_or_else(|| {
-                        UcreError::from_str(
-                            "ucc: Failed to extract bytes for a string from the input",
-                        )
-                    })?;
-                    t = TokenType::String(String::from_utf8(bytes.to_vec()).map_err(|_| {
-                        UcreError::from_str("ucc: Failed to convert a byte vector into a String")
-                    })?)
+                    TokenType::CurlyLeft
                 }
+                '}' => {
+                    self.advance();
+                    TokenType::CurlyRight
+                }
+                '"' => self.string()?,
                 '/' if self.next()? == '/' => {
                     while !self.is_eof() && self.cur()? != '\n' {
                         self.advance();
                     }
                     continue;
                 }
-                '0'..='9' => {
-                    let start = self.pos;
+                '#' => {
                     self.advance();
-                    while !self.is_eof()
-                        && matches!(self.cur()?, '0'..='9' | '_' | '.' | 'A'..='F' | 'a'..='f')
-                    {
+                    let start = self.pos;
+                    while !self.is_eof() && self.cur()?.is_ascii_alphanumeric() {
                         self.advance();
                     }
-                    let bytes = self.source.get(start..self.pos).ok_or_else(|| {
-                        UcreError::from_str(
-                            "ucc: Failed to extract bytes for a string from the input",
-                        )
-                    })?;
-                    let number = String::from_utf8(bytes.to_vec())
-                        .map_err(|_| {
-                            UcreError::from_str(
-                                "ucc: Failed to convert a byte vector into a String",
-                            )
-                        })?
-                        .parse::<f64>()
-                        .map_err(|e| UcreError::new(e.to_string()))?;
-                    t = TokenType::Number(number);
+                    let hex = self.string_from(start)?;
+                    TokenType::Color(
+                        Color::from_hex(&hex)
+                            .ok_or_else(|| self.error(format!("ucc: Invalid color '#{hex}'")))?,
+                    )
                 }
-                'a'..='z' | 'A'..='Z' => {
-                    let start = self.pos;
+                '@' => {
                     self.advance();
-                    while !self.is_eof()
-                        && matches!(self.cur()?, '-' | '_' | 'a'..='z' | 'A'..='Z' | '0'..='9')
-                    {
-                        self.advance();
+                    let ident = self.ident()?;
+                    if ident.is_empty() {
+                        return Err(self.error("ucc: Missing node id after '@'"));
                     }
-                    let bytes = self.source.get(start..self.pos).ok_or_else(|| {
-                        UcreError::from_str(
-                            "ucc: Failed to extract bytes for a string from the input",
-                        )
-                    })?;
-                    let ident = String::from_utf8(bytes.to_vec()).map_err(|_| {
-                        UcreError::from_str("ucc: Failed to convert a byte vector into a String")
-                    })?;
-                    t = if ident == "lua" {
-                        self.skip_whitespace()?;
-                        if self.cur()? != '[' {
-                            return Err(UcreError::from_str(
-                                "ucc: Invalid lua block, missing [ to signal start",
-                            ));
-                        }
-                        self.advance();
-                        self.skip_whitespace()?;
-                        let start = self.pos;
-                        while !self.is_eof() && self.cur()? != ']' {
-                            self.advance();
-                        }
-                        let bytes = self.source.get(start..self.pos - 1).ok_or_else(|| {
-                            UcreError::from_str(
-                                "ucc: Failed to extract bytes for a string from the input",
-                            )
-                        })?;
-                        let ident = String::from_utf8(bytes.to_vec()).map_err(|_| {
-                            UcreError::from_str(
-                                "ucc: Failed to convert a byte vector into a String",
-                            )
-                        })?;
-
-                        TokenType::Lua(ident)
+                    TokenType::NodeRef(ident)
+                }
+                '$' => {
+                    self.advance();
+                    let ident = self.ident()?;
+                    if ident.is_empty() {
+                        return Err(self.error("ucc: Missing variable name after '$'"));
+                    }
+                    TokenType::Var(ident)
+                }
+                ':' => {
+                    self.advance();
+                    let ident = self.ident()?;
+                    if ident.is_empty() {
+                        return Err(self.error("ucc: Missing state name after ':'"));
+                    }
+                    TokenType::State(ident)
+                }
+                '-' if self.next()?.is_ascii_digit() => self.number()?,
+                '0'..='9' => self.number()?,
+                'a'..='z' | 'A'..='Z' => {
+                    let start = self.span();
+                    let ident = self.ident()?;
+                    if ident == "lua" {
+                        self.lua()?
+                    } else if matches!(ident.as_str(), "rgb" | "rgba" | "hsl" | "hsla")
+                        && !self.is_eof()
+                        && self.cur()? == '('
+                    {
+                        self.color_function(ident, start)?
                     } else {
                         TokenType::Ident(ident)
                     }
                 }
-                c @ _ => {
-                    return Err(UcreError::new(format!(
-                        "ucc: Unknown character '{c}' in line {}",
-                        self.line - 1
-                    )))
-                }
+                c => return Err(self.error(format!("ucc: Unknown character '{c}'"))),
+            };
+            r.push(Token { ty: t, line });
+        }
+        r.push(Token {
+            ty: TokenType::Eof,
+            line: self.line,
+        });
+        Ok(r)
+    }
+
+    /// lexes a string, supports the escapes `\"`, `\\`, `\n` and `\t`
+    fn string(&mut self) -> Result<TokenType, UcreError> {
+        let start = self.span();
+        self.advance();
+        let mut bytes = vec![];
+        while !self.is_eof() && self.cur()? != '"' {
+            let mut b = self.source[self.pos];
+            if b == b'\\' {
+                self.advance();
+                b = match self.cur()? {
+                    '"' => b'"',
+                    '\\' => b'\\',
+                    'n' => b'\n',
+                    't' => b'\t',
+                    c => return Err(self.error(format!("ucc: Unknown escape '\\{c}'"))),
+                };
+            } else if b == b'\n' {
+                self.newline();
             }
-            r.push(t);
+            bytes.push(b);
             self.advance();
         }
-        r.push(TokenType::Eof);
-        Ok(r)
+        if self.is_eof() {
+            return Err(UcreError::lex("ucc: Unterminated String").at(start));
+        }
+        self.advance();
+        Ok(TokenType::String(String::from_utf8(bytes).map_err(
+            |e| {
+                UcreError::lex("ucc: Failed to convert a byte vector into a String")
+                    .at(start)
+                    .caused_by(e)
+            },
+        )?))
+    }
+
+    /// lexes a number and its optional unit, `12`, `-1.5`, `12pt` and `50%`
+    fn number(&mut self) -> Result<TokenType, UcreError> {
+        let start = self.pos;
+        self.advance();
+        while !self.is_eof() && matches!(self.cur()?, '0'..='9' | '_' | '.') {
+            self.advance();
+        }
+        let number = self
+            .string_from(start)?
+            .replace('_', "")
+            .parse::<f64>()
+            .map_err(|e| self.error(format!("ucc: {e}")).caused_by(e))?;
+
+        if self.is_eof() {
+            return Ok(TokenType::Number(number));
+        }
+        let unit_start = self.pos;
+        if self.cur()? == '%' {
+            self.advance();
+        } else {
+            while !self.is_eof() && self.cur()?.is_ascii_alphabetic() {
+                self.advance();
+            }
+        }
+        if unit_start == self.pos {
+            return Ok(TokenType::Number(number));
+        }
+        let unit = self.string_from(unit_start)?;
+        match Unit::from_suffix(&unit) {
+            Some(u) => Ok(TokenType::Length(number, u)),
+            None => Err(self.error(format!("ucc: Unknown unit '{unit}'"))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, UcreError> {
+        let start = self.pos;
+        while !self.is_eof() && matches!(self.cur()?, '-' | '_' | 'a'..='z' | 'A'..='Z' | '0'..='9')
+        {
+            self.advance();
+        }
+        self.string_from(start)
+    }
+
+    /// lexes the arguments of the color function name, e.g. `rgb(245 35 173)`, see [Color]
+    fn color_function(&mut self, name: String, start: SourceSpan) -> Result<TokenType, UcreError> {
+        let args = self.pos;
+        while !self.is_eof() && !matches!(self.cur()?, ')' | '\n') {
+            self.advance();
+        }
+        if self.is_eof() || self.cur()? != ')' {
+            return Err(UcreError::lex(format!("ucc: Unterminated {name}(")).at(start));
+        }
+        self.advance();
+        let function = format!("{name}{}", self.string_from(args)?);
+        Color::parse(&function)
+            .map(TokenType::Color)
+            .ok_or_else(|| UcreError::lex(format!("ucc: Invalid color '{function}'")).at(start))
+    }
+
+    /// lexes the content of a `lua [ ... ]` block, nested brackets are allowed
+    fn lua(&mut self) -> Result<TokenType, UcreError> {
+        self.skip_whitespace()?;
+        if self.is_eof() || self.cur()? != '[' {
+            return Err(self.error("ucc: Invalid lua block, missing [ to signal start"));
+        }
+        self.advance();
+        let block = self.span();
+        self.skip_whitespace()?;
+        let start = self.pos;
+        let mut depth = 0;
+        while !self.is_eof() {
+            match self.cur()? {
+                ']' if depth == 0 => break,
+                ']' => depth -= 1,
+                '[' => depth += 1,
+                '\n' => self.newline(),
+                _ => {}
+            }
+            self.advance();
+        }
+        if self.is_eof() {
+            return Err(
+                UcreError::lex("ucc: Invalid lua block, missing ] to signal end").at(block),
+            );
+        }
+        let lua = self.string_from(start)?;
+        self.advance();
+        Ok(TokenType::Lua(lua.trim_end().to_string()))
+    }
+
+    /// extracts the input from start to the current position as a String
+    fn string_from(&self, start: usize) -> Result<String, UcreError> {
+        let bytes = self.source.get(start..self.pos).ok_or_else(|| {
+            self.error("ucc: Failed to extract bytes for a string from the input")
+        })?;
+        String::from_utf8(bytes.to_vec()).map_err(|e| {
+            self.error("ucc: Failed to convert a byte vector into a String")
+                .caused_by(e)
+        })
     }
 
     fn is_eof(&self) -> bool {
@@ -145,7 +256,7 @@ impl<'lexer> Lexer<'lexer> {
             if !cur.is_whitespace() {
                 break;
             } else if cur == '\n' {
-                self.line += 1;
+                self.newline();
             }
             self.advance();
         }
@@ -155,22 +266,115 @@ impl<'lexer> Lexer<'lexer> {
     fn cur(&self) -> Result<char, UcreError> {
         self.source
             .get(self.pos)
-            .ok_or_else(|| {
-                dbg!(UcreError::from_str(
-                    "ucc: can't access current character, unexpected EOF"
-                ))
-            })
+            .ok_or_else(|| self.error("ucc: can't access current character, unexpected EOF"))
             .map(|x| *x as char)
     }
 
     fn next(&self) -> Result<char, UcreError> {
         self.source
             .get(self.pos + 1)
-            .ok_or_else(|| UcreError::from_str("ucc: can't access next character, unexpected EOF"))
+            .ok_or_else(|| self.error("ucc: can't access next character, unexpected EOF"))
             .map(|x| *x as char)
     }
 
     fn advance(&mut self) {
         self.pos += 1
     }
+
+    /// newline has to be called while the current character is the '\n' ending a line
+    fn newline(&mut self) {
+        self.line += 1;
+        self.line_start = self.pos + 1;
+    }
+
+    /// span of the current character
+    fn span(&self) -> SourceSpan {
+        SourceSpan::new(self.line, self.pos.saturating_sub(self.line_start) + 1)
+    }
+
+    fn error(&self, msg: impl Into<Cow<'static, str>>) -> UcreError {
+        UcreError::lex(msg).at(self.span())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared::types::{
+        token::TokenType,
+        uom::{Color, ErrorKind, SourceSpan, Unit},
+    };
+
+    use super::Lexer;
+
+    fn lex(s: &str) -> Vec<TokenType> {
+        Lexer::new(s.as_bytes())
+            .run()
+            .expect("failed to lex")
+            .into_iter()
+            .map(|t| t.ty)
+            .collect()
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(
+            lex(r#"a "b\"c" 5 -1.5 12pt 50% #f523ad @hello-world $accent :hover"#),
+            vec![
+                TokenType::Ident("a".into()),
+                TokenType::String("b\"c".into()),
+                TokenType::Number(5.0),
+                TokenType::Number(-1.5),
+                TokenType::Length(12.0, Unit::Pt),
+                TokenType::Length(50.0, Unit::Percent),
+                TokenType::Color(Color::rgba(0xf5, 0x23, 0xad, 255)),
+                TokenType::NodeRef("hello-world".into()),
+                TokenType::Var("accent".into()),
+                TokenType::State("hover".into()),
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn color_functions() {
+        assert_eq!(
+            lex("rgb(245, 35, 173) hsla(0 100% 50% / 50%) rgb"),
+            vec![
+                TokenType::Color(Color::rgba(0xf5, 0x23, 0xad, 255)),
+                TokenType::Color(Color::rgba(255, 0, 0, 128)),
+                TokenType::Ident("rgb".into()),
+                TokenType::Eof,
+            ]
+        );
+        assert!(Lexer::new(b"rgb(1 2").run().is_err());
+        assert!(Lexer::new(b"hsl(a b c)").run().is_err());
+    }
+
+    #[test]
+    fn blocks_and_lines() {
+        let tokens = Lexer::new(b"heading {\n    level 5}\n// comment\nlua [ t[1] = 2 ]")
+            .run()
+            .expect("failed to lex");
+        let lines = tokens.iter().map(|t| t.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![1, 1, 2, 2, 2, 4, 4]);
+        assert_eq!(tokens[4].ty, TokenType::CurlyRight);
+        assert_eq!(tokens[5].ty, TokenType::Lua("t[1] = 2".into()));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Lexer::new(b"\"unterminated").run().is_err());
+        assert!(Lexer::new(b"12parsec").run().is_err());
+        assert!(Lexer::new(b"#zz").run().is_err());
+        assert!(Lexer::new(b"lua [ x").run().is_err());
+
+        let err = Lexer::new(b"a\n  b \"unterminated").run().err();
+        assert_eq!(err.as_ref().map(|e| e.kind()), Some(ErrorKind::Lex));
+        assert_eq!(err.and_then(|e| e.span()), Some(SourceSpan::new(2, 5)));
+        let err = Lexer::new(b"doc {\n\tdiv ~ }").run().err();
+        assert_eq!(
+            err.map(|e| e.to_string()),
+            Some("ucc: Unknown character '~' in line 2, column 6".into())
+        );
+    }
 }
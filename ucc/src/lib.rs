@@ -1,14 +1,476 @@
-use std::{fs::File, io::Read};
+use std::path::{Path, PathBuf};
 
 use lexer::Lexer;
-use shared::types::uom::UcreError;
+use parser::Parser;
+use shared::types::{
+    style::Stylesheet,
+    uom::{self, UcreError},
+};
 
 mod lexer;
+mod parser;
+
+/// lexes and parses the ucre lang source in bytes into a [uom::File]
+pub fn parse(bytes: &[u8]) -> Result<uom::File, UcreError> {
+    let tokens = Lexer::new(bytes).run()?;
+    Parser::new(tokens).parse()
+}
+
+/// reads file_name and parses it into a [uom::File], loading the style sheets it links to, see
+/// [load_sheets]
+pub fn parse_file(file_name: &str) -> Result<uom::File, UcreError> {
+    let mut file = parse(&read(file_name)?).map_err(|e| e.in_file(file_name))?;
+    let dir = Path::new(file_name).parent().unwrap_or(Path::new(""));
+    load_sheets(&mut file, dir).map_err(|e| e.in_file(file_name))?;
+    Ok(file)
+}
+
+/// lexes and parses a style sheet, the contents of a `style {}` block as found in `.ustyle`
+/// files
+pub fn parse_style(bytes: &[u8]) -> Result<Stylesheet, UcreError> {
+    let tokens = Lexer::new(bytes).run()?;
+    Parser::new(tokens).stylesheet()
+}
+
+/// reads the style sheet file_name and the sheets it imports, returned in cascade order: the
+/// imported sheets, then the sheet itself
+pub fn parse_style_file(file_name: &str) -> Result<Vec<Stylesheet>, UcreError> {
+    let mut sheets = vec![];
+    load_sheet(Path::new(file_name), &mut vec![], &mut vec![], &mut sheets)?;
+    Ok(sheets)
+}
+
+/// loads the style sheets file links to, relative to dir: those named by the `styles` meta-data,
+/// then those imported by its style, each preceded by the sheets it imports itself. A sheet
+/// linked more than once is loaded at its first position only, sheets importing each other are
+/// an error. Errors if the theme of the meta-data is defined by none of the sheets.
+pub fn load_sheets(file: &mut uom::File, dir: &Path) -> Result<(), UcreError> {
+    let mut loaded = vec![];
+    let mut sheets = vec![];
+    let paths = file
+        .meta_data()
+        .styles()
+        .into_iter()
+        .chain(file.style().imports.iter().map(String::as_str));
+    for path in paths {
+        load_sheet(&dir.join(path), &mut vec![], &mut loaded, &mut sheets)?;
+    }
+    file.set_linked_sheets(sheets);
+    if let Some(theme) = file.theme() {
+        if file.cascaded_style().theme(theme).is_none() {
+            return Err(UcreError::validate(format!("ucc: Unknown theme '{theme}'")));
+        }
+    }
+    Ok(())
+}
+
+/// loads the sheet at path after its imports into out, loading is the chain of sheets importing
+/// path
+fn load_sheet(
+    path: &Path,
+    loading: &mut Vec<PathBuf>,
+    loaded: &mut Vec<PathBuf>,
+    out: &mut Vec<Stylesheet>,
+) -> Result<(), UcreError> {
+    let canonical = path.canonicalize().map_err(|e| {
+        UcreError::io("Failed to read file")
+            .in_file(path)
+            .caused_by(e)
+    })?;
+    if loading.contains(&canonical) {
+        return Err(
+            UcreError::validate(format!("ucc: Cyclic import of '{}'", path.display()))
+                .in_file(path),
+        );
+    }
+    if loaded.contains(&canonical) {
+        return Ok(());
+    }
+    let sheet = parse_style(&read(&path.to_string_lossy())?).map_err(|e| e.in_file(path))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    loading.push(canonical.clone());
+    for import in &sheet.imports {
+        load_sheet(&dir.join(import), loading, loaded, out)?;
+    }
+    loading.pop();
+    loaded.push(canonical);
+    out.push(sheet);
+    Ok(())
+}
 
 pub fn run(file_name: &str) -> Result<(), UcreError> {
-    let file = File::open(file_name).map_err(|e| UcreError::new(e.to_string()))?;
-    let bytes = file.bytes().flatten().collect::<Vec<u8>>();
-    let mut l = Lexer::new(&bytes);
-    println!("{:?}", l.run()?);
+    let bytes = read(file_name)?;
+    let tokens = Lexer::new(&bytes).run().map_err(|e| e.in_file(file_name))?;
+    Parser::new(tokens)
+        .parse()
+        .map_err(|e| e.in_file(file_name))?;
     Ok(())
 }
+
+fn read(file_name: &str) -> Result<Vec<u8>, UcreError> {
+    std::fs::read(file_name).map_err(|e| {
+        UcreError::io("Failed to read file")
+            .in_file(file_name)
+            .caused_by(e)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use shared::types::{
+        style::{
+            Condition, Declaration, Keyframe, Keyframes, Orientation, Rule, State, Stylesheet,
+            Theme,
+        },
+        uom::{
+            nodes, AttributeValue, Color, Document, File, Inline, MetaData, NodeId, NodeKind,
+            Predicate, Unit,
+        },
+    };
+
+    use crate::{parse, parse_file, parse_style_file};
+
+    /// xorshift, enough randomness to generate documents without pulling in a dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn string(&mut self) -> String {
+            const CHARS: &[char] = &['a', 'Z', ' ', '"', '\\', '\n', '\t', '{', ']', 'ä', '7'];
+            (0..self.below(8))
+                .map(|_| CHARS[self.below(CHARS.len())])
+                .collect()
+        }
+
+        fn value(&mut self, depth: usize) -> AttributeValue {
+            match self.below(if depth == 0 { 9 } else { 8 }) {
+                0 => AttributeValue::String(self.string()),
+                1 => AttributeValue::Number(match self.below(4) {
+                    0 => [1e-7, 1e21, -0.5, 4503599627370497.0][self.below(4)],
+                    _ => self.below(2000) as f64 / 8.0 - 100.0,
+                }),
+                2 => AttributeValue::Length(self.below(400) as f64 / 4.0, Unit::Pt),
+                3 => AttributeValue::Length(self.below(100) as f64, Unit::Percent),
+                4 => AttributeValue::Color(Color::rgba(
+                    self.below(256) as u8,
+                    self.below(256) as u8,
+                    self.below(256) as u8,
+                    self.below(256) as u8,
+                )),
+                5 => AttributeValue::Boolean(self.below(2) == 0),
+                6 => AttributeValue::NodeRef(format!("n{}", self.below(10))),
+                7 => AttributeValue::Keyword(["left", "ease-out", "a_1"][self.below(3)].into()),
+                _ => {
+                    let mut l = (0..2 + self.below(3))
+                        .map(|_| self.value(1))
+                        .collect::<Vec<_>>();
+                    // in doc and meta-data blocks keywords can only be followed by keywords
+                    l.sort_by_key(|v| v.as_keyword().is_some());
+                    AttributeValue::List(l)
+                }
+            }
+        }
+
+        /// a value without a ucre lang syntax, rejected by documents and meta-data
+        fn invalid(&mut self) -> AttributeValue {
+            match self.below(6) {
+                0 => AttributeValue::Number(f64::NAN),
+                1 => AttributeValue::Length(f64::INFINITY, Unit::Px),
+                2 => AttributeValue::List(vec![]),
+                3 => AttributeValue::List(vec![self.value(1)]),
+                4 => AttributeValue::List(vec![self.value(1), self.value(0)]),
+                _ => AttributeValue::NodeRef(self.string()),
+            }
+        }
+
+        fn runs(&mut self, depth: usize) -> Vec<Inline> {
+            (0..1 + self.below(4))
+                .map(|_| match self.below(if depth > 2 { 3 } else { 6 }) {
+                    0 => Inline::Text(self.string()),
+                    1 => Inline::Code(self.string()),
+                    2 => Inline::LineBreak,
+                    3 => Inline::Strong(self.runs(depth + 1)),
+                    4 => Inline::Emphasis(self.runs(depth + 1)),
+                    _ => Inline::Link {
+                        href: self.string(),
+                        content: self.runs(depth + 1),
+                    },
+                })
+                .collect()
+        }
+
+        fn node(&mut self, doc: &mut Document, parent: NodeId, depth: usize, ids: &mut usize) {
+            let parent_kind = doc.get(parent).map(|n| n.kind());
+            let allowed = NodeKind::ALL
+                .into_iter()
+                .filter(|k| {
+                    parent == doc.root() || nodes::allows_child(parent_kind.as_ref().unwrap(), k)
+                })
+                .collect::<Vec<_>>();
+            if allowed.is_empty() {
+                return;
+            }
+            let mut node = nodes::new(allowed[self.below(allowed.len())].clone());
+            for i in 0..self.below(4) {
+                node.set_attr(format!("attr-{i}"), self.value(0));
+            }
+            if self.below(3) == 0 {
+                node.set_attr("id".into(), format!("n{ids}").into());
+                *ids += 1;
+            }
+            if self.below(2) == 0 {
+                let _ = node.set_inline(self.runs(0));
+            } else {
+                let _ = node.set_text(self.string());
+            }
+            let id = doc.append(parent, node).expect("generated nodes are valid");
+            if self.below(4) == 0 {
+                let invalid = self.invalid();
+                if invalid.validate().is_err() {
+                    assert!(doc.set_attr(id, "invalid".into(), invalid).is_err());
+                }
+            }
+            if depth < 3 {
+                for _ in 0..self.below(4) {
+                    self.node(doc, id, depth + 1, ids);
+                }
+            }
+        }
+
+        fn rule(&mut self, depth: usize) -> Rule {
+            let mut rule = Rule::new(NodeKind::ALL[self.below(NodeKind::ALL.len())].clone());
+            for i in 0..self.below(3) {
+                rule.matches.push(match self.below(2) {
+                    0 => Predicate::Exists(format!("attr-{i}")),
+                    _ => Predicate::Equals(format!("attr-{i}"), self.value(0)),
+                });
+            }
+            for _ in 0..self.below(2) {
+                rule.states.push(State::ALL[self.below(State::ALL.len())]);
+            }
+            for i in 0..self.below(4) {
+                let value = match self.below(4) {
+                    0 => AttributeValue::Var(format!("var-{}", self.below(3))),
+                    _ => self.value(0),
+                };
+                rule.declarations
+                    .push(Declaration::new(&format!("prop-{}", i % 2), value));
+            }
+            if depth < 2 {
+                for _ in 0..self.below(3) {
+                    rule.rules.push(self.rule(depth + 1));
+                }
+            }
+            rule
+        }
+
+        fn conditions(&mut self) -> Vec<Condition> {
+            (0..self.below(3))
+                .map(|_| match self.below(4) {
+                    0 => Condition::MinWidth(self.below(2000) as f64, Unit::Px),
+                    1 => Condition::MaxHeight(self.below(100) as f64 / 2.0, Unit::Em),
+                    2 => Condition::MinDensity(1.0 + self.below(3) as f64),
+                    _ => Condition::Orientation(Orientation::Landscape),
+                })
+                .collect()
+        }
+
+        fn variables(&mut self) -> Vec<Declaration> {
+            (0..self.below(3))
+                .map(|i| Declaration::new(&format!("var-{i}"), self.value(0)))
+                .collect()
+        }
+
+        fn file(&mut self) -> File {
+            let mut doc = Document::new();
+            let mut ids = 0;
+            let root = doc.root();
+            for _ in 0..self.below(5) {
+                self.node(&mut doc, root, 0, &mut ids);
+            }
+            let mut meta_data = MetaData::new();
+            for i in 0..self.below(3) {
+                let value = self.value(0);
+                meta_data
+                    .set(format!("key{i}"), value)
+                    .expect("custom keys accept any value");
+            }
+            let invalid = self.invalid();
+            if invalid.validate().is_err() {
+                assert!(meta_data.set("invalid".into(), invalid).is_err());
+            }
+            if self.below(2) == 0 {
+                meta_data.set_title(&self.string());
+                meta_data.set_keywords(&["a", "b", "c"][..self.below(4)]);
+            }
+            let lua = if self.below(2) == 0 {
+                String::new()
+            } else {
+                format!("x = {}\nprint(x)", self.below(100))
+            };
+            let style = Stylesheet {
+                imports: (0..self.below(2))
+                    .map(|i| format!("sheet-{i}.ustyle"))
+                    .collect(),
+                rules: (0..self.below(3))
+                    .map(|_| {
+                        let mut rule = self.rule(0);
+                        rule.conditions = self.conditions();
+                        rule
+                    })
+                    .collect(),
+                variables: self.variables(),
+                themes: (0..self.below(3))
+                    .map(|i| Theme {
+                        name: format!("theme-{i}"),
+                        variables: self.variables(),
+                    })
+                    .collect(),
+                keyframes: (0..self.below(2))
+                    .map(|i| Keyframes {
+                        name: format!("keyframes-{i}"),
+                        frames: (0..self.below(3))
+                            .map(|_| Keyframe {
+                                offset: self.below(401) as f64 / 4.0,
+                                declarations: self.variables(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            };
+            File::new(meta_data, lua, doc).with_style(style)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng(0x2545F4914F6CDD1D);
+        for _ in 0..500 {
+            let file = rng.file();
+            let source = file.to_string();
+            let parsed = parse(source.as_bytes())
+                .unwrap_or_else(|e| panic!("failed to parse printed source: {e}\n{source}"));
+            assert!(
+                file == parsed,
+                "round trip changed the file:\n{source}\n{parsed}"
+            );
+            assert_eq!(source, parsed.to_string());
+        }
+    }
+
+    #[test]
+    fn diff_patch() {
+        let mut rng = Rng(0x9E3779B97F4A7C15);
+        for _ in 0..300 {
+            let (mut a, b) = (rng.file(), rng.file());
+            assert!(a.doc().diff(a.doc()).is_empty());
+            let edits = a.doc().diff(b.doc());
+            let original = a.to_string();
+            a.doc_mut()
+                .transaction(|doc| doc.patch(&edits))
+                .unwrap_or_else(|e| panic!("failed to apply diff: {e}\n{a}\n{b}"));
+            assert!(a.doc() == b.doc(), "patched document differs:\n{a}\n{b}");
+            if !edits.is_empty() {
+                assert!(a.doc_mut().undo().expect("failed to undo patch"));
+                assert_eq!(a.to_string(), original);
+                assert!(a.doc_mut().redo().expect("failed to redo patch"));
+                assert!(a.doc() == b.doc(), "redone document differs:\n{a}\n{b}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_keywords() {
+        for keywords in [&[][..], &["only"], &["a", "b"]] {
+            let mut meta_data = MetaData::new();
+            meta_data.set_title("a");
+            meta_data.set_keywords(keywords);
+            let file = File::new(meta_data, String::new(), Document::new());
+            let parsed = parse(file.to_string().as_bytes())
+                .unwrap_or_else(|e| panic!("failed to parse {keywords:?}: {e}"));
+            assert!(file == parsed, "{keywords:?}");
+            assert_eq!(parsed.meta_data().keywords(), keywords);
+        }
+    }
+
+    #[test]
+    fn round_trip_example() {
+        let file = parse(include_bytes!("../../example/helloworld.ucre")).expect("failed to parse");
+        let parsed = parse(file.to_string().as_bytes()).expect("failed to parse printed source");
+        assert!(file == parsed);
+    }
+
+    #[test]
+    fn sheets() {
+        let dir = std::env::temp_dir().join(format!("ucc-sheets-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("brand")).expect("failed to create dir");
+        let write = |name: &str, content: &str| {
+            std::fs::write(dir.join(name), content).expect("failed to write file");
+        };
+        write("brand/base.ustyle", "vars {\n accent black\n}");
+        write(
+            "brand/brand.ustyle",
+            "import \"base.ustyle\"\ntheme dark {\n accent white\n}\nheading { color $accent }",
+        );
+        write(
+            "extra.ustyle",
+            "import \"brand/base.ustyle\"\nimage { radius 1px }",
+        );
+        write(
+            "doc.ucre",
+            "meta-data {\n styles \"brand/brand.ustyle\"\n theme \"dark\"\n}\n\
+             style {\n import \"extra.ustyle\"\n heading { fontSize 10pt }\n}\n\
+             doc { heading { level 1 } }",
+        );
+        write("a.ustyle", "import \"b.ustyle\"");
+        write("b.ustyle", "import \"a.ustyle\"");
+        write(
+            "missing.ucre",
+            "meta-data {\n styles \"base.ustyle\"\n theme \"dark\"\n}",
+        );
+
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        let file = parse_file(&path("doc.ucre")).expect("failed to parse");
+        // base is loaded once, before the brand sheet importing it first
+        assert_eq!(
+            file.linked_sheets()
+                .iter()
+                .map(|s| s.imports.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                vec![],
+                vec!["base.ustyle".to_string()],
+                vec!["brand/base.ustyle".to_string()]
+            ]
+        );
+        let styles = file.computed_styles().expect("dark is defined by brand");
+        let heading = styles
+            .get(file.doc().children(file.doc().root())[0])
+            .expect("heading is styled");
+        assert_eq!(
+            heading.get("color"),
+            Some(&AttributeValue::Keyword("white".into()))
+        );
+        assert_eq!(heading.px("fontSize", 0.0).map(f64::round), Some(13.0));
+
+        assert_eq!(
+            parse_style_file(&path("brand/brand.ustyle"))
+                .map(|s| s.len())
+                .ok(),
+            Some(2)
+        );
+        assert!(parse_style_file(&path("a.ustyle")).is_err());
+        assert!(parse_file(&path("missing.ucre")).is_err());
+        std::fs::remove_dir_all(&dir).expect("failed to remove dir");
+    }
+}
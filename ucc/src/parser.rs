@@ -0,0 +1,935 @@
+use shared::types::{
+    style::{Condition, Declaration, Keyframe, Keyframes, Rule, State, Stylesheet, Theme},
+    token::{Token, TokenType},
+    uom::{
+        lookup, nodes, AttributeValue, Document, File, Inline, MetaData, Node, NodeKind, Predicate,
+        SourceSpan, UcreError, Unit,
+    },
+};
+
+/// Parser converts the tokens produced by the [crate::lexer::Lexer] into a [File].
+///
+/// Attributes are written as a key followed by one or more values on the same line, a key
+/// followed by `{` opens a child node instead.
+pub struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    pub fn parse(mut self) -> Result<File, UcreError> {
+        let mut meta_data = MetaData::new();
+        let mut lua = String::new();
+        let mut doc = Document::new();
+        let mut style = Stylesheet::new();
+        // line of the theme selected in the meta-data, checked once all styles are parsed
+        let mut theme_line = None;
+        loop {
+            match &self.cur().ty {
+                TokenType::Eof => break,
+                TokenType::Lua(l) => {
+                    if !lua.is_empty() {
+                        lua.push('\n');
+                    }
+                    lua.push_str(l);
+                    self.advance();
+                }
+                TokenType::Ident(i) if i == "meta-data" => {
+                    self.advance();
+                    self.expect(TokenType::CurlyLeft)?;
+                    while !self.is(&TokenType::CurlyRight) {
+                        let line = self.cur().line;
+                        let (key, value) = self.attribute(false)?;
+                        if key == "theme" {
+                            theme_line = Some(line);
+                        }
+                        meta_data
+                            .set(key, value)
+                            .map_err(|e| e.at(SourceSpan::line(line)))?;
+                    }
+                    self.expect(TokenType::CurlyRight)?;
+                }
+                TokenType::Ident(i) if i == "doc" => {
+                    self.advance();
+                    self.expect(TokenType::CurlyLeft)?;
+                    while !self.is(&TokenType::CurlyRight) {
+                        let line = self.cur().line;
+                        let node = self.node()?;
+                        doc.append(doc.root(), node)
+                            .map_err(|e| e.at(SourceSpan::line(line)))?;
+                    }
+                    self.expect(TokenType::CurlyRight)?;
+                }
+                TokenType::Ident(i) if i == "style" => {
+                    self.advance();
+                    self.expect(TokenType::CurlyLeft)?;
+                    self.style(&mut style, &TokenType::CurlyRight)?;
+                    self.expect(TokenType::CurlyRight)?;
+                }
+                t => return Err(self.error(format!("Unexpected {t:?} at the top level"))),
+            }
+        }
+        // themes of linked sheets are only known once ucc loaded them, see crate::load_sheets
+        let linked = !meta_data.styles().is_empty() || !style.imports.is_empty();
+        if let (Some(theme), Some(line), false) = (meta_data.theme(), theme_line, linked) {
+            if style.theme(theme).is_none() {
+                return Err(UcreError::validate(format!("ucc: Unknown theme '{theme}'"))
+                    .at(SourceSpan::line(line)));
+            }
+        }
+        Ok(File::new(meta_data, lua, doc).with_style(style))
+    }
+
+    /// stylesheet parses a style sheet file, holding the contents of a `style {}` block
+    pub fn stylesheet(mut self) -> Result<Stylesheet, UcreError> {
+        let mut style = Stylesheet::new();
+        self.style(&mut style, &TokenType::Eof)?;
+        Ok(style)
+    }
+
+    /// style parses the contents of a `style {}` block into style until end
+    fn style(&mut self, style: &mut Stylesheet, end: &TokenType) -> Result<(), UcreError> {
+        while !self.is(end) {
+            match &self.cur().ty {
+                TokenType::Ident(i) if i == "import" => {
+                    let line = self.cur().line;
+                    self.advance();
+                    match &self.cur().ty {
+                        TokenType::String(path) if self.cur().line == line => {
+                            style.imports.push(path.clone());
+                            self.advance();
+                        }
+                        t => {
+                            return Err(
+                                self.error(format!("Expected the path of an import, got {t:?}"))
+                            )
+                        }
+                    }
+                }
+                TokenType::Ident(i) if i == "vars" => {
+                    self.advance();
+                    let variables = self.declarations()?;
+                    style.variables.extend(variables);
+                }
+                TokenType::Ident(i) if i == "theme" => {
+                    self.advance();
+                    let name = match &self.cur().ty {
+                        TokenType::Ident(i) => i.clone(),
+                        t => return Err(self.error(format!("Expected a theme name, got {t:?}"))),
+                    };
+                    self.advance();
+                    let variables = self.declarations()?;
+                    // themes of the same name are merged, as `vars {}` blocks are
+                    match style.themes.iter_mut().find(|t| t.name == name) {
+                        Some(theme) => theme.variables.extend(variables),
+                        None => style.themes.push(Theme { name, variables }),
+                    }
+                }
+                TokenType::Ident(i) if i == "keyframes" => {
+                    let keyframes = self.keyframes()?;
+                    style.keyframes.push(keyframes);
+                }
+                TokenType::Ident(i) if i == "when" => self.when(&[], &mut style.rules)?,
+                _ => style.rules.push(self.rule()?),
+            }
+        }
+        Ok(())
+    }
+
+    /// node parses `kind { attributes and children }`
+    fn node(&mut self) -> Result<Box<dyn Node>, UcreError> {
+        let line = self.cur().line;
+        let name = match &self.cur().ty {
+            TokenType::Ident(i) => i.clone(),
+            t => return Err(self.error(format!("Expected a node, got {t:?}"))),
+        };
+        // kinds of plugins not registered resolve to generic nodes instead of failing
+        let kind = NodeKind::resolve(&name)
+            .ok_or_else(|| self.error(format!("Invalid node kind '{name}'")))?;
+        let mut node = nodes::new(kind);
+        self.advance();
+        self.expect(TokenType::CurlyLeft)?;
+
+        let mut children = vec![];
+        while !self.is(&TokenType::CurlyRight) {
+            if matches!(self.peek().ty, TokenType::CurlyLeft) {
+                if matches!(&self.cur().ty, TokenType::Ident(i) if i == "inline") {
+                    let line = self.cur().line;
+                    self.advance();
+                    let runs = self.inline_block()?;
+                    node.set_inline(runs)
+                        .map_err(|e| e.at(SourceSpan::line(line)))?;
+                } else {
+                    children.push(self.node()?);
+                }
+                continue;
+            }
+            let line = self.cur().line;
+            match self.attribute(false)? {
+                (key, AttributeValue::String(s)) if key == "text" => {
+                    node.set_text(s).map_err(|e| e.at(SourceSpan::line(line)))?
+                }
+                (key, value) => {
+                    node.validate_attr(&key, &value)
+                        .map_err(|e| e.at(SourceSpan::line(line)))?;
+                    node.set_attr(key, value)
+                }
+            }
+        }
+        self.expect(TokenType::CurlyRight)?;
+
+        if !children.is_empty() {
+            node.set_children(children)
+                .map_err(|e| e.at(SourceSpan::line(line)))?;
+        }
+        if let Some(parsed) = lookup(&name).and_then(|t| t.parsed) {
+            parsed(node.as_mut()).map_err(|e| e.at(SourceSpan::line(line)))?;
+        }
+        Ok(node)
+    }
+
+    /// keyframes parses `keyframes name { offset { declarations } }`, offsets being percentages
+    /// or `from` and `to`
+    fn keyframes(&mut self) -> Result<Keyframes, UcreError> {
+        self.advance();
+        let name = match &self.cur().ty {
+            TokenType::Ident(i) => i.clone(),
+            t => return Err(self.error(format!("Expected a keyframes name, got {t:?}"))),
+        };
+        self.advance();
+        self.expect(TokenType::CurlyLeft)?;
+        let mut frames = vec![];
+        while !self.is(&TokenType::CurlyRight) {
+            let offset = match &self.cur().ty {
+                TokenType::Length(n, Unit::Percent) if (0.0..=100.0).contains(n) => *n,
+                TokenType::Ident(i) if i == "from" => 0.0,
+                TokenType::Ident(i) if i == "to" => 100.0,
+                t => return Err(self.error(format!("Expected a keyframe offset, got {t:?}"))),
+            };
+            self.advance();
+            let declarations = self.declarations()?;
+            frames.push(Keyframe {
+                offset,
+                declarations,
+            });
+        }
+        self.expect(TokenType::CurlyRight)?;
+        Ok(Keyframes { name, frames })
+    }
+
+    /// when parses `when conditions { rules and when blocks }`, appending the rules to out with
+    /// the conditions of the block and those of the blocks enclosing it
+    fn when(&mut self, enclosing: &[Condition], out: &mut Vec<Rule>) -> Result<(), UcreError> {
+        let line = self.cur().line;
+        self.advance();
+        let mut conditions = enclosing.to_vec();
+        while self.cur().line == line && !self.is(&TokenType::CurlyLeft) {
+            let name = match &self.cur().ty {
+                TokenType::Ident(i) => i.clone(),
+                t => return Err(self.error(format!("Expected a condition, got {t:?}"))),
+            };
+            self.advance();
+            let value = self
+                .value(true)
+                .filter(|_| self.cur().line == line)
+                .ok_or_else(|| self.error(format!("Condition '{name}' is missing a value")))?;
+            self.advance();
+            conditions
+                .push(Condition::new(&name, &value).map_err(|e| e.at(SourceSpan::line(line)))?);
+        }
+        if conditions.len() == enclosing.len() {
+            return Err(self.error("Expected conditions after 'when'".into()));
+        }
+        self.expect(TokenType::CurlyLeft)?;
+        while !self.is(&TokenType::CurlyRight) {
+            if matches!(&self.cur().ty, TokenType::Ident(i) if i == "when") {
+                self.when(&conditions, out)?;
+                continue;
+            }
+            let mut rule = self.rule()?;
+            rule.conditions = conditions.clone();
+            out.push(rule);
+        }
+        self.expect(TokenType::CurlyRight)
+    }
+
+    /// rule parses `kind { matches { predicates and states } declarations and nested rules }`
+    fn rule(&mut self) -> Result<Rule, UcreError> {
+        let name = match &self.cur().ty {
+            TokenType::Ident(i) => i.clone(),
+            t => return Err(self.error(format!("Expected a style rule, got {t:?}"))),
+        };
+        let kind = NodeKind::resolve(&name)
+            .ok_or_else(|| self.error(format!("Invalid node kind '{name}'")))?;
+        let mut rule = Rule::new(kind);
+        self.advance();
+        self.expect(TokenType::CurlyLeft)?;
+        while !self.is(&TokenType::CurlyRight) {
+            if matches!(self.peek().ty, TokenType::CurlyLeft) {
+                if matches!(&self.cur().ty, TokenType::Ident(i) if i == "matches") {
+                    self.advance();
+                    self.expect(TokenType::CurlyLeft)?;
+                    while !self.is(&TokenType::CurlyRight) {
+                        if let TokenType::State(name) = &self.cur().ty {
+                            let state = State::from_name(name)
+                                .ok_or_else(|| self.error(format!("Unknown state ':{name}'")))?;
+                            self.advance();
+                            rule.states.push(state);
+                        } else {
+                            rule.matches.push(self.predicate()?);
+                        }
+                    }
+                    self.expect(TokenType::CurlyRight)?;
+                } else {
+                    rule.rules.push(self.rule()?);
+                }
+                continue;
+            }
+            let (property, value) = self.attribute(true)?;
+            rule.declarations.push(Declaration { property, value });
+        }
+        self.expect(TokenType::CurlyRight)?;
+        Ok(rule)
+    }
+
+    /// declarations parses `{ property value }` as used by `vars` and `theme` blocks
+    fn declarations(&mut self) -> Result<Vec<Declaration>, UcreError> {
+        self.expect(TokenType::CurlyLeft)?;
+        let mut declarations = vec![];
+        while !self.is(&TokenType::CurlyRight) {
+            let (property, value) = self.attribute(true)?;
+            declarations.push(Declaration { property, value });
+        }
+        self.expect(TokenType::CurlyRight)?;
+        Ok(declarations)
+    }
+
+    /// predicate parses `key value` into [Predicate::Equals] and a lone `key` into
+    /// [Predicate::Exists]
+    fn predicate(&mut self) -> Result<Predicate, UcreError> {
+        if let TokenType::Ident(key) = &self.cur().ty {
+            let next = self.peek();
+            if next.line != self.cur().line || matches!(next.ty, TokenType::CurlyRight) {
+                let key = key.clone();
+                self.advance();
+                return Ok(Predicate::Exists(key));
+            }
+        }
+        let (key, value) = self.attribute(true)?;
+        Ok(Predicate::Equals(key, value))
+    }
+
+    /// attribute parses a key and all values following it in the same line. Bare identifiers
+    /// other than `true` and `false` are [AttributeValue::Keyword]s. If keywords is set, as in
+    /// style declarations, all of them are values. Otherwise an identifier followed by a value
+    /// starts the next attribute, as in `heading { id "a" level 1 }`, while `heading { textAlign
+    /// left }` sets textAlign to the keyword left.
+    fn attribute(&mut self, keywords: bool) -> Result<(String, AttributeValue), UcreError> {
+        let key = match &self.cur().ty {
+            TokenType::Ident(i) => i.clone(),
+            t => return Err(self.error(format!("Expected an attribute name, got {t:?}"))),
+        };
+        let line = self.cur().line;
+        self.advance();
+        let mut values = vec![];
+        while self.cur().line == line {
+            let Some(value) = self.value(keywords) else {
+                break;
+            };
+            values.push(value);
+            self.advance();
+        }
+        if values.is_empty() {
+            return Err(self.error(format!("Attribute '{key}' is missing a value")));
+        }
+        Ok((key, values.into()))
+    }
+
+    /// value converts the current token into an attribute value, None if it is no value, see
+    /// [Parser::attribute] for keywords
+    fn value(&self, keywords: bool) -> Option<AttributeValue> {
+        let line = self.cur().line;
+        // an identifier followed by a value in the same line is the key of the next attribute
+        let keyword = keywords || {
+            let next = self.peek();
+            next.line != line
+                || match &next.ty {
+                    TokenType::CurlyRight => true,
+                    TokenType::Ident(i) => i != "true" && i != "false",
+                    _ => false,
+                }
+        };
+        Some(match &self.cur().ty {
+            TokenType::String(s) => AttributeValue::String(s.clone()),
+            TokenType::Number(n) => AttributeValue::Number(*n),
+            TokenType::Length(n, u) => AttributeValue::Length(*n, *u),
+            TokenType::Color(c) => AttributeValue::Color(*c),
+            TokenType::NodeRef(r) => AttributeValue::NodeRef(r.clone()),
+            TokenType::Var(v) => AttributeValue::Var(v.clone()),
+            TokenType::Ident(i) if i == "true" => AttributeValue::Boolean(true),
+            TokenType::Ident(i) if i == "false" => AttributeValue::Boolean(false),
+            TokenType::Ident(i) if keyword => AttributeValue::Keyword(i.clone()),
+            _ => return None,
+        })
+    }
+
+    /// inline_block parses `{ runs }` into [Inline] runs, see [Inline] for the syntax
+    fn inline_block(&mut self) -> Result<Vec<Inline>, UcreError> {
+        self.expect(TokenType::CurlyLeft)?;
+        let mut runs = vec![];
+        while !self.is(&TokenType::CurlyRight) {
+            runs.push(self.inline_run()?);
+        }
+        self.expect(TokenType::CurlyRight)?;
+        Ok(runs)
+    }
+
+    fn inline_run(&mut self) -> Result<Inline, UcreError> {
+        let name = match &self.cur().ty {
+            TokenType::Ident(i) => i.clone(),
+            t => return Err(self.error(format!("Expected an inline run, got {t:?}"))),
+        };
+        let line = self.cur().line;
+        self.advance();
+        Ok(match name.as_str() {
+            "break" => Inline::LineBreak,
+            "text" => Inline::Text(self.inline_string(line)?),
+            "code" => Inline::Code(self.inline_string(line)?),
+            "strong" | "emphasis" => {
+                let content = if self.is(&TokenType::CurlyLeft) {
+                    self.inline_block()?
+                } else {
+                    vec![Inline::Text(self.inline_string(line)?)]
+                };
+                if name == "strong" {
+                    Inline::Strong(content)
+                } else {
+                    Inline::Emphasis(content)
+                }
+            }
+            "link" => {
+                let href = self.inline_string(line)?;
+                let content = if self.is(&TokenType::CurlyLeft) {
+                    self.inline_block()?
+                } else if matches!(self.cur().ty, TokenType::String(_)) && self.cur().line == line {
+                    vec![Inline::Text(self.inline_string(line)?)]
+                } else {
+                    vec![Inline::Text(href.clone())]
+                };
+                Inline::Link { href, content }
+            }
+            _ => return Err(self.error(format!("Unknown inline run '{name}'"))),
+        })
+    }
+
+    /// inline_string expects a string in the given line
+    fn inline_string(&mut self, line: usize) -> Result<String, UcreError> {
+        match &self.cur().ty {
+            TokenType::String(s) if self.cur().line == line => {
+                let s = s.clone();
+                self.advance();
+                Ok(s)
+            }
+            _ => Err(UcreError::parse("ucc: Expected a string").at(SourceSpan::line(line))),
+        }
+    }
+
+    fn expect(&mut self, t: TokenType) -> Result<(), UcreError> {
+        if !self.is(&t) {
+            return Err(self.error(format!("Expected {t:?}, got {:?}", self.cur().ty)));
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn is(&self, t: &TokenType) -> bool {
+        self.cur().ty == *t
+    }
+
+    fn cur(&self) -> &Token {
+        // the lexer always terminates the token stream with TokenType::Eof
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[(self.pos + 1).min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) {
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1
+        }
+    }
+
+    fn error(&self, msg: String) -> UcreError {
+        UcreError::parse(format!("ucc: {msg}")).at(SourceSpan::line(self.cur().line))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use shared::types::{
+        style::{Condition, Declaration, Orientation, State},
+        uom::{
+            nodes, register, AttributeValue, ErrorKind, Inline, Node, NodeKind, NodeType,
+            Predicate, UcreError, Unit,
+        },
+    };
+
+    use crate::parse;
+
+    #[test]
+    fn attributes() {
+        let file = parse(
+            br#"doc {
+                heading {
+                    id "hello-world"
+                    level 5
+                    fontSize 12pt
+                    padding 2px 4px
+                    hidden false
+                    text "Hello World"
+                }
+            }"#,
+        )
+        .expect("failed to parse");
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.children(doc.root())[0])
+            .expect("heading exists");
+        assert_eq!(heading.kind(), NodeKind::Heading);
+        assert_eq!(heading.text(), Some("Hello World"));
+        let attr = heading.attr();
+        assert_eq!(attr["id"], AttributeValue::String("hello-world".into()));
+        assert_eq!(attr["level"].as_number(), Some(5.0));
+        assert_eq!(attr["fontSize"].as_length(), Some((12.0, Unit::Pt)));
+        assert_eq!(
+            attr["padding"],
+            AttributeValue::List(vec![
+                AttributeValue::Length(2.0, Unit::Px),
+                AttributeValue::Length(4.0, Unit::Px)
+            ])
+        );
+        assert_eq!(attr["hidden"].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn one_line() {
+        let file = parse(
+            br#"meta-data { title "a" author "b" }
+            doc { heading { id "h" level 2 hidden true text "x" } }
+            style { heading { transition color 150ms ease-out } }"#,
+        )
+        .expect("failed to parse");
+        assert_eq!(file.meta_data().title(), Some("a"));
+        assert_eq!(file.meta_data().author(), Some("b"));
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.children(doc.root())[0])
+            .expect("heading exists");
+        assert_eq!(heading.text(), Some("x"));
+        assert_eq!(heading.attr()["id"], AttributeValue::String("h".into()));
+        assert_eq!(heading.attr()["level"].as_number(), Some(2.0));
+        assert_eq!(heading.attr()["hidden"].as_bool(), Some(true));
+        // keywords are values in style declarations
+        assert_eq!(
+            file.style().rules[0].declarations[0].value.as_list().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn keywords() {
+        let source = r#"meta-data {
+    theme dark
+}
+
+doc {
+    heading {
+        align left
+        edges top bottom
+        tag inline
+        text "x"
+    }
+
+    paragraph {
+        a 1 top
+    }
+}
+
+style {
+    theme dark {
+        text white
+    }
+
+    heading {
+        transition color 150ms ease-out
+    }
+}
+"#;
+        let file = parse(source.as_bytes()).expect("failed to parse");
+        assert_eq!(file.meta_data().theme(), Some("dark"));
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.children(doc.root())[0])
+            .expect("heading exists");
+        assert_eq!(
+            heading.attr()["align"],
+            AttributeValue::Keyword("left".into())
+        );
+        assert_eq!(
+            heading.attr()["edges"],
+            AttributeValue::List(vec![
+                AttributeValue::Keyword("top".into()),
+                AttributeValue::Keyword("bottom".into())
+            ])
+        );
+        assert_eq!(heading.text(), Some("x"));
+        // keywords are written back bare
+        assert_eq!(file.to_string(), source);
+
+        // a keyword followed by a value starts the next attribute
+        let file = parse(b"doc { heading { align left level 2 } }").expect("failed to parse");
+        let doc = file.doc();
+        let heading = doc
+            .get(doc.children(doc.root())[0])
+            .expect("heading exists");
+        assert_eq!(heading.attr()["align"].as_keyword(), Some("left"));
+        assert_eq!(heading.attr()["level"].as_number(), Some(2.0));
+    }
+
+    #[test]
+    fn example() {
+        let file = parse(include_bytes!("../../example/helloworld.ucre")).expect("failed to parse");
+        let doc = file.doc();
+        let kinds = doc
+            .descendants(doc.root())
+            .filter_map(|id| doc.get(id).map(|n| n.kind()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                NodeKind::Heading,
+                NodeKind::Container,
+                NodeKind::Paragraph,
+                NodeKind::Image
+            ]
+        );
+        let heading = doc.by_id("hello-world").expect("heading is indexed");
+        assert_eq!(doc.get(heading).and_then(|n| n.text()), Some("Hello World"));
+    }
+
+    #[test]
+    fn inline() {
+        let file = parse(
+            br#"doc {
+                paragraph {
+                    inline {
+                        text "This is "
+                        strong "bold"
+                        emphasis {
+                            code "x"
+                        }
+                        link "https://xnacly.me" "blog"
+                        break
+                    }
+                }
+            }"#,
+        )
+        .expect("failed to parse");
+        let doc = file.doc();
+        let p = doc
+            .get(doc.children(doc.root())[0])
+            .expect("paragraph exists");
+        assert_eq!(p.text(), Some("This is boldxblog\n"));
+        assert_eq!(
+            p.inline().expect("paragraph has inline content"),
+            &[
+                Inline::Text("This is ".into()),
+                Inline::Strong(vec![Inline::Text("bold".into())]),
+                Inline::Emphasis(vec![Inline::Code("x".into())]),
+                Inline::Link {
+                    href: "https://xnacly.me".into(),
+                    content: vec![Inline::Text("blog".into())]
+                },
+                Inline::LineBreak,
+            ]
+        );
+        assert!(parse(b"doc { image { inline { text \"a\" } } }").is_err());
+        assert!(parse(b"doc { paragraph { inline { blink \"a\" } } }").is_err());
+    }
+
+    #[test]
+    fn custom_nodes() {
+        fn callout() -> Box<dyn Node> {
+            Box::new(nodes::Generic::new("callout"))
+        }
+        fn parsed(node: &mut dyn Node) -> Result<(), UcreError> {
+            match node.attr().get("severity") {
+                Some(_) => Ok(()),
+                None => Err(UcreError::validate("callout requires a severity")),
+            }
+        }
+        register(NodeType {
+            name: "callout",
+            new: callout,
+            parsed: Some(parsed),
+        })
+        .expect("failed to register");
+
+        let file = parse(
+            b"doc {\n callout {\n severity \"warn\"\n text \"careful\"\n }\n sparkline {\n data 1 2 3\n span { text \"a\" }\n }\n}",
+        )
+        .expect("failed to parse");
+        let doc = file.doc();
+        let kinds = doc
+            .descendants(doc.root())
+            .filter_map(|id| doc.get(id).map(|n| n.kind().name().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, vec!["callout", "sparkline", "span"]);
+        assert_eq!(
+            parse(file.to_string().as_bytes()).map(|f| f == file).ok(),
+            Some(true)
+        );
+
+        let err = parse(b"doc {\n callout {\n text \"a\"\n }\n}").err();
+        assert_eq!(err.as_ref().map(|e| e.kind()), Some(ErrorKind::Validate));
+        assert_eq!(
+            err.map(|e| e.to_string()),
+            Some("callout requires a severity in line 2".into())
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let file = parse(b"meta-data {\n title \"t\"\n}\nstyle { a { b 1 } }\nlua [ x = 1 ]")
+            .expect("failed to parse");
+        assert_eq!(file.lua(), "x = 1");
+        assert_eq!(file.meta_data().title(), Some("t"));
+        assert!(file.doc().is_empty());
+        assert!(parse(b"doc {\n heading { id \"a\" }\n heading { id \"a\" }\n}").is_err());
+    }
+
+    #[test]
+    fn style() {
+        let file = parse(
+            br#"style {
+                import "brand.ustyle"
+                heading {
+                    matches {
+                        id "hello-world"
+                        text "Hello World"
+                        level
+                        :hover
+                        :focus
+                    }
+                    fontSize 12pt
+                    padding 2px 4px
+                }
+
+                container { image { radius 50% } }
+            }
+            style { chart { color #fff } }"#,
+        )
+        .expect("failed to parse");
+        let rules = &file.style().rules;
+        assert!(file.style().themes.is_empty());
+        assert_eq!(file.style().imports, vec!["brand.ustyle".to_string()]);
+        assert_eq!(
+            rules.iter().map(|r| r.kind.name()).collect::<Vec<_>>(),
+            vec!["heading", "container", "chart"]
+        );
+        assert_eq!(
+            rules[0].matches,
+            vec![
+                Predicate::Equals("id".into(), "hello-world".into()),
+                Predicate::Equals("text".into(), "Hello World".into()),
+                Predicate::Exists("level".into()),
+            ]
+        );
+        assert_eq!(rules[0].states, vec![State::Hover, State::Focus]);
+        assert_eq!(
+            rules[0].declarations[1].value,
+            AttributeValue::List(vec![
+                AttributeValue::Length(2.0, Unit::Px),
+                AttributeValue::Length(4.0, Unit::Px)
+            ])
+        );
+        let image = &rules[1].rules[0];
+        assert_eq!(image.kind, NodeKind::Image);
+        assert_eq!(
+            image.declarations[0].value,
+            AttributeValue::Length(50.0, Unit::Percent)
+        );
+        assert_eq!(
+            parse(file.to_string().as_bytes()).map(|f| f == file).ok(),
+            Some(true)
+        );
+        assert!(parse(b"style { heading { matches { id } color } }").is_err());
+        assert!(parse(b"style { Heading { } }").is_err());
+        assert!(parse(b"style { link { matches { :pressed } } }").is_err());
+        assert!(parse(b"style { import brand }").is_err());
+        // the theme may be defined by the imported sheet
+        assert!(parse(b"meta-data {\n theme \"dark\"\n}\nstyle { import \"a.ustyle\" }").is_ok());
+    }
+
+    #[test]
+    fn themes() {
+        let file = parse(
+            br#"meta-data {
+                theme "dark"
+            }
+            style {
+                vars {
+                    text black
+                    gap 2px
+                }
+                theme dark { text white }
+                theme dark {
+                    gap $text
+                }
+                heading { color $text }
+            }"#,
+        )
+        .expect("failed to parse");
+        let style = file.style();
+        assert_eq!(
+            style.variables,
+            vec![
+                Declaration::new("text", AttributeValue::Keyword("black".into())),
+                Declaration::new("gap", AttributeValue::Length(2.0, Unit::Px)),
+            ]
+        );
+        assert_eq!(style.themes.len(), 1);
+        assert_eq!(style.theme("dark").map(|t| t.variables.len()), Some(2));
+        assert_eq!(
+            style.rules[0].declarations[0].value,
+            AttributeValue::Var("text".into())
+        );
+        assert_eq!(file.theme(), Some("dark"));
+        assert_eq!(
+            parse(file.to_string().as_bytes()).map(|f| f == file).ok(),
+            Some(true)
+        );
+        let err = parse(
+            b"meta-data {
+ theme \"light\"
+}",
+        )
+        .err()
+        .expect("light is not defined");
+        assert_eq!(err.kind(), ErrorKind::Validate);
+        assert_eq!(err.span().map(|s| s.line), Some(2));
+        assert!(parse(b"style { theme { a 1 } }").is_err());
+    }
+
+    #[test]
+    fn conditions() {
+        let file = parse(
+            br#"style {
+                heading { fontSize 20pt }
+                when maxWidth 600px {
+                    heading { fontSize 10pt }
+                    when orientation portrait minDensity 2 {
+                        image { radius 0px }
+                    }
+                    container { }
+                }
+            }"#,
+        )
+        .expect("failed to parse");
+        let narrow = Condition::MaxWidth(600.0, Unit::Px);
+        assert_eq!(
+            file.style()
+                .rules
+                .iter()
+                .map(|r| (r.kind.clone(), r.conditions.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (NodeKind::Heading, vec![]),
+                (NodeKind::Heading, vec![narrow]),
+                (
+                    NodeKind::Image,
+                    vec![
+                        narrow,
+                        Condition::Orientation(Orientation::Portrait),
+                        Condition::MinDensity(2.0)
+                    ]
+                ),
+                (NodeKind::Container, vec![narrow]),
+            ]
+        );
+        assert_eq!(
+            parse(file.to_string().as_bytes()).map(|f| f == file).ok(),
+            Some(true)
+        );
+        for source in [
+            "style { when { heading { } } }",
+            "style { when maxWidth { heading { } } }",
+            "style { when maxWidth 50% { heading { } } }",
+            "style { when size 5px { heading { } } }",
+            "style { heading { when maxWidth 5px { } } }",
+        ] {
+            assert!(parse(source.as_bytes()).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn keyframes() {
+        let file = parse(
+            br#"style {
+                keyframes pulse {
+                    from { color white }
+                    50% {
+                        color $accent
+                        width 2em
+                    }
+                    to { color white }
+                }
+                heading { animation pulse 1.5s ease-in infinite }
+                image { transition all 200ms }
+            }"#,
+        )
+        .expect("failed to parse");
+        let keyframes = &file.style().keyframes[0];
+        assert_eq!(keyframes.name, "pulse");
+        assert_eq!(
+            keyframes
+                .frames
+                .iter()
+                .map(|f| f.offset)
+                .collect::<Vec<_>>(),
+            vec![0.0, 50.0, 100.0]
+        );
+        assert_eq!(keyframes.frames[1].declarations.len(), 2);
+        assert_eq!(
+            file.style().rules[0].declarations[0].value,
+            AttributeValue::List(vec![
+                AttributeValue::Keyword("pulse".into()),
+                AttributeValue::Length(1.5, Unit::S),
+                AttributeValue::Keyword("ease-in".into()),
+                AttributeValue::Keyword("infinite".into()),
+            ])
+        );
+        assert_eq!(
+            parse(file.to_string().as_bytes()).map(|f| f == file).ok(),
+            Some(true)
+        );
+        assert!(parse(b"style { keyframes a { 150% { } } }").is_err());
+        assert!(parse(b"style { keyframes a { middle { } } }").is_err());
+    }
+
+    #[test]
+    fn errors() {
+        assert!(parse(b"doc { Unknown { } }").is_err());
+        assert!(parse(b"doc { heading { level } }").is_err());
+        assert!(parse(b"doc { heading { ").is_err());
+        assert!(parse(b"wat").is_err());
+        assert!(parse(b"meta-data {\n language \"en_US\"\n}").is_err());
+        assert!(parse(b"meta-data {\n date \"2023-02-29\"\n}").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::HashMap,
+    sync::{OnceLock, RwLock},
+};
+
+use shared::types::uom::{lookup, Node, UcreError};
+
+/// Encode encodes a node and its descendants
+pub type Encode = fn(&dyn Node) -> Result<Vec<u8>, UcreError>;
+
+/// Decode decodes the bytes [Encode] returned back into a node
+pub type Decode = fn(&[u8]) -> Result<Box<dyn Node>, UcreError>;
+
+/// BytecodeHook encodes and decodes the nodes of a custom kind registered via
+/// [shared::types::uom::register]. uvm has no bytecode format of its own yet, the hooks are
+/// what it will call for custom kinds once it has one.
+#[derive(Debug, Clone, Copy)]
+pub struct BytecodeHook {
+    /// the name of the registered kind, e.g. `chart`
+    pub name: &'static str,
+    /// encodes the nodes of the kind
+    pub encode: Encode,
+    /// decodes the nodes of the kind
+    pub decode: Decode,
+}
+
+type Hooks = RwLock<HashMap<&'static str, BytecodeHook>>;
+
+fn hooks() -> &'static Hooks {
+    static HOOKS: OnceLock<Hooks> = OnceLock::new();
+    HOOKS.get_or_init(Default::default)
+}
+
+/// register makes uvm encode and decode the nodes of the custom kind hook.name via hook. Errors
+/// if the kind is not registered or already has a hook.
+pub fn register(hook: BytecodeHook) -> Result<(), UcreError> {
+    if lookup(hook.name).is_none() {
+        return Err(UcreError::validate(format!(
+            "bytecode: '{}' is not a registered node kind",
+            hook.name
+        )));
+    }
+    let mut hooks = hooks().write().expect("bytecode hooks lock poisoned");
+    if hooks.contains_key(hook.name) {
+        return Err(UcreError::validate(format!(
+            "bytecode: '{}' already has a bytecode hook",
+            hook.name
+        )));
+    }
+    hooks.insert(hook.name, hook);
+    Ok(())
+}
+
+/// the bytecode hook of the custom kind name
+pub fn hook(name: &str) -> Option<BytecodeHook> {
+    hooks()
+        .read()
+        .expect("bytecode hooks lock poisoned")
+        .get(name)
+        .copied()
+}
+
+#[cfg(test)]
+mod test {
+    use shared::types::uom::{self, nodes::Generic, Node, NodeKind, NodeType, UcreError};
+
+    use super::{hook, register, BytecodeHook};
+
+    fn gauge() -> Box<dyn Node> {
+        Box::new(Generic::new("gauge"))
+    }
+
+    fn encode(node: &dyn Node) -> Result<Vec<u8>, UcreError> {
+        Ok(node.text().unwrap_or_default().as_bytes().to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Box<dyn Node>, UcreError> {
+        let text = std::str::from_utf8(bytes).map_err(|e| UcreError::validate(e.to_string()))?;
+        let mut node = gauge();
+        node.set_text(text.into())?;
+        Ok(node)
+    }
+
+    #[test]
+    fn hooks() {
+        let gauge_hook = BytecodeHook {
+            name: "gauge",
+            encode,
+            decode,
+        };
+        assert!(register(gauge_hook).is_err());
+        assert!(hook("gauge").is_none());
+
+        uom::register(NodeType {
+            name: "gauge",
+            new: gauge,
+            parsed: None,
+        })
+        .expect("failed to register the kind");
+        register(gauge_hook).expect("failed to register the hook");
+        assert!(register(gauge_hook).is_err());
+
+        let mut node = gauge();
+        node.set_text("75%".into())
+            .expect("generic nodes hold text");
+        let hook = hook("gauge").expect("hook exists");
+        let decoded =
+            (hook.decode)(&(hook.encode)(node.as_ref()).expect("encode")).expect("decode");
+        assert_eq!(decoded.kind(), NodeKind::Custom("gauge".into()));
+        assert_eq!(decoded.text(), Some("75%"));
+    }
+}
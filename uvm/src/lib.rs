@@ -0,0 +1,4 @@
+/// bytecode provides hooks encoding the nodes of custom kinds
+mod bytecode;
+
+pub use bytecode::{hook, register, BytecodeHook, Decode, Encode};